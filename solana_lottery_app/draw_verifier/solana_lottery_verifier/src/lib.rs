@@ -0,0 +1,640 @@
+//! # Solana Lottery Draw Verifier
+//!
+//! Recomputes the winners of a finished `LotteryRound` from its stored `randomness_seed` and
+//! `participants`, using exactly the same keccak index derivation as the on-chain `conduct_draw`
+//! instruction, and reports whether the stored `main_winner` and `other_winners` match.
+//!
+//! Rounds can be fetched directly from an RPC node or loaded from a JSON dump (see [`RoundDump`]),
+//! so results can also be audited offline or long after the round was drawn.
+//!
+//! Once a round has been closed only its `RoundResult` remains on-chain. Its `participants_root` commits
+//! to the participant list, so [`ParticipantProof`]s built from a dump prove that a participant was at a
+//! given index, and [`verify_result_with_proofs`] checks the stored winners against the seed without
+//! the full list.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator, Space};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_lottery_contract::{keccak_hashv, participants_merkle_root, LegacyLotteryRound, LotteryConfig, LotteryRound, RoundResult};
+use solana_lottery_core::draw::{select_winner_indices, WINNER_COUNT};
+use solana_lottery_core::merkle::{leaf_hash, merkle_proof, verify_proof};
+use solana_lottery_core::seeds::{lottery_id_seed, round_id_seed, LOTTERY_CONFIG_SEED, LOTTERY_ROUND_SEED, ROUND_RESULT_SEED};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// A self-contained snapshot of the `LotteryRound` fields needed to verify a draw.
+///
+/// This is the format used by the offline mode. Public keys are encoded as base58 strings and the
+/// randomness seed as an array of 32 bytes, so a dump can be produced by any client and checked later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundDump {
+    /// The unique identifier of the round.
+    pub round_id: u64,
+    /// The total pot of the round in lamports (informational only, not part of the draw).
+    pub total_pot_lamports: u64,
+    /// All participants, in entry order. The order matters: winners are selected by index.
+    #[serde(with = "pubkey_vec_as_base58")]
+    pub participants: Vec<Pubkey>,
+    /// Whether winners have been drawn for this round (also true for rounds cancelled after their draw).
+    pub winners_drawn: bool,
+    /// The stored main winner, if any.
+    #[serde(with = "option_pubkey_as_base58")]
+    pub main_winner: Option<Pubkey>,
+    /// The stored other winners, in selection order.
+    #[serde(with = "pubkey_vec_as_base58")]
+    pub other_winners: Vec<Pubkey>,
+    /// The randomness seed stored by `conduct_draw`.
+    pub randomness_seed: Option<[u8; 32]>,
+    /// The Merkle root committed when sales closed, if they have (absent in legacy rounds and older dumps).
+    #[serde(default)]
+    pub participants_root: Option<[u8; 32]>,
+}
+
+impl From<&LotteryRound> for RoundDump {
+    fn from(round: &LotteryRound) -> Self {
+        RoundDump {
+            round_id: round.round_id,
+            total_pot_lamports: round.total_pot_lamports,
+            participants: round.participants.clone(),
+            // Winners are only ever stored by a draw, whatever status the round moved to afterwards.
+            winners_drawn: round.main_winner.is_some(),
+            main_winner: round.main_winner,
+            other_winners: round.other_winners.clone(),
+            randomness_seed: round.randomness_seed,
+            participants_root: (round.participants_root != [0u8; 32]).then_some(round.participants_root),
+        }
+    }
+}
+
+impl From<&LegacyLotteryRound> for RoundDump {
+    fn from(round: &LegacyLotteryRound) -> Self {
+        RoundDump {
+            round_id: round.round_id,
+            total_pot_lamports: round.total_pot_lamports,
+            participants: round.participants.clone(),
+            winners_drawn: round.winners_drawn,
+            main_winner: round.main_winner,
+            other_winners: round.other_winners.clone(),
+            randomness_seed: round.randomness_seed,
+            participants_root: None,
+        }
+    }
+}
+
+/// The outcome of verifying a single round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// The round that was verified.
+    pub round_id: u64,
+    /// Number of participants the draw was computed over.
+    pub participant_count: usize,
+    /// The main winner recomputed from the seed.
+    pub expected_main_winner: Pubkey,
+    /// The other winners recomputed from the seed, in selection order.
+    pub expected_other_winners: Vec<Pubkey>,
+    /// Whether the stored main winner equals the recomputed one.
+    pub main_winner_matches: bool,
+    /// Whether the stored other winners equal the recomputed ones, in the same order.
+    pub other_winners_match: bool,
+    /// Whether the stored participant Merkle root matches the participant list, if the round has one.
+    pub participants_root_matches: Option<bool>,
+}
+
+impl VerificationReport {
+    /// Returns `true` if every stored winner matches the recomputed result and the participant root (if any) matches.
+    pub fn is_valid(&self) -> bool {
+        self.main_winner_matches && self.other_winners_match && self.participants_root_matches != Some(false)
+    }
+}
+
+/// Proof that `participant` was at `index` of the participant list of round `round_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParticipantProof {
+    /// The round the proof refers to.
+    pub round_id: u64,
+    /// Position of the participant in entry order.
+    pub index: u64,
+    /// The participant's public key.
+    #[serde(with = "pubkey_as_base58")]
+    pub participant: Pubkey,
+    /// Number of participants in the round.
+    pub participant_count: u64,
+    /// The Merkle root the proof was built for.
+    pub participants_root: [u8; 32],
+    /// Sibling hashes from the leaf level up.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// The outcome of checking a closed round's `RoundResult` with participant proofs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultVerification {
+    /// The round that was verified.
+    pub round_id: u64,
+    /// The winner indices recomputed from the stored seed and participant count, main winner first.
+    pub expected_indices: Vec<usize>,
+    /// For each winner slot, whether a valid proof places the stored winner at the expected index.
+    pub winners_proven: Vec<bool>,
+    /// Indices of supplied proofs that do not verify against the stored root.
+    pub invalid_proofs: Vec<u64>,
+}
+
+impl ResultVerification {
+    /// Returns `true` if every winner is proven and every supplied proof is valid.
+    pub fn is_valid(&self) -> bool {
+        self.winners_proven.iter().all(|proven| *proven) && self.invalid_proofs.is_empty()
+    }
+}
+
+/// Derives the `LotteryConfig` PDA of lottery `lottery_id` (seeds = ["lottery_config", lottery_id as u64 LE]).
+pub fn find_lottery_config_pda(program_id: &Pubkey, lottery_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOTTERY_CONFIG_SEED, &lottery_id_seed(lottery_id)], program_id)
+}
+
+/// Derives the `LotteryRound` PDA for `round_id` of the lottery at `lottery_config`
+/// (seeds = ["lottery_round", lottery_config, round_id as u64 LE]).
+pub fn find_lottery_round_pda(program_id: &Pubkey, lottery_config: &Pubkey, round_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOTTERY_ROUND_SEED, lottery_config.as_ref(), &round_id_seed(round_id)], program_id)
+}
+
+/// Derives the address of a round of the legacy lottery that has not been through `migrate_round` yet
+/// (seeds = ["lottery_round", round_id as u64 LE]).
+pub fn find_legacy_lottery_round_pda(program_id: &Pubkey, round_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOTTERY_ROUND_SEED, &round_id_seed(round_id)], program_id)
+}
+
+/// Derives the `RoundResult` PDA for `round_id` of the lottery at `lottery_config`
+/// (seeds = ["round_result", lottery_config, round_id as u64 LE]).
+pub fn find_round_result_pda(program_id: &Pubkey, lottery_config: &Pubkey, round_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROUND_RESULT_SEED, lottery_config.as_ref(), &round_id_seed(round_id)], program_id)
+}
+
+/// Recomputes the winner indices for a draw with the same `solana_lottery_core` selection used by
+/// `conduct_draw`, so a draw the program would reject is also rejected here.
+pub fn recompute_winner_indices(randomness_seed: &[u8; 32], num_participants: usize) -> Result<[usize; WINNER_COUNT]> {
+    select_winner_indices(keccak_hashv, randomness_seed, num_participants)
+        .map_err(|e| anyhow!("Winner selection failed for {} participants: {:?}", num_participants, e))
+}
+
+/// Recomputes the winners of `dump` and compares them with the stored winners.
+///
+/// Returns an error if the round has not been drawn yet or is missing its seed; a mismatch between
+/// stored and recomputed winners is reported through [`VerificationReport`] rather than as an error.
+pub fn verify_round(dump: &RoundDump) -> Result<VerificationReport> {
+    if !dump.winners_drawn {
+        bail!("Round {} has not been drawn yet", dump.round_id);
+    }
+    let seed = dump
+        .randomness_seed
+        .ok_or_else(|| anyhow!("Round {} has no randomness seed", dump.round_id))?;
+
+    let indices = recompute_winner_indices(&seed, dump.participants.len())?;
+    let winners: Vec<Pubkey> = indices.iter().map(|&index| dump.participants[index]).collect();
+    let expected_main_winner = winners[0];
+    let expected_other_winners = winners[1..].to_vec();
+
+    let participants_root_matches = match dump.participants_root {
+        Some(root) => Some(participants_merkle_root(&dump.participants).map_err(|e| anyhow!("{}", e))? == root),
+        None => None,
+    };
+
+    Ok(VerificationReport {
+        round_id: dump.round_id,
+        participant_count: dump.participants.len(),
+        main_winner_matches: dump.main_winner == Some(expected_main_winner),
+        other_winners_match: dump.other_winners == expected_other_winners,
+        participants_root_matches,
+        expected_main_winner,
+        expected_other_winners,
+    })
+}
+
+/// Builds the inclusion proof for the participant at `index` of `dump`.
+pub fn participant_proof(dump: &RoundDump, index: usize) -> Result<ParticipantProof> {
+    let participant = *dump
+        .participants
+        .get(index)
+        .ok_or_else(|| anyhow!("Round {} has no participant at index {}", dump.round_id, index))?;
+    let leaves: Vec<[u8; 32]> = dump.participants.iter().map(|p| leaf_hash(&keccak_hashv, p.as_ref())).collect();
+    let proof = merkle_proof(keccak_hashv, &leaves, index).map_err(|e| anyhow!("Failed to build proof: {:?}", e))?;
+    Ok(ParticipantProof {
+        round_id: dump.round_id,
+        index: index as u64,
+        participant,
+        participant_count: dump.participants.len() as u64,
+        participants_root: participants_merkle_root(&dump.participants).map_err(|e| anyhow!("{}", e))?,
+        siblings: proof.as_slice().to_vec(),
+    })
+}
+
+/// Builds the inclusion proofs of the drawn winners of `dump`, main winner first.
+pub fn winner_proofs(dump: &RoundDump) -> Result<Vec<ParticipantProof>> {
+    let seed = dump
+        .randomness_seed
+        .ok_or_else(|| anyhow!("Round {} has no randomness seed", dump.round_id))?;
+    recompute_winner_indices(&seed, dump.participants.len())?
+        .iter()
+        .map(|&index| participant_proof(dump, index))
+        .collect()
+}
+
+/// Checks `proof` against a trusted `participants_root` and `participant_count`.
+pub fn verify_participant_proof(proof: &ParticipantProof, participants_root: &[u8; 32], participant_count: u64) -> bool {
+    verify_proof(
+        keccak_hashv,
+        participants_root,
+        &leaf_hash(&keccak_hashv, proof.participant.as_ref()),
+        proof.index,
+        participant_count,
+        &proof.siblings,
+    )
+}
+
+/// Checks the winners stored in a closed round's `RoundResult` using participant proofs.
+///
+/// The winner indices are recomputed from the stored seed and participant count; each stored winner must
+/// be proven to sit at its index under the stored `participants_root`.
+pub fn verify_result_with_proofs(result: &RoundResult, proofs: &[ParticipantProof]) -> Result<ResultVerification> {
+    let seed = result
+        .randomness_seed
+        .ok_or_else(|| anyhow!("Round {} was never drawn", result.round_id))?;
+    let expected_indices = recompute_winner_indices(&seed, result.participant_count as usize)?.to_vec();
+    let stored_winners: Vec<Pubkey> = result.main_winner.into_iter().chain(result.other_winners.iter().copied()).collect();
+
+    let participant_count = result.participant_count as u64;
+    let valid = |proof: &ParticipantProof| {
+        proof.round_id == result.round_id && verify_participant_proof(proof, &result.participants_root, participant_count)
+    };
+    let winners_proven = expected_indices
+        .iter()
+        .enumerate()
+        .map(|(slot, &index)| {
+            proofs.iter().any(|proof| {
+                proof.index == index as u64 && stored_winners.get(slot) == Some(&proof.participant) && valid(proof)
+            })
+        })
+        .collect();
+    let invalid_proofs = proofs.iter().filter(|proof| !valid(proof)).map(|proof| proof.index).collect();
+
+    Ok(ResultVerification { round_id: result.round_id, expected_indices, winners_proven, invalid_proofs })
+}
+
+/// Fetches and decodes the `LotteryRound` account for `round_id` of the lottery at `lottery_config` from the cluster
+/// behind `client`.
+///
+/// If `lottery_config` is the legacy lottery (see `migrate_config`), rounds that have not been through `migrate_round`
+/// yet are fetched from their legacy address and decoded with the legacy layout.
+pub fn fetch_round(client: &RpcClient, program_id: &Pubkey, lottery_config: &Pubkey, round_id: u64) -> Result<RoundDump> {
+    let (lottery_round_pda, _bump) = find_lottery_round_pda(program_id, lottery_config, round_id);
+    let fetched = client
+        .get_account_data(&lottery_round_pda)
+        .with_context(|| format!("Failed to fetch LotteryRound {} at {}", round_id, lottery_round_pda));
+    let data = match fetched {
+        Ok(data) => data,
+        Err(err) => return fetch_legacy_round(client, program_id, lottery_config, round_id).map_err(|_| err),
+    };
+    let round = LotteryRound::try_deserialize(&mut data.as_slice())
+        .map_err(|e| anyhow!("Failed to decode LotteryRound {}: {}", round_id, e))?;
+    Ok(RoundDump::from(&round))
+}
+
+/// Fetches and decodes an unmigrated round of the legacy lottery at `lottery_config`.
+fn fetch_legacy_round(client: &RpcClient, program_id: &Pubkey, lottery_config: &Pubkey, round_id: u64) -> Result<RoundDump> {
+    let config_data = client.get_account_data(lottery_config)?;
+    let config = LotteryConfig::try_deserialize(&mut config_data.as_slice())?;
+    if !config.is_legacy_lottery {
+        bail!("Lottery {} has no legacy rounds", lottery_config);
+    }
+    let (legacy_round_pda, _bump) = find_legacy_lottery_round_pda(program_id, round_id);
+    let data = client.get_account_data(&legacy_round_pda)?;
+    if data.len() != 8 + LegacyLotteryRound::INIT_SPACE || !data.starts_with(LotteryRound::DISCRIMINATOR) {
+        bail!("Account {} is not a legacy LotteryRound", legacy_round_pda);
+    }
+    let round = LegacyLotteryRound::deserialize(&mut &data[8..])
+        .map_err(|e| anyhow!("Failed to decode legacy LotteryRound {}: {}", round_id, e))?;
+    Ok(RoundDump::from(&round))
+}
+
+/// Fetches and decodes the `RoundResult` account of a closed round.
+pub fn fetch_round_result(client: &RpcClient, program_id: &Pubkey, lottery_config: &Pubkey, round_id: u64) -> Result<RoundResult> {
+    let (round_result_pda, _bump) = find_round_result_pda(program_id, lottery_config, round_id);
+    let data = client
+        .get_account_data(&round_result_pda)
+        .with_context(|| format!("Failed to fetch RoundResult {} at {}", round_id, round_result_pda))?;
+    RoundResult::try_deserialize(&mut data.as_slice()).map_err(|e| anyhow!("Failed to decode RoundResult {}: {}", round_id, e))
+}
+
+/// Loads a [`RoundDump`] from a JSON file.
+pub fn load_round_dump(path: &Path) -> Result<RoundDump> {
+    let mut contents = String::new();
+    File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .read_to_string(&mut contents)?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse round dump {}", path.display()))
+}
+
+/// Writes a [`RoundDump`] to a JSON file.
+pub fn save_round_dump(dump: &RoundDump, path: &Path) -> Result<()> {
+    save_json(dump, path)
+}
+
+/// Loads a list of [`ParticipantProof`]s from a JSON file.
+pub fn load_proofs(path: &Path) -> Result<Vec<ParticipantProof>> {
+    let mut contents = String::new();
+    File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .read_to_string(&mut contents)?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse proofs {}", path.display()))
+}
+
+/// Writes a list of [`ParticipantProof`]s to a JSON file.
+pub fn save_proofs(proofs: &[ParticipantProof], path: &Path) -> Result<()> {
+    save_json(&proofs, path)
+}
+
+fn save_json<T: Serialize>(value: &T, path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?
+        .write_all(json.as_bytes())?;
+    Ok(())
+}
+
+mod pubkey_as_base58 {
+    use anchor_lang::prelude::Pubkey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        Pubkey::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+mod option_pubkey_as_base58 {
+    use anchor_lang::prelude::Pubkey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &Option<Pubkey>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(pubkey) => serializer.serialize_some(&pubkey.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| Pubkey::from_str(&s).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+mod pubkey_vec_as_base58 {
+    use anchor_lang::prelude::Pubkey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(|pubkey| pubkey.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| Pubkey::from_str(s).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::keccak;
+    use solana_lottery_contract::{participants_hash, RoundStatus};
+
+    const SEED: [u8; 32] = [7u8; 32];
+
+    fn participants(count: u8) -> Vec<Pubkey> {
+        (1..=count).map(|byte| Pubkey::new_from_array([byte; 32])).collect()
+    }
+
+    /// A round of `count` participants drawn from [`SEED`], with the winners `conduct_draw` stores.
+    fn drawn_dump(count: u8) -> RoundDump {
+        let participants = participants(count);
+        let winners: Vec<Pubkey> =
+            recompute_winner_indices(&SEED, participants.len()).unwrap().iter().map(|&index| participants[index]).collect();
+        RoundDump {
+            round_id: 42,
+            total_pot_lamports: count as u64 * 100_000_000,
+            winners_drawn: true,
+            main_winner: Some(winners[0]),
+            other_winners: winners[1..].to_vec(),
+            randomness_seed: Some(SEED),
+            participants_root: Some(participants_merkle_root(&participants).unwrap()),
+            participants,
+        }
+    }
+
+    /// The `RoundResult` that `close_round` records for `dump`.
+    fn round_result(dump: &RoundDump) -> RoundResult {
+        RoundResult {
+            round_id: dump.round_id,
+            outcome: RoundStatus::Distributed,
+            total_pot_lamports: dump.total_pot_lamports,
+            participant_count: dump.participants.len() as u32,
+            participants_hash: participants_hash(&dump.participants),
+            participants_root: dump.participants_root.unwrap(),
+            main_winner: dump.main_winner,
+            other_winners: dump.other_winners.clone(),
+            randomness_seed: dump.randomness_seed,
+            closed_timestamp: 1_700_000_000,
+            bump: 255,
+            lottery_config: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn known_seed_selects_the_pinned_winners() {
+        // Pinned: if these change, past draws no longer verify.
+        assert_eq!(recompute_winner_indices(&SEED, 20).unwrap(), [13, 15, 11, 12, 9]);
+
+        // The main winner is the first candidate, keccak(seed || slot 0 LE || attempt 0 LE) modulo the participant count.
+        let hash = keccak::hashv(&[&SEED, &0u64.to_le_bytes(), &0u64.to_le_bytes()]).to_bytes();
+        let first_candidate = u64::from_le_bytes(hash[0..8].try_into().unwrap()) % 20;
+        assert_eq!(recompute_winner_indices(&SEED, 20).unwrap()[0], first_candidate as usize);
+
+        let dump = drawn_dump(20);
+        let report = verify_round(&dump).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.participant_count, 20);
+        assert_eq!(Some(report.expected_main_winner), dump.main_winner);
+        assert_eq!(report.expected_other_winners, dump.other_winners);
+        assert_eq!(report.participants_root_matches, Some(true));
+    }
+
+    #[test]
+    fn tampered_winners_are_reported_as_mismatches() {
+        let dump = drawn_dump(20);
+        let outsider = Pubkey::new_unique();
+
+        let mut wrong_main = dump.clone();
+        wrong_main.main_winner = Some(outsider);
+        let report = verify_round(&wrong_main).unwrap();
+        assert!(!report.main_winner_matches && report.other_winners_match && !report.is_valid());
+
+        let mut reordered = dump.clone();
+        reordered.other_winners.swap(0, 1);
+        let report = verify_round(&reordered).unwrap();
+        assert!(report.main_winner_matches && !report.other_winners_match && !report.is_valid());
+
+        let mut missing = dump.clone();
+        missing.main_winner = None;
+        assert!(!verify_round(&missing).unwrap().is_valid());
+
+        // Replacing a participant who did not win leaves the winners intact but breaks the committed root.
+        let mut replaced = dump.clone();
+        let is_winner = |p: &Pubkey| Some(*p) == dump.main_winner || dump.other_winners.contains(p);
+        let loser = dump.participants.iter().position(|p| !is_winner(p)).unwrap();
+        replaced.participants[loser] = outsider;
+        let report = verify_round(&replaced).unwrap();
+        assert!(report.main_winner_matches && report.other_winners_match);
+        assert_eq!(report.participants_root_matches, Some(false));
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn undrawn_rounds_and_missing_seeds_are_errors() {
+        let mut undrawn = drawn_dump(20);
+        undrawn.winners_drawn = false;
+        assert!(verify_round(&undrawn).is_err());
+
+        let mut seedless = drawn_dump(20);
+        seedless.randomness_seed = None;
+        assert!(verify_round(&seedless).is_err());
+
+        let mut too_few = drawn_dump(20);
+        too_few.participants.truncate(WINNER_COUNT - 1);
+        assert!(verify_round(&too_few).is_err());
+    }
+
+    #[test]
+    fn round_dumps_round_trip_through_json() {
+        let path = std::env::temp_dir().join(format!("solana_lottery_verifier_dump_{}.json", std::process::id()));
+        let dump = drawn_dump(20);
+        save_round_dump(&dump, &path).unwrap();
+        let loaded = load_round_dump(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), dump);
+
+        // Dumps written before participant roots existed still load.
+        let mut json = serde_json::to_value(&dump).unwrap();
+        json.as_object_mut().unwrap().remove("participants_root");
+        let legacy: RoundDump = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.participants_root, None);
+        assert!(verify_round(&legacy).unwrap().is_valid());
+    }
+
+    #[test]
+    fn every_participant_proof_verifies() {
+        for count in [5, 13, 20] {
+            let dump = drawn_dump(count);
+            let root = dump.participants_root.unwrap();
+            for index in 0..dump.participants.len() {
+                let proof = participant_proof(&dump, index).unwrap();
+                assert_eq!((proof.round_id, proof.participant), (dump.round_id, dump.participants[index]));
+                assert_eq!(proof.participants_root, root);
+                assert!(verify_participant_proof(&proof, &root, count as u64), "count={} index={}", count, index);
+            }
+        }
+        assert!(participant_proof(&drawn_dump(20), 20).is_err());
+    }
+
+    #[test]
+    fn proofs_with_a_wrong_index_or_participant_count_are_rejected() {
+        let dump = drawn_dump(20);
+        let root = dump.participants_root.unwrap();
+        let proof = participant_proof(&dump, 6).unwrap();
+
+        for index in [5, 7, 20] {
+            let moved = ParticipantProof { index, ..proof.clone() };
+            assert!(!verify_participant_proof(&moved, &root, 20), "index={}", index);
+        }
+        // The root does not commit to the count, which verifiers take from the `RoundResult`; a wrong count is
+        // rejected wherever it changes the proof's path, as it does for the last participant and for the tree depth.
+        let last = participant_proof(&dump, 19).unwrap();
+        for (proof, count) in [(&last, 19), (&last, 21), (&proof, 8), (&proof, 40)] {
+            assert!(!verify_participant_proof(proof, &root, count), "index={} count={}", proof.index, count);
+        }
+        let impostor = ParticipantProof { participant: dump.participants[7], ..proof.clone() };
+        assert!(!verify_participant_proof(&impostor, &root, 20));
+    }
+
+    #[test]
+    fn proofs_from_another_round_are_rejected() {
+        let dump = drawn_dump(20);
+        let result = round_result(&dump);
+
+        // A round with other participants has another root.
+        let mut other = drawn_dump(20);
+        other.round_id = 43;
+        other.participants[0] = Pubkey::new_unique();
+        other.participants_root = Some(participants_merkle_root(&other.participants).unwrap());
+        let foreign = participant_proof(&other, 3).unwrap();
+        assert!(!verify_participant_proof(&foreign, &result.participants_root, 20));
+
+        // Proofs are bound to their round even when the participant lists are the same.
+        let relabelled: Vec<ParticipantProof> =
+            winner_proofs(&dump).unwrap().into_iter().map(|proof| ParticipantProof { round_id: 43, ..proof }).collect();
+        let verification = verify_result_with_proofs(&result, &relabelled).unwrap();
+        assert_eq!(verification.winners_proven, vec![false; WINNER_COUNT]);
+        assert_eq!(verification.invalid_proofs.len(), WINNER_COUNT);
+        assert!(!verification.is_valid());
+    }
+
+    #[test]
+    fn round_results_verify_with_winner_proofs() {
+        let dump = drawn_dump(20);
+        let result = round_result(&dump);
+        let proofs = winner_proofs(&dump).unwrap();
+        let verification = verify_result_with_proofs(&result, &proofs).unwrap();
+        assert_eq!(verification.round_id, dump.round_id);
+        assert_eq!(verification.expected_indices, vec![13, 15, 11, 12, 9]);
+        assert!(verification.is_valid());
+
+        // A missing proof leaves its winner unproven.
+        let verification = verify_result_with_proofs(&result, &proofs[1..]).unwrap();
+        assert_eq!(verification.winners_proven, vec![false, true, true, true, true]);
+        assert!(!verification.is_valid());
+
+        let undrawn = RoundResult { randomness_seed: None, ..round_result(&dump) };
+        assert!(verify_result_with_proofs(&undrawn, &proofs).is_err());
+    }
+
+    #[test]
+    fn swapped_winners_in_a_round_result_are_rejected() {
+        let dump = drawn_dump(20);
+        let proofs = winner_proofs(&dump).unwrap();
+
+        let mut swapped = round_result(&dump);
+        swapped.other_winners.swap(0, 1);
+        let verification = verify_result_with_proofs(&swapped, &proofs).unwrap();
+        assert_eq!(verification.winners_proven, vec![true, false, false, true, true]);
+        assert!(verification.invalid_proofs.is_empty());
+        assert!(!verification.is_valid());
+
+        // A participant who did not win cannot be proven into a winner slot, even with a valid proof of their own.
+        let mut substituted = round_result(&dump);
+        substituted.main_winner = Some(dump.participants[0]);
+        let mut with_own_proof = proofs.clone();
+        with_own_proof.push(participant_proof(&dump, 0).unwrap());
+        let verification = verify_result_with_proofs(&substituted, &with_own_proof).unwrap();
+        assert_eq!(verification.winners_proven, vec![false, true, true, true, true]);
+        assert!(!verification.is_valid());
+    }
+}
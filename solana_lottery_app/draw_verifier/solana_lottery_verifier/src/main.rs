@@ -0,0 +1,151 @@
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_lottery_verifier::{
+    fetch_round, fetch_round_result, find_lottery_config_pda, load_proofs, load_round_dump, participant_proof, save_proofs,
+    save_round_dump, verify_result_with_proofs, verify_round, winner_proofs, ResultVerification, RoundDump,
+};
+use std::path::Path;
+use std::str::FromStr;
+
+const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+
+const USAGE: &str = "Usage:
+  solana_lottery_verifier verify <ROUND_ID> [--rpc-url <URL>] [--program-id <PUBKEY>] [--lottery-id <ID>]
+  solana_lottery_verifier verify-file <ROUND_JSON>
+  solana_lottery_verifier dump <ROUND_ID> <OUT_JSON> [--rpc-url <URL>] [--program-id <PUBKEY>] [--lottery-id <ID>]
+  solana_lottery_verifier prove <ROUND_JSON> <INDEX> <OUT_JSON>
+  solana_lottery_verifier prove-winners <ROUND_JSON> <OUT_JSON>
+  solana_lottery_verifier verify-result <ROUND_ID> <PROOFS_JSON> [--rpc-url <URL>] [--program-id <PUBKEY>] [--lottery-id <ID>]";
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (positional, rpc_url, program_id, lottery_id) = parse_args(&args)?;
+    let (lottery_config, _bump) = find_lottery_config_pda(&program_id, lottery_id);
+
+    match positional.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["verify", round_id] => {
+            let dump = fetch(&rpc_url, &program_id, &lottery_config, round_id)?;
+            report(&dump)
+        }
+        ["verify-file", path] => {
+            let dump = load_round_dump(Path::new(path))?;
+            println!("Loaded round {} from {}", dump.round_id, path);
+            report(&dump)
+        }
+        ["dump", round_id, path] => {
+            let dump = fetch(&rpc_url, &program_id, &lottery_config, round_id)?;
+            save_round_dump(&dump, Path::new(path))?;
+            println!("Round {} written to {}", dump.round_id, path);
+            Ok(())
+        }
+        ["prove", dump_path, index, path] => {
+            let dump = load_round_dump(Path::new(dump_path))?;
+            let index: usize = index.parse().map_err(|_| anyhow!("Invalid participant index: {}", index))?;
+            let proof = participant_proof(&dump, index)?;
+            save_proofs(&[proof], Path::new(path))?;
+            println!("Proof for participant {} of round {} written to {}", index, dump.round_id, path);
+            Ok(())
+        }
+        ["prove-winners", dump_path, path] => {
+            let dump = load_round_dump(Path::new(dump_path))?;
+            let proofs = winner_proofs(&dump)?;
+            save_proofs(&proofs, Path::new(path))?;
+            println!("Proofs for the {} winners of round {} written to {}", proofs.len(), dump.round_id, path);
+            Ok(())
+        }
+        ["verify-result", round_id, proofs_path] => {
+            let round_id: u64 = round_id.parse().map_err(|_| anyhow!("Invalid round ID: {}", round_id))?;
+            let proofs = load_proofs(Path::new(proofs_path))?;
+            let client = RpcClient::new(rpc_url.clone());
+            println!("Fetching result of round {} of lottery {} from {} (program {})", round_id, lottery_id, rpc_url, program_id);
+            let result = fetch_round_result(&client, &program_id, &lottery_config, round_id)?;
+            report_result(&verify_result_with_proofs(&result, &proofs)?)
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+/// Splits the command line into positional arguments and the optional `--rpc-url` / `--program-id` / `--lottery-id`
+/// flags. The lottery ID defaults to 0.
+fn parse_args(args: &[String]) -> Result<(Vec<String>, String, Pubkey, u64)> {
+    let mut positional = Vec::new();
+    let mut rpc_url = String::from(DEFAULT_RPC_URL);
+    let mut program_id = solana_lottery_contract::ID;
+    let mut lottery_id = 0;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--rpc-url" => {
+                rpc_url = iter.next().ok_or_else(|| anyhow!("--rpc-url requires a value"))?.clone();
+            }
+            "--program-id" => {
+                let value = iter.next().ok_or_else(|| anyhow!("--program-id requires a value"))?;
+                program_id = Pubkey::from_str(value)?;
+            }
+            "--lottery-id" => {
+                let value = iter.next().ok_or_else(|| anyhow!("--lottery-id requires a value"))?;
+                lottery_id = value.parse().map_err(|_| anyhow!("Invalid lottery ID: {}", value))?;
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+    Ok((positional, rpc_url, program_id, lottery_id))
+}
+
+fn fetch(rpc_url: &str, program_id: &Pubkey, lottery_config: &Pubkey, round_id: &str) -> Result<RoundDump> {
+    let round_id: u64 = round_id.parse().map_err(|_| anyhow!("Invalid round ID: {}", round_id))?;
+    let client = RpcClient::new(rpc_url.to_string());
+    println!("Fetching round {} of lottery {} from {} (program {})", round_id, lottery_config, rpc_url, program_id);
+    fetch_round(&client, program_id, lottery_config, round_id)
+}
+
+/// Verifies `dump`, prints the result and fails if any stored winner does not match.
+fn report(dump: &RoundDump) -> Result<()> {
+    let report = verify_round(dump)?;
+    println!("Round {}: {} participants", report.round_id, report.participant_count);
+    println!(
+        "Main winner:   expected {}, stored {} -> {}",
+        report.expected_main_winner,
+        dump.main_winner.map(|w| w.to_string()).unwrap_or_else(|| String::from("<none>")),
+        if report.main_winner_matches { "MATCH" } else { "MISMATCH" }
+    );
+    for (i, expected) in report.expected_other_winners.iter().enumerate() {
+        let stored = dump.other_winners.get(i);
+        println!(
+            "Other winner {}: expected {}, stored {} -> {}",
+            i + 1,
+            expected,
+            stored.map(|w| w.to_string()).unwrap_or_else(|| String::from("<none>")),
+            if stored == Some(expected) { "MATCH" } else { "MISMATCH" }
+        );
+    }
+
+    if let Some(matches) = report.participants_root_matches {
+        println!("Participants root: {}", if matches { "MATCH" } else { "MISMATCH" });
+    }
+
+    if !report.is_valid() {
+        bail!("Round {} FAILED verification: stored winners or participants root do not match", report.round_id);
+    }
+    println!("Round {} verified: stored winners match the seed.", report.round_id);
+    Ok(())
+}
+
+/// Prints the outcome of checking a `RoundResult` with proofs and fails unless every winner is proven.
+fn report_result(verification: &ResultVerification) -> Result<()> {
+    for (slot, (index, proven)) in verification.expected_indices.iter().zip(&verification.winners_proven).enumerate() {
+        let label = if slot == 0 { String::from("Main winner") } else { format!("Other winner {}", slot) };
+        println!("{}: index {} -> {}", label, index, if *proven { "PROVEN" } else { "NOT PROVEN" });
+    }
+    for index in &verification.invalid_proofs {
+        println!("Proof for index {} does not match the stored participants root", index);
+    }
+
+    if !verification.is_valid() {
+        bail!("Round {} FAILED verification: winners could not be proven against the stored result", verification.round_id);
+    }
+    println!("Round {} verified: stored winners are proven at the indices derived from the seed.", verification.round_id);
+    Ok(())
+}
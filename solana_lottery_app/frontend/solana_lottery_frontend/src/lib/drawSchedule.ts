@@ -0,0 +1,49 @@
+// Client-side preview of calendar-aligned draw times.
+// Mirrors `solana_lottery_core::schedule`, which the program uses to compute `draw_timestamp` on-chain,
+// so the UI can show upcoming draws before a round is started.
+
+export const SECONDS_PER_DAY = 86_400;
+export const SECONDS_PER_WEEK = 7 * SECONDS_PER_DAY;
+
+// Days from Monday to Thursday: the Unix epoch (1970-01-01) was a Thursday.
+const EPOCH_DAYS_AFTER_MONDAY = 3;
+
+/** Remainder with the sign of the divisor, like Rust's `rem_euclid`. */
+const remEuclid = (value: number, divisor: number) => ((value % divisor) + divisor) % divisor;
+
+/**
+ * Returns the offset into a weekly period of `weekday` (0 = Monday, ..., 6 = Sunday) at `hour:minute` UTC.
+ * Pass the result as `draw_period_offset_seconds` to `set_draw_calendar` together with `SECONDS_PER_WEEK`.
+ */
+export function weeklyOffsetSeconds(weekday: number, hour: number, minute: number): number {
+    if (weekday < 0 || weekday > 6 || hour < 0 || hour > 23 || minute < 0 || minute > 59) {
+        throw new Error(`Invalid weekly draw time: weekday ${weekday}, ${hour}:${minute}`);
+    }
+    return remEuclid(weekday - EPOCH_DAYS_AFTER_MONDAY, 7) * SECONDS_PER_DAY + hour * 3_600 + minute * 60;
+}
+
+/**
+ * Returns the first calendar slot at or after `earliestSeconds` (Unix seconds).
+ * A `periodSeconds` of 0 means no calendar is configured, so `earliestSeconds` itself is returned.
+ */
+export function nextDrawTimestamp(earliestSeconds: number, periodSeconds: number, offsetSeconds: number): number {
+    if (periodSeconds === 0) return earliestSeconds;
+    return earliestSeconds + remEuclid(offsetSeconds - remEuclid(earliestSeconds, periodSeconds), periodSeconds);
+}
+
+/**
+ * Previews the draw times of the next `count` rounds, assuming each is started at `nowSeconds` or later
+ * and runs for at least `minDurationSeconds`, as `start_new_lottery_round` / `start_scheduled_round` do.
+ * Without a calendar only the first draw time is known, so a single timestamp is returned.
+ */
+export function previewDrawTimestamps(
+    nowSeconds: number,
+    minDurationSeconds: number,
+    periodSeconds: number,
+    offsetSeconds: number,
+    count: number,
+): number[] {
+    const first = nextDrawTimestamp(nowSeconds + minDurationSeconds, periodSeconds, offsetSeconds);
+    if (periodSeconds === 0) return count > 0 ? [first] : [];
+    return Array.from({ length: count }, (_, n) => first + n * periodSeconds);
+}
@@ -6,6 +6,7 @@ import { Connection, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY, SYSVAR_CLOCK_
 import idl from "@/idl/solana_lottery_contract.json"; // Import the Interface Definition Language (IDL) JSON file for the smart contract.
                                                  // The `@/` alias typically points to the `src` directory in Next.js projects.
                                                  // Ensure this IDL file is correctly placed and accessible.
+import { previewDrawTimestamps } from "@/lib/drawSchedule"; // Mirrors the on-chain draw calendar computation.
 
 // --- Constants ---
 
@@ -31,6 +32,14 @@ export interface LotteryConfigData {
     isPaused: boolean;            // Flag indicating if the lottery is currently paused.
     currentLotteryRoundId: BN;    // The ID of the current or most recently concluded lottery round (as a BigNumber).
     bump: number;                 // The bump seed used for PDA derivation of this account.
+    roundDurationSeconds: BN;     // Time from a scheduled round's start to its draw, in seconds.
+    drawPeriodSeconds: BN;        // Length of the draw calendar period in seconds (0 = draws not aligned to a calendar).
+    drawPeriodOffsetSeconds: BN;  // Draw time within each calendar period, counted from the Unix epoch.
+    lotteryId: BN;                // The identifier of this lottery among all lotteries run by the program.
+    platformFeeBps: number;       // Basis points of every distributed pot paid to the platform.
+    devFeeBps: number;            // Basis points of every distributed pot paid to the dev fee receiver.
+    mainWinnerBps: number;        // Basis points of every distributed pot paid to the main winner; the other winners share the rest.
+    metadataUri: string;          // URI of the lottery's branding metadata (empty if none).
 }
 
 /**
@@ -38,19 +47,51 @@ export interface LotteryConfigData {
  */
 export interface LotteryRoundData {
     roundId: BN;                  // The unique identifier for this lottery round (as a BigNumber).
-    // lotteryConfig: PublicKey; // Note: The Rust struct might not store a direct reference back to config PDA if derivable.
-                                // If it does, uncomment and ensure type matches. For now, assuming it's not directly stored.
+    lotteryConfig: PublicKey;     // The `LotteryConfig` PDA of the lottery this round belongs to.
     isActive: boolean;            // Flag indicating if the lottery round is currently active (accepting entries).
     drawTimestamp: BN;            // Unix timestamp when the draw will occur (as a BigNumber).
     totalPotLamports: BN;         // Total lamports collected from ticket sales for this round (as a BigNumber).
     participants: PublicKey[];    // Array of public keys of participants in this round.
     winnersDrawn: boolean;        // Flag indicating if winners for this round have been drawn.
     prizesDistributed: boolean;   // Flag indicating if prizes for this round have been distributed.
+    isSoldOut: boolean;           // Flag indicating the round reached its participant cap; it is drawn without waiting for `drawTimestamp`.
     mainWinner?: PublicKey | null; // Public key of the main winner (optional, null if not drawn).
     otherWinners: PublicKey[];    // Array of public keys of other winners.
     randomnessSeed?: BN | null;    // Pseudo-random seed used for the draw (optional, null if not generated, BN might need to be [u8;32]).
                                   // Consider if BN is the correct type for a 32-byte array or if it should be `Uint8Array` or `number[]`.
     bump: number;                 // The bump seed used for PDA derivation of this account.
+    entryFeeLamports: BN;         // The ticket price of this round, fixed when it started (may differ from the config's current fee).
+    platformFeeBps: number;       // Payout split of this round in basis points, fixed when it started.
+    devFeeBps: number;
+    mainWinnerBps: number;
+}
+
+/**
+ * Represents the data structure of the `LotteryStats` account on-chain: the lottery's lifetime statistics.
+ */
+export interface LotteryStatsData {
+    lotteryConfig: PublicKey;           // The `LotteryConfig` PDA of the lottery these statistics describe.
+    totalRoundsDrawn: BN;               // Rounds drawn so far.
+    totalTicketsSold: BN;               // Tickets sold so far, including those of cancelled rounds.
+    totalPaidToWinnersLamports: BN;     // Prizes paid to winners, including treasury top-ups, in lamports.
+    totalDevFeesLamports: BN;           // Dev fees credited to the fee vault, in lamports.
+    largestJackpotLamports: BN;         // The largest main prize paid so far, in lamports.
+    largestJackpotRoundId: BN;          // The round that paid the largest jackpot (0 if none yet).
+    bump: number;                       // The bump seed used for PDA derivation of this account.
+}
+
+/**
+ * Represents the data structure of the `PlayerProfile` account on-chain: one wallet's history and preferences.
+ */
+export interface PlayerProfileData {
+    player: PublicKey;                          // The wallet this profile belongs to.
+    roundsEntered: BN;                          // Rounds the player entered, in any lottery.
+    ticketsBought: BN;                          // Tickets the player bought (one per entry).
+    lifetimeWinningsLamports: BN;               // Prizes paid to the player, in lamports.
+    lastLotteryConfig: PublicKey;               // The `LotteryConfig` PDA of the lottery the player entered last.
+    lastRoundId: BN;                            // The round of that lottery the player entered last.
+    preferredPayoutAddress?: PublicKey | null;  // Where prizes are paid (null = the player's wallet).
+    bump: number;                               // The bump seed used for PDA derivation of this account.
 }
 
 // --- Custom Hook ---
@@ -63,8 +104,10 @@ export interface LotteryRoundData {
  * - Fetching data from `LotteryConfig` and `LotteryRound` accounts.
  * - Sending transactions to enter the lottery.
  * - (Placeholder) Fetching past lottery results.
+ * One program can run several lotteries side by side; the hook talks to the one identified by `lotteryId`.
+ * @param {number} [lotteryId=0] - The ID of the lottery (e.g. daily, weekly, VIP) whose config and rounds are used.
  */
-export function useSolanaLottery() {
+export function useSolanaLottery(lotteryId: number = 0) {
     const { connection } = useConnection(); // Hook to get the current Solana connection object.
     const wallet = useWallet();             // Hook to get the connected wallet's state and methods.
 
@@ -109,6 +152,47 @@ export function useSolanaLottery() {
         return new Program(typedIdl, PROGRAM_ID, provider);
     };
 
+    /**
+     * Derives the `LotteryConfig` PDA of this hook's lottery.
+     * Seeds: b"lottery_config" and the lottery ID (as little-endian bytes).
+     */
+    const findLotteryConfigPDA = (programId: PublicKey): PublicKey =>
+        PublicKey.findProgramAddressSync(
+            [Buffer.from("lottery_config"), new BN(lotteryId).toArrayLike(Buffer, "le", 8)],
+            programId
+        )[0];
+
+    /**
+     * Derives the `LotteryRound` PDA of round `roundId` of the lottery at `lotteryConfigPDA`.
+     * Seeds: b"lottery_round", the `LotteryConfig` PDA and the round ID (as little-endian bytes).
+     */
+    const findLotteryRoundPDA = (programId: PublicKey, lotteryConfigPDA: PublicKey, roundId: BN): PublicKey =>
+        PublicKey.findProgramAddressSync(
+            [Buffer.from("lottery_round"), lotteryConfigPDA.toBuffer(), roundId.toArrayLike(Buffer, "le", 8)],
+            programId
+        )[0];
+
+    /**
+     * Derives the prize vault PDA of the round at `lotteryRoundPDA`. The vault holds the round's pot.
+     * Seeds: b"prize_vault" and the `LotteryRound` PDA.
+     */
+    const findPrizeVaultPDA = (programId: PublicKey, lotteryRoundPDA: PublicKey): PublicKey =>
+        PublicKey.findProgramAddressSync([Buffer.from("prize_vault"), lotteryRoundPDA.toBuffer()], programId)[0];
+
+    /**
+     * Derives the `LotteryStats` PDA of the lottery at `lotteryConfigPDA`.
+     * Seeds: b"lottery_stats" and the `LotteryConfig` PDA.
+     */
+    const findLotteryStatsPDA = (programId: PublicKey, lotteryConfigPDA: PublicKey): PublicKey =>
+        PublicKey.findProgramAddressSync([Buffer.from("lottery_stats"), lotteryConfigPDA.toBuffer()], programId)[0];
+
+    /**
+     * Derives the `PlayerProfile` PDA of the wallet `player`.
+     * Seeds: b"player_profile" and the wallet's public key.
+     */
+    const findPlayerProfilePDA = (programId: PublicKey, player: PublicKey): PublicKey =>
+        PublicKey.findProgramAddressSync([Buffer.from("player_profile"), player.toBuffer()], programId)[0];
+
     /**
      * Fetches the `LotteryConfig` account data from the blockchain.
      * @returns {Promise<LotteryConfigData | null>} The lottery configuration data, or null if an error occurs.
@@ -120,10 +204,7 @@ export function useSolanaLottery() {
         try {
             // Derive the Program Derived Address (PDA) for the LotteryConfig account.
             // The seeds must match those defined in the smart contract (`InitializeConfig` accounts struct).
-            const [lotteryConfigPDA] = PublicKey.findProgramAddressSync(
-                [Buffer.from("lottery_config")], // Seed: b"lottery_config"
-                program.programId
-            );
+            const lotteryConfigPDA = findLotteryConfigPDA(program.programId);
             console.log("Fetching LotteryConfig from PDA:", lotteryConfigPDA.toBase58());
             const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
             return configAccount as LotteryConfigData; // Cast to the defined interface.
@@ -155,11 +236,7 @@ export function useSolanaLottery() {
             const currentLotteryRoundId = config.currentLotteryRoundId;
             
             // Derive the PDA for the current LotteryRound account.
-            // Seeds: b"lottery_round" and the current_lottery_round_id (as little-endian bytes).
-            const [lotteryRoundPDA] = PublicKey.findProgramAddressSync(
-                [Buffer.from("lottery_round"), currentLotteryRoundId.toArrayLike(Buffer, "le", 8)],
-                program.programId
-            );
+            const lotteryRoundPDA = findLotteryRoundPDA(program.programId, findLotteryConfigPDA(program.programId), currentLotteryRoundId);
             console.log(`Fetching LotteryRound PDA: ${lotteryRoundPDA.toBase58()} for round ID: ${currentLotteryRoundId.toString()}`);
             const roundAccount = await program.account.lotteryRound.fetch(lotteryRoundPDA);
             return roundAccount as LotteryRoundData; // Cast to the defined interface.
@@ -169,6 +246,95 @@ export function useSolanaLottery() {
         }
     };
 
+    /**
+     * Fetches the lottery's lifetime statistics from the `LotteryStats` account. Read-only; no wallet is needed.
+     * @returns {Promise<LotteryStatsData | null>} The statistics, or null if an error occurs.
+     */
+    const fetchLotteryStats = async (): Promise<LotteryStatsData | null> => {
+        const program = getProgram(true); // Use a read-only program instance.
+        if (!program) return null;
+
+        try {
+            const lotteryStatsPDA = findLotteryStatsPDA(program.programId, findLotteryConfigPDA(program.programId));
+            console.log("Fetching LotteryStats from PDA:", lotteryStatsPDA.toBase58());
+            const statsAccount = await program.account.lotteryStats.fetch(lotteryStatsPDA);
+            return statsAccount as LotteryStatsData; // Cast to the defined interface.
+        } catch (error) {
+            console.error("Error fetching lottery stats:", error);
+            return null;
+        }
+    };
+
+    /**
+     * Fetches the history and preferences of a player from their `PlayerProfile` account. Read-only.
+     * @param {PublicKey} [player] - The wallet to look up; defaults to the connected wallet.
+     * @returns {Promise<PlayerProfileData | null>} The profile, or null if the player has never entered or an error occurs.
+     */
+    const fetchPlayerProfile = async (player?: PublicKey): Promise<PlayerProfileData | null> => {
+        const program = getProgram(true); // Use a read-only program instance.
+        const playerKey = player ?? wallet.publicKey;
+        if (!program || !playerKey) return null;
+
+        try {
+            const playerProfilePDA = findPlayerProfilePDA(program.programId, playerKey);
+            // The profile is created by the player's first entry; until then there is nothing to show.
+            const profileAccount = await program.account.playerProfile.fetchNullable(playerProfilePDA);
+            return profileAccount as PlayerProfileData | null; // Cast to the defined interface.
+        } catch (error) {
+            console.error("Error fetching player profile:", error);
+            return null;
+        }
+    };
+
+    /**
+     * Sends a transaction that sets where the connected wallet's prizes are paid.
+     * The wallet must have entered a round before, which creates its profile.
+     * @param {PublicKey | null} payoutAddress - The account to pay prizes to, or null to pay them to the wallet.
+     * @returns {Promise<string | null>} The transaction signature if successful, or null if an error occurs.
+     */
+    const setPayoutAddress = async (payoutAddress: PublicKey | null) => {
+        const program = getProgram();
+        if (!program || !wallet.publicKey) {
+            console.error("Program or wallet is not available for `setPayoutAddress`.");
+            return null;
+        }
+
+        try {
+            const txSignature = await program.methods
+                .setPayoutAddress(payoutAddress)
+                .accounts({
+                    playerProfile: findPlayerProfilePDA(program.programId, wallet.publicKey),
+                    player: wallet.publicKey,
+                    payoutAccount: payoutAddress, // Checked to be able to receive prizes; omitted when clearing the address.
+                })
+                .rpc();
+            console.log("Set payout address transaction successful. Signature:", txSignature);
+            return txSignature;
+        } catch (error) {
+            console.error("Error setting payout address:", error);
+            return null;
+        }
+    };
+
+    /**
+     * Previews the upcoming draw times from the on-chain draw calendar, without sending a transaction.
+     * Uses the scheduled round duration as the minimum time from a round's start to its draw.
+     * @param {number} [count=5] - How many upcoming draw times to return.
+     * @returns {Promise<Date[]>} The upcoming draw times, or an empty array if the config cannot be fetched.
+     */
+    const previewUpcomingDrawTimes = async (count: number = 5): Promise<Date[]> => {
+        const config = await fetchLotteryConfig();
+        if (!config) return [];
+        const nowSeconds = Math.floor(Date.now() / 1000);
+        return previewDrawTimestamps(
+            nowSeconds,
+            config.roundDurationSeconds.toNumber(),
+            config.drawPeriodSeconds.toNumber(),
+            config.drawPeriodOffsetSeconds.toNumber(),
+            count,
+        ).map((timestamp) => new Date(timestamp * 1000));
+    };
+
     /**
      * Sends a transaction to enter the current active lottery round.
      * Requires a connected wallet with signing capabilities.
@@ -186,10 +352,7 @@ export function useSolanaLottery() {
 
         try {
             // Derive the LotteryConfig PDA.
-            const [lotteryConfigPDA] = PublicKey.findProgramAddressSync(
-                [Buffer.from("lottery_config")],
-                program.programId
-            );
+            const lotteryConfigPDA = findLotteryConfigPDA(program.programId);
 
             // Fetch the current lottery round ID from the config account.
             // This is necessary to derive the correct LotteryRound PDA.
@@ -203,10 +366,7 @@ export function useSolanaLottery() {
             }
             
             // Derive the current LotteryRound PDA.
-            const [lotteryRoundPDA] = PublicKey.findProgramAddressSync(
-                [Buffer.from("lottery_round"), currentLotteryRoundId.toArrayLike(Buffer, "le", 8)],
-                program.programId
-            );
+            const lotteryRoundPDA = findLotteryRoundPDA(program.programId, lotteryConfigPDA, currentLotteryRoundId);
 
             console.log("Attempting to enter lottery for round ID:", currentLotteryRoundId.toString());
             console.log("Using Lottery Config PDA:", lotteryConfigPDA.toBase58());
@@ -219,7 +379,10 @@ export function useSolanaLottery() {
                 .accounts({
                     lotteryConfig: lotteryConfigPDA,
                     lotteryRound: lotteryRoundPDA,
+                    prizeVault: findPrizeVaultPDA(program.programId, lotteryRoundPDA), // Receives the entry fee.
+                    lotteryStats: findLotteryStatsPDA(program.programId, lotteryConfigPDA), // Counts the ticket.
                     participant: wallet.publicKey,       // The connected wallet's public key is the participant.
+                    playerProfile: findPlayerProfilePDA(program.programId, wallet.publicKey), // Records the entry; created on the first one.
                     systemProgram: SystemProgram.programId, // Required for SOL transfer CPI.
                 })
                 .rpc(); // Send the transaction and await confirmation.
@@ -262,7 +425,11 @@ export function useSolanaLottery() {
         enterLottery, 
         fetchLotteryConfig, 
         fetchCurrentLotteryRound, 
+        fetchLotteryStats, 
+        fetchPlayerProfile, 
+        setPayoutAddress, 
         fetchPastLotteryResults, 
+        previewUpcomingDrawTimes, 
         getProgram, // Exposing getProgram and getProvider might be useful for advanced direct interactions.
         getProvider 
     };
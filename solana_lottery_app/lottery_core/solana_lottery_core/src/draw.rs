@@ -0,0 +1,203 @@
+//! Randomness seed derivation and winner selection.
+//!
+//! Every function takes a `hashv` function that must return the keccak-256 hash of the
+//! concatenation of its input slices (`solana_program::keccak::hashv` on-chain).
+
+use crate::CoreError;
+
+/// Number of winners selected per round: one main winner followed by the other winners.
+pub const WINNER_COUNT: usize = 5;
+
+/// Derives the round's randomness seed as `keccak(slot_hash || unix_timestamp LE || participant_count LE)`.
+pub fn randomness_seed<H>(hashv: H, slot_hash: &[u8], unix_timestamp: i64, participant_count: u64) -> [u8; 32]
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    hashv(&[slot_hash, &unix_timestamp.to_le_bytes(), &participant_count.to_le_bytes()])
+}
+
+/// Computes the candidate index for winner slot `slot` on attempt `attempt`.
+///
+/// The candidate is the first 8 bytes of `keccak(seed || slot LE || attempt LE)` read as a
+/// little-endian `u64`, modulo `num_participants`. `num_participants` must be non-zero.
+pub fn candidate_index<H>(hashv: &H, seed: &[u8; 32], slot: u64, attempt: u64, num_participants: usize) -> usize
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    let index_hash = hashv(&[seed, &slot.to_le_bytes(), &attempt.to_le_bytes()]);
+    let mut first_eight = [0u8; 8];
+    first_eight.copy_from_slice(&index_hash[0..8]);
+    (u64::from_le_bytes(first_eight) % num_participants as u64) as usize
+}
+
+/// Selects [`WINNER_COUNT`] unique participant indices from `seed`.
+///
+/// Slots are filled in order; when a candidate was already selected, the attempt counter is
+/// incremented and a new candidate is derived. A slot that still has no unique winner after more
+/// than `2 * num_participants` attempts fails with [`CoreError::WinnerSelectionFailed`]. With very
+/// few participants this limit is reachable for some seeds (the last slot of a five-participant draw
+/// has only one free index); the draw can then be retried with a fresh seed.
+pub fn select_winner_indices<H>(hashv: H, seed: &[u8; 32], num_participants: usize) -> Result<[usize; WINNER_COUNT], CoreError>
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    if num_participants < WINNER_COUNT {
+        return Err(CoreError::NotEnoughParticipants);
+    }
+    let max_attempts = (num_participants as u64).saturating_mul(2);
+
+    let mut selected = [0usize; WINNER_COUNT];
+    for slot in 0..WINNER_COUNT {
+        let mut attempt: u64 = 0;
+        loop {
+            let index = candidate_index(&hashv, seed, slot as u64, attempt, num_participants);
+            if !selected[..slot].contains(&index) {
+                selected[slot] = index;
+                break;
+            }
+            attempt += 1;
+            if attempt > max_attempts {
+                return Err(CoreError::WinnerSelectionFailed);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_keccak::{Hasher, Keccak};
+
+    fn keccak_hashv(data: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Keccak::v256();
+        for part in data {
+            hasher.update(part);
+        }
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        output
+    }
+
+    fn seed_from(byte: u8) -> [u8; 32] {
+        keccak_hashv(&[&[byte]])
+    }
+
+    #[test]
+    fn test_hasher_is_keccak_256() {
+        // Keccak-256 of the empty string (differs from SHA3-256).
+        let expected = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+            0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+        ];
+        assert_eq!(keccak_hashv(&[]), expected);
+    }
+
+    #[test]
+    fn randomness_seed_hashes_fields_in_order() {
+        let slot_hash = [9u8; 32];
+        let mut material = [0u8; 48];
+        material[..32].copy_from_slice(&slot_hash);
+        material[32..40].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        material[40..].copy_from_slice(&7u64.to_le_bytes());
+        assert_eq!(randomness_seed(keccak_hashv, &slot_hash, 1_700_000_000, 7), keccak_hashv(&[&material]));
+        assert_ne!(randomness_seed(keccak_hashv, &slot_hash, 1_700_000_000, 8), keccak_hashv(&[&material]));
+    }
+
+    #[test]
+    fn candidate_index_uses_first_eight_bytes_little_endian() {
+        let seed = seed_from(1);
+        let mut material = [0u8; 48];
+        material[..32].copy_from_slice(&seed);
+        material[32..40].copy_from_slice(&3u64.to_le_bytes());
+        material[40..].copy_from_slice(&2u64.to_le_bytes());
+        let hash = keccak_hashv(&[&material]);
+        let expected = u64::from_le_bytes(hash[0..8].try_into().unwrap()) % 1_000;
+        assert_eq!(candidate_index(&keccak_hashv, &seed, 3, 2, 1_000), expected as usize);
+    }
+
+    #[test]
+    fn rejects_fewer_participants_than_winners() {
+        for n in 0..WINNER_COUNT {
+            assert_eq!(select_winner_indices(keccak_hashv, &seed_from(0), n), Err(CoreError::NotEnoughParticipants));
+        }
+    }
+
+    #[test]
+    fn winners_are_unique_and_in_range() {
+        let mut successes = 0;
+        for n in WINNER_COUNT..=64 {
+            for byte in 0..16u8 {
+                let indices = match select_winner_indices(keccak_hashv, &seed_from(byte), n) {
+                    Ok(indices) => indices,
+                    Err(e) => {
+                        assert_eq!(e, CoreError::WinnerSelectionFailed);
+                        continue;
+                    }
+                };
+                successes += 1;
+                for (i, index) in indices.iter().enumerate() {
+                    assert!(*index < n);
+                    assert!(!indices[..i].contains(index), "duplicate winner for n={} seed={}", n, byte);
+                }
+            }
+        }
+        assert!(successes > 900);
+    }
+
+    #[test]
+    fn larger_rounds_never_hit_the_attempt_limit() {
+        for n in [50, 100, 500, 2_000] {
+            for byte in 0..64u8 {
+                assert!(select_winner_indices(keccak_hashv, &seed_from(byte), n).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn exactly_five_participants_all_win_or_the_draw_fails() {
+        let mut successes = 0;
+        for byte in 0..64u8 {
+            match select_winner_indices(keccak_hashv, &seed_from(byte), WINNER_COUNT) {
+                Ok(mut indices) => {
+                    successes += 1;
+                    indices.sort_unstable();
+                    assert_eq!(indices, [0, 1, 2, 3, 4]);
+                }
+                Err(e) => assert_eq!(e, CoreError::WinnerSelectionFailed),
+            }
+        }
+        assert!(successes > 0);
+    }
+
+    #[test]
+    fn selection_is_deterministic_and_seed_dependent() {
+        let a = select_winner_indices(keccak_hashv, &seed_from(1), 2_000).unwrap();
+        assert_eq!(a, select_winner_indices(keccak_hashv, &seed_from(1), 2_000).unwrap());
+        assert_ne!(a, select_winner_indices(keccak_hashv, &seed_from(2), 2_000).unwrap());
+    }
+
+    #[test]
+    fn first_slot_takes_the_first_candidate() {
+        let seed = seed_from(5);
+        let indices = select_winner_indices(keccak_hashv, &seed, 100).unwrap();
+        assert_eq!(indices[0], candidate_index(&keccak_hashv, &seed, 0, 0, 100));
+    }
+
+    #[test]
+    fn collisions_advance_the_attempt_counter() {
+        // Candidate index equals the attempt number for every slot: 0, then 1, 2, 3, 4.
+        let by_attempt = |data: &[&[u8]]| {
+            let mut out = [0u8; 32];
+            out[0..8].copy_from_slice(data[2]);
+            out
+        };
+        assert_eq!(select_winner_indices(by_attempt, &[0; 32], 10), Ok([0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn fails_when_no_unique_winner_is_found() {
+        let constant = |_: &[&[u8]]| [0u8; 32];
+        assert_eq!(select_winner_indices(constant, &[0; 32], 10), Err(CoreError::WinnerSelectionFailed));
+    }
+}
@@ -0,0 +1,40 @@
+//! # Solana Lottery Core
+//!
+//! Pure, `no_std` implementation of the lottery rules that must agree between the on-chain program
+//! and every off-chain client:
+//!
+//! * [`draw`] - randomness seed derivation and keccak-based winner index selection.
+//! * [`merkle`] - Merkle commitment of the participant list and inclusion proofs.
+//! * [`payout`] - checked split of the pot between the platform fee, the dev fee and the winners.
+//! * [`schedule`] - calendar alignment of draw times.
+//! * [`seeds`] - PDA seed constants and helpers.
+//!
+//! The crate has no dependencies. Hashing is injected by the caller as a `hashv`-style function
+//! (keccak-256 over the concatenation of the given slices), so the program can use the native
+//! `keccak` syscall while clients use any software implementation.
+#![no_std]
+
+pub mod draw;
+pub mod merkle;
+pub mod payout;
+pub mod schedule;
+pub mod seeds;
+
+/// Errors produced by the core lottery rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreError {
+    /// Fewer participants than winners to select.
+    NotEnoughParticipants,
+    /// A unique winner could not be found within the attempt limit.
+    WinnerSelectionFailed,
+    /// A checked arithmetic operation overflowed.
+    NumericOverflow,
+    /// The computed shares add up to more than the pot.
+    DistributionExceedsPot,
+    /// An index does not refer to an element of the list.
+    IndexOutOfRange,
+    /// A draw calendar period or offset is out of range.
+    InvalidCalendar,
+    /// The basis points of a pot split add up to more than the whole pot.
+    InvalidSplit,
+}
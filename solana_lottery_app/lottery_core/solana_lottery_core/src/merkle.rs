@@ -0,0 +1,337 @@
+//! Merkle commitment of a round's participant list.
+//!
+//! Leaves are the participants in entry order, hashed as `keccak(0x00 || pubkey)`; inner nodes are
+//! `keccak(0x01 || left || right)`. The prefixes keep leaves and inner nodes from being confused.
+//! Each level pairs adjacent nodes, and an odd node at the end of a level is promoted unchanged to
+//! the next level. The root of an empty list is all zeroes.
+//!
+//! [`MerkleBuilder`] computes the root in a single pass with `O(log n)` memory, so the program never
+//! has to hold a copy of the leaves. A proof for leaf `index` lists the sibling of every level where
+//! the node has one, bottom-up; which levels those are follows from `index` and the leaf count.
+//! The leaf count is not part of the root, so verifiers must take it from the same trusted record.
+
+use crate::CoreError;
+
+/// Domain separation prefix of leaf hashes.
+pub const LEAF_PREFIX: u8 = 0x00;
+/// Domain separation prefix of inner node hashes.
+pub const NODE_PREFIX: u8 = 0x01;
+/// Maximum number of levels above the leaves (enough for `2^32` leaves).
+pub const MAX_DEPTH: usize = 32;
+
+/// Hashes a participant public key into a leaf.
+pub fn leaf_hash<H>(hashv: &H, participant: &[u8]) -> [u8; 32]
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    hashv(&[&[LEAF_PREFIX], participant])
+}
+
+/// Hashes two child nodes into their parent.
+pub fn node_hash<H>(hashv: &H, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32]
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    hashv(&[&[NODE_PREFIX], left, right])
+}
+
+/// Incrementally computes a Merkle root from leaves pushed in order.
+///
+/// Holds at most one pending subtree root per level, like the bits of a binary counter.
+pub struct MerkleBuilder<H> {
+    hashv: H,
+    pending: [[u8; 32]; MAX_DEPTH + 1],
+    leaf_count: u64,
+}
+
+impl<H> MerkleBuilder<H>
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    /// Creates an empty builder.
+    pub fn new(hashv: H) -> Self {
+        MerkleBuilder { hashv, pending: [[0u8; 32]; MAX_DEPTH + 1], leaf_count: 0 }
+    }
+
+    /// Number of leaves pushed so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.leaf_count
+    }
+
+    /// Appends an already hashed leaf.
+    pub fn push_leaf(&mut self, leaf: [u8; 32]) -> Result<(), CoreError> {
+        if self.leaf_count >= 1u64 << MAX_DEPTH {
+            return Err(CoreError::NumericOverflow);
+        }
+        let mut carry = leaf;
+        let mut level = 0;
+        // Every set bit of the count is a complete subtree waiting for its right sibling.
+        while self.leaf_count & (1 << level) != 0 {
+            carry = node_hash(&self.hashv, &self.pending[level], &carry);
+            level += 1;
+        }
+        self.pending[level] = carry;
+        self.leaf_count += 1;
+        Ok(())
+    }
+
+    /// Appends a participant public key.
+    pub fn push_participant(&mut self, participant: &[u8]) -> Result<(), CoreError> {
+        let leaf = leaf_hash(&self.hashv, participant);
+        self.push_leaf(leaf)
+    }
+
+    /// Returns the root of all leaves pushed so far.
+    pub fn root(&self) -> [u8; 32] {
+        // Fold the pending subtrees from the lowest level up; the lower ones are the promoted right edge.
+        let mut root: Option<[u8; 32]> = None;
+        for level in 0..=MAX_DEPTH {
+            if self.leaf_count & (1 << level) != 0 {
+                root = Some(match root {
+                    None => self.pending[level],
+                    Some(right) => node_hash(&self.hashv, &self.pending[level], &right),
+                });
+            }
+        }
+        root.unwrap_or([0u8; 32])
+    }
+}
+
+/// Computes the root over already hashed `leaves`.
+pub fn merkle_root<H>(hashv: H, leaves: &[[u8; 32]]) -> Result<[u8; 32], CoreError>
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    let mut builder = MerkleBuilder::new(hashv);
+    for leaf in leaves {
+        builder.push_leaf(*leaf)?;
+    }
+    Ok(builder.root())
+}
+
+/// A Merkle proof: the sibling hashes from the leaf level up. Only the first `len` entries are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Sibling hashes, bottom-up.
+    pub siblings: [[u8; 32]; MAX_DEPTH],
+    /// Number of siblings in the proof.
+    pub len: usize,
+}
+
+impl MerkleProof {
+    /// The siblings of the proof, bottom-up.
+    pub fn as_slice(&self) -> &[[u8; 32]] {
+        &self.siblings[..self.len]
+    }
+}
+
+/// Builds the proof that `leaves[index]` is part of the root over `leaves`.
+pub fn merkle_proof<H>(hashv: H, leaves: &[[u8; 32]], index: usize) -> Result<MerkleProof, CoreError>
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    if index >= leaves.len() {
+        return Err(CoreError::IndexOutOfRange);
+    }
+    let mut proof = MerkleProof { siblings: [[0u8; 32]; MAX_DEPTH], len: 0 };
+    let mut position = index;
+    let mut level_len = leaves.len();
+    let mut level = 0;
+    while level_len > 1 {
+        let sibling = position ^ 1;
+        if sibling < level_len {
+            if level >= MAX_DEPTH {
+                return Err(CoreError::NumericOverflow);
+            }
+            // The node at `sibling` on this level covers an aligned block of `2^level` leaves.
+            let start = sibling << level;
+            let end = core::cmp::min(start + (1 << level), leaves.len());
+            proof.siblings[proof.len] = merkle_root(&hashv, &leaves[start..end])?;
+            proof.len += 1;
+        }
+        position >>= 1;
+        level_len = level_len.div_ceil(2);
+        level += 1;
+    }
+    Ok(proof)
+}
+
+/// Checks that `leaf` is at `index` of a list of `leaf_count` leaves committed to by `root`.
+pub fn verify_proof<H>(hashv: H, root: &[u8; 32], leaf: &[u8; 32], index: u64, leaf_count: u64, siblings: &[[u8; 32]]) -> bool
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    if index >= leaf_count {
+        return false;
+    }
+    let mut node = *leaf;
+    let mut position = index;
+    let mut level_len = leaf_count;
+    let mut remaining = siblings.iter();
+    while level_len > 1 {
+        let sibling_position = position ^ 1;
+        if sibling_position < level_len {
+            let sibling = match remaining.next() {
+                Some(sibling) => sibling,
+                None => return false,
+            };
+            node = if position & 1 == 0 { node_hash(&hashv, &node, sibling) } else { node_hash(&hashv, sibling, &node) };
+        }
+        position >>= 1;
+        level_len = level_len.div_ceil(2);
+    }
+    remaining.next().is_none() && node == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_keccak::{Hasher, Keccak};
+
+    fn keccak_hashv(data: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Keccak::v256();
+        for part in data {
+            hasher.update(part);
+        }
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        output
+    }
+
+    fn leaves(n: usize) -> [[u8; 32]; 64] {
+        let mut out = [[0u8; 32]; 64];
+        for (i, leaf) in out.iter_mut().enumerate().take(n) {
+            *leaf = leaf_hash(&keccak_hashv, &[i as u8; 32]);
+        }
+        out
+    }
+
+    /// Level-by-level reference implementation of the tree shape described in the module docs.
+    fn reference_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+        if leaves.is_empty() {
+            return [0u8; 32];
+        }
+        let mut level = [[0u8; 32]; 64];
+        level[..leaves.len()].copy_from_slice(leaves);
+        let mut len = leaves.len();
+        while len > 1 {
+            let mut next = 0;
+            for i in (0..len).step_by(2) {
+                level[next] = if i + 1 < len { node_hash(&keccak_hashv, &level[i], &level[i + 1]) } else { level[i] };
+                next += 1;
+            }
+            len = next;
+        }
+        level[0]
+    }
+
+    #[test]
+    fn leaves_and_nodes_are_domain_separated() {
+        let key = [7u8; 32];
+        assert_eq!(leaf_hash(&keccak_hashv, &key), keccak_hashv(&[&[0x00], &key]));
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        assert_eq!(node_hash(&keccak_hashv, &a, &b), keccak_hashv(&[&[0x01], &a, &b]));
+        assert_ne!(node_hash(&keccak_hashv, &a, &b), node_hash(&keccak_hashv, &b, &a));
+    }
+
+    #[test]
+    fn empty_and_single_leaf_roots() {
+        assert_eq!(merkle_root(keccak_hashv, &[]), Ok([0u8; 32]));
+        let one = leaves(1);
+        assert_eq!(merkle_root(keccak_hashv, &one[..1]), Ok(one[0]));
+    }
+
+    #[test]
+    fn odd_nodes_are_promoted() {
+        let l = leaves(3);
+        let expected = node_hash(&keccak_hashv, &node_hash(&keccak_hashv, &l[0], &l[1]), &l[2]);
+        assert_eq!(merkle_root(keccak_hashv, &l[..3]), Ok(expected));
+    }
+
+    #[test]
+    fn builder_matches_reference_for_every_size() {
+        let l = leaves(64);
+        for n in 0..=64 {
+            assert_eq!(merkle_root(keccak_hashv, &l[..n]), Ok(reference_root(&l[..n])), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn builder_root_is_available_after_every_push() {
+        let l = leaves(20);
+        let mut builder = MerkleBuilder::new(keccak_hashv);
+        for n in 1..=20 {
+            builder.push_leaf(l[n - 1]).unwrap();
+            assert_eq!(builder.leaf_count(), n as u64);
+            assert_eq!(builder.root(), reference_root(&l[..n]));
+        }
+    }
+
+    #[test]
+    fn push_participant_hashes_the_key() {
+        let mut by_key = MerkleBuilder::new(keccak_hashv);
+        let mut by_leaf = MerkleBuilder::new(keccak_hashv);
+        by_key.push_participant(&[9u8; 32]).unwrap();
+        by_leaf.push_leaf(leaf_hash(&keccak_hashv, &[9u8; 32])).unwrap();
+        assert_eq!(by_key.root(), by_leaf.root());
+    }
+
+    #[test]
+    fn root_depends_on_entry_order() {
+        let l = leaves(4);
+        let swapped = [l[1], l[0], l[2], l[3]];
+        assert_ne!(merkle_root(keccak_hashv, &l[..4]), merkle_root(keccak_hashv, &swapped));
+    }
+
+    #[test]
+    fn every_proof_verifies_for_every_size() {
+        let l = leaves(64);
+        for n in 1..=64 {
+            let root = merkle_root(keccak_hashv, &l[..n]).unwrap();
+            for index in 0..n {
+                let proof = merkle_proof(keccak_hashv, &l[..n], index).unwrap();
+                assert!(proof.len <= 6);
+                assert!(
+                    verify_proof(keccak_hashv, &root, &l[index], index as u64, n as u64, proof.as_slice()),
+                    "n = {}, index = {}",
+                    n,
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_reject_wrong_leaf_index_count_or_root() {
+        let l = leaves(13);
+        let root = merkle_root(keccak_hashv, &l[..13]).unwrap();
+        let proof = merkle_proof(keccak_hashv, &l[..13], 5).unwrap();
+        let siblings = proof.as_slice();
+        assert!(verify_proof(keccak_hashv, &root, &l[5], 5, 13, siblings));
+        assert!(!verify_proof(keccak_hashv, &root, &l[6], 5, 13, siblings));
+        assert!(!verify_proof(keccak_hashv, &root, &l[5], 4, 13, siblings));
+        assert!(!verify_proof(keccak_hashv, &root, &l[5], 5, 6, siblings));
+        assert!(!verify_proof(keccak_hashv, &root, &l[5], 13, 13, siblings));
+        assert!(!verify_proof(keccak_hashv, &[0u8; 32], &l[5], 5, 13, siblings));
+        // Too few or too many siblings.
+        assert!(!verify_proof(keccak_hashv, &root, &l[5], 5, 13, &siblings[..siblings.len() - 1]));
+        let mut extended = [[0u8; 32]; 8];
+        extended[..siblings.len()].copy_from_slice(siblings);
+        assert!(!verify_proof(keccak_hashv, &root, &l[5], 5, 13, &extended[..siblings.len() + 1]));
+    }
+
+    #[test]
+    fn proof_of_a_missing_index_fails() {
+        let l = leaves(3);
+        assert_eq!(merkle_proof(keccak_hashv, &l[..3], 3), Err(CoreError::IndexOutOfRange));
+        assert_eq!(merkle_proof(keccak_hashv, &l[..0], 0), Err(CoreError::IndexOutOfRange));
+    }
+
+    #[test]
+    fn single_leaf_proof_is_empty() {
+        let l = leaves(1);
+        let proof = merkle_proof(keccak_hashv, &l[..1], 0).unwrap();
+        assert_eq!(proof.len, 0);
+        assert!(verify_proof(keccak_hashv, &l[0], &l[0], 0, 1, proof.as_slice()));
+    }
+}
@@ -0,0 +1,485 @@
+//! Checked percentage split of a round's pot.
+//!
+//! - 10% to the dev fee receiver
+//! - 50% to the main winner
+//! - 10% to each of the 4 other winners (total 40%)
+//!
+//! Shares are computed with integer division. [`split_pot`] and [`split_pot_bps`] leave the rounding remainder in
+//! the pot; [`split_pot_bps_exact`] gives it to the main winner so that the whole pot is paid out.
+//!
+//! Lotteries created through the platform factory choose their own split in basis points instead (see [`SplitBps`]):
+//! a platform fee, the creator's dev fee and the main winner's share, with the rest shared equally by the other winners.
+//!
+//! The dev fee accrues in a fee vault, from which it is withdrawn subject to an optional per-period limit (see
+//! [`withdrawal_allowance`]) and can be divided between several beneficiaries by weight (see [`split_by_weights`]).
+//!
+//! Keepers that crank the draw or the distribution can be paid a bounty out of the pot before it is split. A single
+//! bounty never takes more than [`MAX_CRANK_BOUNTY_BPS`] of the pot (see [`crank_bounty`]).
+//!
+//! The account escrowing a round's pot must hold exactly its rent-exempt minimum plus the pot it still owes; see
+//! [`reconcile_escrow`].
+
+use crate::CoreError;
+
+/// Percentage of the pot paid to the dev fee receiver.
+pub const DEV_FEE_PERCENT: u64 = 10;
+/// Percentage of the pot paid to the main winner.
+pub const MAIN_WINNER_PERCENT: u64 = 50;
+/// Percentage of the pot paid to each other winner.
+pub const OTHER_WINNER_PERCENT: u64 = 10;
+/// Number of other winners.
+pub const OTHER_WINNER_COUNT: u64 = 4;
+
+/// Denominator of basis-point amounts.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// Upper bound on the percentage part of a crank bounty, and on the whole bounty paid per crank (5% of the pot).
+pub const MAX_CRANK_BOUNTY_BPS: u16 = 500;
+/// Upper bound on the flat part and the cap of a crank bounty, in lamports (0.1 SOL).
+pub const MAX_CRANK_BOUNTY_LAMPORTS: u64 = 100_000_000;
+
+/// A split of the pot in basis points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitBps {
+    /// Basis points of the pot paid to the platform.
+    pub platform_fee_bps: u16,
+    /// Basis points of the pot paid to the dev fee receiver.
+    pub dev_fee_bps: u16,
+    /// Basis points of the pot paid to the main winner.
+    pub main_winner_bps: u16,
+}
+
+/// The fixed percentage split expressed in basis points, without a platform fee.
+pub const DEFAULT_SPLIT: SplitBps = SplitBps {
+    platform_fee_bps: 0,
+    dev_fee_bps: (DEV_FEE_PERCENT * 100) as u16,
+    main_winner_bps: (MAIN_WINNER_PERCENT * 100) as u16,
+};
+
+impl SplitBps {
+    /// Basis points paid to each other winner: whatever the fees and the main winner leave, shared equally.
+    pub fn other_winner_bps(&self) -> Result<u64, CoreError> {
+        let assigned = self.platform_fee_bps as u64 + self.dev_fee_bps as u64 + self.main_winner_bps as u64;
+        if assigned > BPS_DENOMINATOR {
+            return Err(CoreError::InvalidSplit);
+        }
+        Ok((BPS_DENOMINATOR - assigned) / OTHER_WINNER_COUNT)
+    }
+}
+
+/// The lamport amounts paid out of a pot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrizeSplit {
+    /// Amount paid to the platform.
+    pub platform_share: u64,
+    /// Amount paid to the dev fee receiver.
+    pub dev_share: u64,
+    /// Amount paid to the main winner.
+    pub main_winner_share: u64,
+    /// Amount paid to each of the other winners.
+    pub other_winner_share: u64,
+}
+
+impl PrizeSplit {
+    /// Total amount paid out by this split.
+    pub fn total(&self) -> Result<u64, CoreError> {
+        self.other_winner_share
+            .checked_mul(OTHER_WINNER_COUNT)
+            .and_then(|others| others.checked_add(self.dev_share))
+            .and_then(|sum| sum.checked_add(self.main_winner_share))
+            .and_then(|sum| sum.checked_add(self.platform_share))
+            .ok_or(CoreError::NumericOverflow)
+    }
+}
+
+/// Computes `amount * percent / 100` with checked arithmetic.
+pub fn percent_of(amount: u64, percent: u64) -> Result<u64, CoreError> {
+    amount
+        .checked_mul(percent)
+        .ok_or(CoreError::NumericOverflow)?
+        .checked_div(100)
+        .ok_or(CoreError::NumericOverflow)
+}
+
+/// Splits `total_pot` into the dev fee and the winner shares, and checks that the split never pays
+/// out more than the pot.
+pub fn split_pot(total_pot: u64) -> Result<PrizeSplit, CoreError> {
+    let split = PrizeSplit {
+        platform_share: 0,
+        dev_share: percent_of(total_pot, DEV_FEE_PERCENT)?,
+        main_winner_share: percent_of(total_pot, MAIN_WINNER_PERCENT)?,
+        other_winner_share: percent_of(total_pot, OTHER_WINNER_PERCENT)?,
+    };
+    if split.total()? > total_pot {
+        return Err(CoreError::DistributionExceedsPot);
+    }
+    Ok(split)
+}
+
+/// Computes `amount * bps / 10_000`, rounding down. Never overflows for `bps` up to `BPS_DENOMINATOR`.
+pub fn bps_of(amount: u64, bps: u64) -> Result<u64, CoreError> {
+    u64::try_from(amount as u128 * bps as u128 / BPS_DENOMINATOR as u128).map_err(|_| CoreError::NumericOverflow)
+}
+
+/// Splits `total_pot` according to `split`, and checks that the split never pays out more than the pot.
+pub fn split_pot_bps(total_pot: u64, split: &SplitBps) -> Result<PrizeSplit, CoreError> {
+    let other_winner_bps = split.other_winner_bps()?;
+    let prize_split = PrizeSplit {
+        platform_share: bps_of(total_pot, split.platform_fee_bps as u64)?,
+        dev_share: bps_of(total_pot, split.dev_fee_bps as u64)?,
+        main_winner_share: bps_of(total_pot, split.main_winner_bps as u64)?,
+        other_winner_share: bps_of(total_pot, other_winner_bps)?,
+    };
+    if prize_split.total()? > total_pot {
+        return Err(CoreError::DistributionExceedsPot);
+    }
+    Ok(prize_split)
+}
+
+/// Like [`split_pot_bps`], but adds the rounding remainder to the main winner's share so that the split pays out
+/// exactly `total_pot`.
+pub fn split_pot_bps_exact(total_pot: u64, split: &SplitBps) -> Result<PrizeSplit, CoreError> {
+    let mut prize_split = split_pot_bps(total_pot, split)?;
+    let remainder = total_pot.checked_sub(prize_split.total()?).ok_or(CoreError::DistributionExceedsPot)?;
+    prize_split.main_winner_share = prize_split.main_winner_share.checked_add(remainder).ok_or(CoreError::NumericOverflow)?;
+    Ok(prize_split)
+}
+
+/// Divides `amount` equally between `count` recipients and returns each share and the rounding remainder.
+/// With no recipients, the whole amount is the remainder.
+pub fn split_evenly(amount: u64, count: u64) -> (u64, u64) {
+    match count {
+        0 => (0, amount),
+        count => (amount / count, amount % count),
+    }
+}
+
+/// Computes `amount * numerator / denominator`, rounding down: the share of `amount` left when only `numerator` of
+/// `denominator` lamports remain. Fails on a zero denominator or if the result does not fit in a u64.
+pub fn pro_rata(amount: u64, numerator: u64, denominator: u64) -> Result<u64, CoreError> {
+    if denominator == 0 {
+        return Err(CoreError::NumericOverflow);
+    }
+    u64::try_from(amount as u128 * numerator as u128 / denominator as u128).map_err(|_| CoreError::NumericOverflow)
+}
+
+/// How the balance of an escrow account compares with what it must hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowBalance {
+    /// The balance is exactly the rent-exempt minimum plus the escrowed amount.
+    Exact,
+    /// The account holds this many lamports more than it owes, e.g. from a transfer nobody accounted for.
+    Surplus(u64),
+    /// The account holds this many lamports less than it owes.
+    Shortfall(u64),
+}
+
+/// Compares the `balance` of an escrow account with its `rent_exempt_minimum` plus the `escrowed` amount it owes.
+pub fn reconcile_escrow(balance: u64, rent_exempt_minimum: u64, escrowed: u64) -> Result<EscrowBalance, CoreError> {
+    let expected = rent_exempt_minimum.checked_add(escrowed).ok_or(CoreError::NumericOverflow)?;
+    Ok(match balance.cmp(&expected) {
+        core::cmp::Ordering::Equal => EscrowBalance::Exact,
+        core::cmp::Ordering::Greater => EscrowBalance::Surplus(balance - expected),
+        core::cmp::Ordering::Less => EscrowBalance::Shortfall(expected - balance),
+    })
+}
+
+/// Divides `amount` between beneficiaries weighted in basis points, writing the share of `weights_bps[i]` to
+/// `shares[i]`. The weights must add up to exactly `BPS_DENOMINATOR`. The rounding remainder is added to the first
+/// share, so the shares always add up to `amount`; it is also returned.
+pub fn split_by_weights(amount: u64, weights_bps: &[u16], shares: &mut [u64]) -> Result<u64, CoreError> {
+    let total_weight: u64 = weights_bps.iter().map(|&weight| weight as u64).sum();
+    if weights_bps.is_empty() || total_weight != BPS_DENOMINATOR {
+        return Err(CoreError::InvalidSplit);
+    }
+    if shares.len() != weights_bps.len() {
+        return Err(CoreError::IndexOutOfRange);
+    }
+    let mut paid: u64 = 0;
+    for (share, &weight) in shares.iter_mut().zip(weights_bps) {
+        *share = bps_of(amount, weight as u64)?;
+        paid = paid.checked_add(*share).ok_or(CoreError::NumericOverflow)?;
+    }
+    // Each share rounds down, so together they never exceed `amount`.
+    let dust = amount - paid;
+    shares[0] += dust;
+    Ok(dust)
+}
+
+/// Returns the start of the current withdrawal period and how many lamports may still be withdrawn in it.
+///
+/// A period starts with the first withdrawal after the previous period ended and lasts `period_seconds`; at most
+/// `limit_lamports` may be withdrawn in it. `withdrawn_in_period` is what was withdrawn since `period_start`. A zero
+/// limit or period means withdrawals are unlimited.
+pub fn withdrawal_allowance(
+    now: i64,
+    period_start: i64,
+    period_seconds: i64,
+    limit_lamports: u64,
+    withdrawn_in_period: u64,
+) -> (i64, u64) {
+    if limit_lamports == 0 || period_seconds <= 0 {
+        return (period_start, u64::MAX);
+    }
+    if now >= period_start.saturating_add(period_seconds) {
+        return (now, limit_lamports); // The previous period is over; a new one starts now.
+    }
+    (period_start, limit_lamports.saturating_sub(withdrawn_in_period))
+}
+
+/// Returns the most a single crank bounty may take out of `pot`: [`MAX_CRANK_BOUNTY_BPS`] of it, rounded down.
+pub fn max_crank_bounty(pot: u64) -> u64 {
+    // At most 5% of a u64, so the division always fits back into a u64.
+    (pot as u128 * MAX_CRANK_BOUNTY_BPS as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Computes the bounty paid to a keeper out of `pot`: `flat_lamports + pot * bps / 10_000`, capped at
+/// `cap_lamports` and never more than [`max_crank_bounty`] of the pot, whatever the configuration.
+pub fn crank_bounty(pot: u64, flat_lamports: u64, bps: u16, cap_lamports: u64) -> u64 {
+    // A u64 times a u16 always fits in a u128, and the result is bounded by the pot below anyway.
+    let variable = u64::try_from(pot as u128 * bps as u128 / BPS_DENOMINATOR as u128).unwrap_or(u64::MAX);
+    flat_lamports.saturating_add(variable).min(cap_lamports).min(max_crank_bounty(pot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentages_add_up_to_the_whole_pot() {
+        assert_eq!(DEV_FEE_PERCENT + MAIN_WINNER_PERCENT + OTHER_WINNER_PERCENT * OTHER_WINNER_COUNT, 100);
+    }
+
+    #[test]
+    fn percent_of_rounds_down() {
+        assert_eq!(percent_of(0, 10), Ok(0));
+        assert_eq!(percent_of(9, 10), Ok(0));
+        assert_eq!(percent_of(10, 10), Ok(1));
+        assert_eq!(percent_of(199, 50), Ok(99));
+        assert_eq!(percent_of(1_000, 100), Ok(1_000));
+    }
+
+    #[test]
+    fn percent_of_reports_overflow() {
+        assert_eq!(percent_of(u64::MAX, 2), Err(CoreError::NumericOverflow));
+        assert_eq!(percent_of(u64::MAX / 10 + 1, 10), Err(CoreError::NumericOverflow));
+        assert_eq!(percent_of(u64::MAX / 10, 10), Ok(u64::MAX / 10 * 10 / 100));
+    }
+
+    #[test]
+    fn split_of_an_even_pot_is_exact() {
+        let split = split_pot(500_000_000).unwrap();
+        assert_eq!(
+            split,
+            PrizeSplit { platform_share: 0, dev_share: 50_000_000, main_winner_share: 250_000_000, other_winner_share: 50_000_000 }
+        );
+        assert_eq!(split.total(), Ok(500_000_000));
+    }
+
+    #[test]
+    fn split_of_an_empty_pot_is_zero() {
+        let split = split_pot(0).unwrap();
+        assert_eq!(split, PrizeSplit { platform_share: 0, dev_share: 0, main_winner_share: 0, other_winner_share: 0 });
+        assert_eq!(split.total(), Ok(0));
+    }
+
+    #[test]
+    fn split_never_exceeds_the_pot() {
+        for pot in (0..10_000u64).chain([u64::MAX / 50 - 1, u64::MAX / 50]) {
+            let split = split_pot(pot).unwrap();
+            let total = split.total().unwrap();
+            assert!(total <= pot, "pot {} paid out {}", pot, total);
+            // Each share rounds down by less than one lamport, so at most 5 lamports remain.
+            assert!(pot - total < 6, "pot {} left {} lamports", pot, pot - total);
+        }
+    }
+
+    #[test]
+    fn split_of_a_pot_too_large_for_checked_math_overflows() {
+        assert_eq!(split_pot(u64::MAX / 50 + 1), Err(CoreError::NumericOverflow));
+        assert_eq!(split_pot(u64::MAX), Err(CoreError::NumericOverflow));
+    }
+
+    #[test]
+    fn total_reports_overflow() {
+        let split = PrizeSplit { platform_share: 0, dev_share: u64::MAX, main_winner_share: 1, other_winner_share: 0 };
+        assert_eq!(split.total(), Err(CoreError::NumericOverflow));
+        let split = PrizeSplit { platform_share: 0, dev_share: 0, main_winner_share: 0, other_winner_share: u64::MAX / 2 };
+        assert_eq!(split.total(), Err(CoreError::NumericOverflow));
+    }
+
+    #[test]
+    fn default_split_matches_the_percentage_split() {
+        assert_eq!(DEFAULT_SPLIT.other_winner_bps(), Ok(OTHER_WINNER_PERCENT * 100));
+        for pot in (0..10_000u64).chain([123_456_789, u64::MAX / 50]) {
+            assert_eq!(split_pot_bps(pot, &DEFAULT_SPLIT), split_pot(pot), "pot {}", pot);
+        }
+    }
+
+    #[test]
+    fn custom_split_gives_the_rest_to_the_other_winners() {
+        let split = SplitBps { platform_fee_bps: 250, dev_fee_bps: 750, main_winner_bps: 6_000 };
+        assert_eq!(split.other_winner_bps(), Ok(750));
+        let prize_split = split_pot_bps(1_000_000, &split).unwrap();
+        assert_eq!(
+            prize_split,
+            PrizeSplit { platform_share: 25_000, dev_share: 75_000, main_winner_share: 600_000, other_winner_share: 75_000 }
+        );
+        assert_eq!(prize_split.total(), Ok(1_000_000));
+    }
+
+    #[test]
+    fn split_over_the_whole_pot_is_rejected() {
+        let split = SplitBps { platform_fee_bps: 1_000, dev_fee_bps: 1_000, main_winner_bps: 8_001 };
+        assert_eq!(split.other_winner_bps(), Err(CoreError::InvalidSplit));
+        assert_eq!(split_pot_bps(1_000, &split), Err(CoreError::InvalidSplit));
+        let everything_to_the_main_winner = SplitBps { platform_fee_bps: 0, dev_fee_bps: 0, main_winner_bps: 10_000 };
+        assert_eq!(split_pot_bps(1_000, &everything_to_the_main_winner).unwrap().main_winner_share, 1_000);
+    }
+
+    #[test]
+    fn split_by_bps_never_exceeds_the_pot_and_handles_large_pots() {
+        let split = SplitBps { platform_fee_bps: 333, dev_fee_bps: 777, main_winner_bps: 4_999 };
+        for pot in (0..10_000u64).chain([u64::MAX - 1, u64::MAX]) {
+            let total = split_pot_bps(pot, &split).unwrap().total().unwrap();
+            assert!(total <= pot, "pot {} paid out {}", pot, total);
+        }
+    }
+
+    #[test]
+    fn exact_split_gives_the_remainder_to_the_main_winner() {
+        let split = SplitBps { platform_fee_bps: 333, dev_fee_bps: 777, main_winner_bps: 4_999 };
+        for pot in (0..10_000u64).chain([123_456_789, u64::MAX - 1, u64::MAX]) {
+            let rounded = split_pot_bps(pot, &split).unwrap();
+            let exact = split_pot_bps_exact(pot, &split).unwrap();
+            assert_eq!(exact.total(), Ok(pot), "pot {}", pot);
+            assert_eq!(exact.main_winner_share - rounded.main_winner_share, pot - rounded.total().unwrap(), "pot {}", pot);
+            assert_eq!((exact.platform_share, exact.dev_share, exact.other_winner_share),
+                       (rounded.platform_share, rounded.dev_share, rounded.other_winner_share));
+        }
+        assert_eq!(split_pot_bps_exact(1_000_000_000, &DEFAULT_SPLIT), split_pot(1_000_000_000));
+    }
+
+    #[test]
+    fn split_evenly_returns_the_remainder() {
+        assert_eq!(split_evenly(100, 3), (33, 1));
+        assert_eq!(split_evenly(500_000_000, 5), (100_000_000, 0));
+        assert_eq!(split_evenly(7, 0), (0, 7));
+        assert_eq!(split_evenly(0, 4), (0, 0));
+    }
+
+    #[test]
+    fn reconcile_escrow_reports_surplus_and_shortfall() {
+        assert_eq!(reconcile_escrow(1_500, 1_000, 500), Ok(EscrowBalance::Exact));
+        assert_eq!(reconcile_escrow(1_507, 1_000, 500), Ok(EscrowBalance::Surplus(7)));
+        assert_eq!(reconcile_escrow(1_400, 1_000, 500), Ok(EscrowBalance::Shortfall(100)));
+        assert_eq!(reconcile_escrow(0, 1_000, 0), Ok(EscrowBalance::Shortfall(1_000)));
+        assert_eq!(reconcile_escrow(u64::MAX, u64::MAX, 1), Err(CoreError::NumericOverflow));
+    }
+
+    #[test]
+    fn split_by_weights_gives_the_dust_to_the_first_beneficiary() {
+        let mut shares = [0u64; 3];
+        assert_eq!(split_by_weights(100, &[3_333, 3_333, 3_334], &mut shares), Ok(1));
+        assert_eq!(shares, [34, 33, 33]);
+
+        assert_eq!(split_by_weights(50_000_000, &[5_000, 3_000, 2_000], &mut shares), Ok(0));
+        assert_eq!(shares, [25_000_000, 15_000_000, 10_000_000]);
+
+        let mut single = [0u64; 1];
+        assert_eq!(split_by_weights(u64::MAX, &[10_000], &mut single), Ok(0));
+        assert_eq!(single, [u64::MAX]);
+    }
+
+    #[test]
+    fn split_by_weights_always_pays_out_the_whole_amount() {
+        let weights = [1_234, 4_321, 999, 3_446];
+        let mut shares = [0u64; 4];
+        for amount in (0..5_000u64).chain([u64::MAX - 7, u64::MAX]) {
+            split_by_weights(amount, &weights, &mut shares).unwrap();
+            assert_eq!(shares.iter().map(|&share| share as u128).sum::<u128>(), amount as u128, "amount {}", amount);
+        }
+    }
+
+    #[test]
+    fn split_by_weights_rejects_invalid_tables() {
+        let mut shares = [0u64; 2];
+        assert_eq!(split_by_weights(100, &[5_000, 4_999], &mut shares), Err(CoreError::InvalidSplit));
+        assert_eq!(split_by_weights(100, &[5_000, 5_001], &mut shares), Err(CoreError::InvalidSplit));
+        assert_eq!(split_by_weights(100, &[], &mut []), Err(CoreError::InvalidSplit));
+        assert_eq!(split_by_weights(100, &[10_000], &mut shares), Err(CoreError::IndexOutOfRange));
+    }
+
+    #[test]
+    fn withdrawals_are_unlimited_without_a_limit() {
+        assert_eq!(withdrawal_allowance(1_000, 0, 0, 500, 400), (0, u64::MAX));
+        assert_eq!(withdrawal_allowance(1_000, 0, 86_400, 0, 400), (0, u64::MAX));
+    }
+
+    #[test]
+    fn withdrawals_are_limited_within_a_period() {
+        assert_eq!(withdrawal_allowance(1_000, 900, 86_400, 500, 0), (900, 500));
+        assert_eq!(withdrawal_allowance(1_000, 900, 86_400, 500, 400), (900, 100));
+        assert_eq!(withdrawal_allowance(1_000, 900, 86_400, 500, 500), (900, 0));
+        assert_eq!(withdrawal_allowance(87_299, 900, 86_400, 500, 500), (900, 0));
+    }
+
+    #[test]
+    fn a_new_period_starts_once_the_previous_one_is_over() {
+        assert_eq!(withdrawal_allowance(87_300, 900, 86_400, 500, 500), (87_300, 500));
+        assert_eq!(withdrawal_allowance(1_000_000, 0, 86_400, 500, 500), (1_000_000, 500));
+        assert_eq!(withdrawal_allowance(i64::MAX - 1, i64::MAX - 2, 86_400, 500, 500), (i64::MAX - 2, 0));
+    }
+
+    #[test]
+    fn pro_rata_scales_down_and_rounds_down() {
+        assert_eq!(pro_rata(1_000, 950, 1_000), Ok(950));
+        assert_eq!(pro_rata(999, 1, 3), Ok(333));
+        assert_eq!(pro_rata(1_000, 0, 1_000), Ok(0));
+        assert_eq!(pro_rata(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn pro_rata_rejects_a_zero_denominator_and_overflow() {
+        assert_eq!(pro_rata(1_000, 1, 0), Err(CoreError::NumericOverflow));
+        assert_eq!(pro_rata(u64::MAX, 2, 1), Err(CoreError::NumericOverflow));
+    }
+
+    #[test]
+    fn crank_bounty_is_zero_when_disabled() {
+        assert_eq!(crank_bounty(1_000_000, 0, 0, u64::MAX), 0);
+        assert_eq!(crank_bounty(1_000_000, 5_000, 100, 0), 0);
+    }
+
+    #[test]
+    fn crank_bounty_combines_flat_and_basis_points() {
+        assert_eq!(crank_bounty(1_000_000, 5_000, 0, u64::MAX), 5_000);
+        assert_eq!(crank_bounty(1_000_000, 0, 100, u64::MAX), 10_000);
+        assert_eq!(crank_bounty(1_000_000, 5_000, 100, u64::MAX), 15_000);
+        // Basis points round down.
+        assert_eq!(crank_bounty(9_999, 0, 1, u64::MAX), 0);
+    }
+
+    #[test]
+    fn crank_bounty_is_capped() {
+        assert_eq!(crank_bounty(1_000_000, 5_000, 100, 12_000), 12_000);
+        assert_eq!(crank_bounty(0, 5_000, 100, u64::MAX), 0);
+    }
+
+    #[test]
+    fn crank_bounty_never_exceeds_the_maximum_share_of_the_pot() {
+        assert_eq!(max_crank_bounty(1_000_000), 50_000);
+        assert_eq!(crank_bounty(1_000, 5_000, 0, u64::MAX), 50);
+        assert_eq!(crank_bounty(1_000_000, MAX_CRANK_BOUNTY_LAMPORTS, 0, MAX_CRANK_BOUNTY_LAMPORTS), 50_000);
+        assert_eq!(crank_bounty(1_000_000, u64::MAX, MAX_CRANK_BOUNTY_BPS, u64::MAX), 50_000);
+        assert_eq!(crank_bounty(19, u64::MAX, 0, u64::MAX), 0);
+    }
+
+    #[test]
+    fn crank_bounty_handles_extreme_values() {
+        assert_eq!(crank_bounty(u64::MAX, u64::MAX, u16::MAX, u64::MAX), max_crank_bounty(u64::MAX));
+        assert_eq!(
+            crank_bounty(u64::MAX, 0, MAX_CRANK_BOUNTY_BPS, u64::MAX),
+            ((u64::MAX as u128) * MAX_CRANK_BOUNTY_BPS as u128 / BPS_DENOMINATOR as u128) as u64
+        );
+    }
+}
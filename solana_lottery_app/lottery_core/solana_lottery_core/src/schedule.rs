@@ -0,0 +1,126 @@
+//! Calendar alignment of draw times.
+//!
+//! A draw calendar is a period and an offset into it: draws happen at every Unix timestamp `t` with
+//! `t mod period == offset`. A weekly period with [`weekly_offset`] expresses rules such as
+//! "every Friday 20:00 UTC"; a daily period with an offset of `hour * 3600` expresses "every day at
+//! that hour". All times are UTC.
+
+use crate::CoreError;
+
+/// Seconds in a day.
+pub const SECONDS_PER_DAY: i64 = 86_400;
+/// Seconds in a week.
+pub const SECONDS_PER_WEEK: i64 = 7 * SECONDS_PER_DAY;
+
+/// Days from Monday to Thursday: the Unix epoch (1970-01-01) was a Thursday.
+const EPOCH_DAYS_AFTER_MONDAY: i64 = 3;
+
+/// Returns the offset into a weekly period of `weekday` (0 = Monday, ..., 6 = Sunday) at `hour:minute` UTC.
+pub fn weekly_offset(weekday: u8, hour: u8, minute: u8) -> Result<i64, CoreError> {
+    if weekday > 6 || hour > 23 || minute > 59 {
+        return Err(CoreError::InvalidCalendar);
+    }
+    let days_after_epoch_weekday = (weekday as i64 - EPOCH_DAYS_AFTER_MONDAY).rem_euclid(7);
+    Ok(days_after_epoch_weekday * SECONDS_PER_DAY + hour as i64 * 3_600 + minute as i64 * 60)
+}
+
+/// Checks that `period_seconds` is positive and `offset_seconds` lies within it.
+pub fn validate_calendar(period_seconds: i64, offset_seconds: i64) -> Result<(), CoreError> {
+    if period_seconds <= 0 || offset_seconds < 0 || offset_seconds >= period_seconds {
+        return Err(CoreError::InvalidCalendar);
+    }
+    Ok(())
+}
+
+/// Returns the first calendar slot at or after `earliest`.
+pub fn next_aligned_timestamp(earliest: i64, period_seconds: i64, offset_seconds: i64) -> Result<i64, CoreError> {
+    validate_calendar(period_seconds, offset_seconds)?;
+    let wait = (offset_seconds - earliest.rem_euclid(period_seconds)).rem_euclid(period_seconds);
+    earliest.checked_add(wait).ok_or(CoreError::NumericOverflow)
+}
+
+/// Returns the calendar slots at or after `earliest` in order, for previewing upcoming draws.
+pub fn upcoming_aligned_timestamps(
+    earliest: i64,
+    period_seconds: i64,
+    offset_seconds: i64,
+) -> Result<impl Iterator<Item = i64>, CoreError> {
+    let first = next_aligned_timestamp(earliest, period_seconds, offset_seconds)?;
+    Ok((0i64..).map_while(move |n| n.checked_mul(period_seconds).and_then(|delta| first.checked_add(delta))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday 2025-05-16 20:00:00 UTC.
+    const FRIDAY_20_00: i64 = 1_747_425_600;
+
+    #[test]
+    fn weekly_offset_is_measured_from_the_epoch_thursday() {
+        assert_eq!(weekly_offset(3, 0, 0), Ok(0));
+        assert_eq!(weekly_offset(4, 20, 0), Ok(SECONDS_PER_DAY + 20 * 3_600));
+        assert_eq!(weekly_offset(0, 0, 0), Ok(4 * SECONDS_PER_DAY));
+        assert_eq!(weekly_offset(2, 23, 59), Ok(6 * SECONDS_PER_DAY + 23 * 3_600 + 59 * 60));
+        assert_eq!(FRIDAY_20_00.rem_euclid(SECONDS_PER_WEEK), weekly_offset(4, 20, 0).unwrap());
+    }
+
+    #[test]
+    fn weekly_offset_rejects_out_of_range_fields() {
+        assert_eq!(weekly_offset(7, 0, 0), Err(CoreError::InvalidCalendar));
+        assert_eq!(weekly_offset(0, 24, 0), Err(CoreError::InvalidCalendar));
+        assert_eq!(weekly_offset(0, 0, 60), Err(CoreError::InvalidCalendar));
+    }
+
+    #[test]
+    fn calendar_validation() {
+        assert!(validate_calendar(SECONDS_PER_WEEK, 0).is_ok());
+        assert!(validate_calendar(SECONDS_PER_WEEK, SECONDS_PER_WEEK - 1).is_ok());
+        assert_eq!(validate_calendar(0, 0), Err(CoreError::InvalidCalendar));
+        assert_eq!(validate_calendar(-SECONDS_PER_DAY, 0), Err(CoreError::InvalidCalendar));
+        assert_eq!(validate_calendar(SECONDS_PER_DAY, -1), Err(CoreError::InvalidCalendar));
+        assert_eq!(validate_calendar(SECONDS_PER_DAY, SECONDS_PER_DAY), Err(CoreError::InvalidCalendar));
+    }
+
+    #[test]
+    fn next_slot_is_aligned_and_not_before_earliest() {
+        let offset = weekly_offset(4, 20, 0).unwrap();
+        // Exactly on a slot.
+        assert_eq!(next_aligned_timestamp(FRIDAY_20_00, SECONDS_PER_WEEK, offset), Ok(FRIDAY_20_00));
+        // One second after a slot waits a full week.
+        assert_eq!(next_aligned_timestamp(FRIDAY_20_00 + 1, SECONDS_PER_WEEK, offset), Ok(FRIDAY_20_00 + SECONDS_PER_WEEK));
+        // Earlier in the same week.
+        assert_eq!(next_aligned_timestamp(FRIDAY_20_00 - 3 * SECONDS_PER_DAY, SECONDS_PER_WEEK, offset), Ok(FRIDAY_20_00));
+        for earliest in (FRIDAY_20_00 - SECONDS_PER_WEEK..FRIDAY_20_00 + SECONDS_PER_WEEK).step_by(3_607) {
+            let next = next_aligned_timestamp(earliest, SECONDS_PER_WEEK, offset).unwrap();
+            assert!(next >= earliest && next - earliest < SECONDS_PER_WEEK);
+            assert_eq!(next.rem_euclid(SECONDS_PER_WEEK), offset);
+        }
+    }
+
+    #[test]
+    fn next_slot_handles_negative_timestamps() {
+        assert_eq!(next_aligned_timestamp(-1, SECONDS_PER_DAY, 0), Ok(0));
+        assert_eq!(next_aligned_timestamp(-SECONDS_PER_DAY - 1, SECONDS_PER_DAY, 3_600), Ok(-SECONDS_PER_DAY + 3_600));
+    }
+
+    #[test]
+    fn next_slot_reports_overflow() {
+        assert_eq!(next_aligned_timestamp(i64::MAX, SECONDS_PER_DAY, 0), Err(CoreError::NumericOverflow));
+    }
+
+    #[test]
+    fn upcoming_slots_are_one_period_apart() {
+        let offset = weekly_offset(4, 20, 0).unwrap();
+        let mut slots = upcoming_aligned_timestamps(FRIDAY_20_00 - 60, SECONDS_PER_WEEK, offset).unwrap();
+        assert_eq!(slots.next(), Some(FRIDAY_20_00));
+        assert_eq!(slots.next(), Some(FRIDAY_20_00 + SECONDS_PER_WEEK));
+        assert_eq!(slots.next(), Some(FRIDAY_20_00 + 2 * SECONDS_PER_WEEK));
+    }
+
+    #[test]
+    fn upcoming_slots_stop_before_overflow() {
+        let last_slot = i64::MAX - i64::MAX.rem_euclid(SECONDS_PER_DAY);
+        assert_eq!(upcoming_aligned_timestamps(last_slot, SECONDS_PER_DAY, 0).unwrap().count(), 1);
+    }
+}
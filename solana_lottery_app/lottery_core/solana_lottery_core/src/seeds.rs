@@ -0,0 +1,76 @@
+//! PDA seed constants and helpers shared by the program and its clients.
+
+/// Prefix seed of every `LotteryConfig` PDA (seeds = ["lottery_config", lottery_id as u64 LE]).
+pub const LOTTERY_CONFIG_SEED: &[u8] = b"lottery_config";
+
+/// Prefix seed of every `LotteryRound` PDA (seeds = ["lottery_round", lottery_config, round_id as u64 LE]).
+pub const LOTTERY_ROUND_SEED: &[u8] = b"lottery_round";
+
+/// Prefix seed of every `RoundResult` PDA (seeds = ["round_result", lottery_config, round_id as u64 LE]).
+pub const ROUND_RESULT_SEED: &[u8] = b"round_result";
+
+/// Seed of the `PlatformConfig` PDA (seeds = ["platform_config"]).
+pub const PLATFORM_CONFIG_SEED: &[u8] = b"platform_config";
+
+/// Prefix seed of every `Treasury` PDA (seeds = ["treasury", lottery_config]).
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Prefix seed of every `FeeSplit` PDA (seeds = ["fee_split", lottery_config]).
+pub const FEE_SPLIT_SEED: &[u8] = b"fee_split";
+
+/// Prefix seed of every `FeeVault` PDA (seeds = ["fee_vault", lottery_config]).
+pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+
+/// Prefix seed of every round's prize vault PDA (seeds = ["prize_vault", lottery_round]). The vault is a System
+/// Program account that holds the round's pot apart from the `LotteryRound` data.
+pub const PRIZE_VAULT_SEED: &[u8] = b"prize_vault";
+
+/// Prefix seed of every `LotteryStats` PDA (seeds = ["lottery_stats", lottery_config]).
+pub const LOTTERY_STATS_SEED: &[u8] = b"lottery_stats";
+
+/// Prefix seed of every `PlayerProfile` PDA (seeds = ["player_profile", player wallet]).
+pub const PLAYER_PROFILE_SEED: &[u8] = b"player_profile";
+
+/// Encodes a lottery ID the way it is used in the `LotteryConfig` PDA seeds (8 bytes, little-endian).
+pub fn lottery_id_seed(lottery_id: u64) -> [u8; 8] {
+    lottery_id.to_le_bytes()
+}
+
+/// Encodes a round ID the way it is used in the `LotteryRound` and `RoundResult` PDA seeds (8 bytes, little-endian).
+pub fn round_id_seed(round_id: u64) -> [u8; 8] {
+    round_id.to_le_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_constants_match_program_literals() {
+        assert_eq!(LOTTERY_CONFIG_SEED, b"lottery_config");
+        assert_eq!(LOTTERY_ROUND_SEED, b"lottery_round");
+        assert_eq!(ROUND_RESULT_SEED, b"round_result");
+        assert_eq!(TREASURY_SEED, b"treasury");
+        assert_eq!(PLATFORM_CONFIG_SEED, b"platform_config");
+        assert_eq!(FEE_SPLIT_SEED, b"fee_split");
+        assert_eq!(FEE_VAULT_SEED, b"fee_vault");
+        assert_eq!(PRIZE_VAULT_SEED, b"prize_vault");
+        assert_eq!(LOTTERY_STATS_SEED, b"lottery_stats");
+        assert_eq!(PLAYER_PROFILE_SEED, b"player_profile");
+    }
+
+    #[test]
+    fn lottery_id_seed_is_little_endian() {
+        assert_eq!(lottery_id_seed(0), [0; 8]);
+        assert_eq!(lottery_id_seed(7), [7, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(lottery_id_seed(u64::MAX), [0xff; 8]);
+    }
+
+    #[test]
+    fn round_id_seed_is_little_endian() {
+        assert_eq!(round_id_seed(0), [0; 8]);
+        assert_eq!(round_id_seed(1), [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(round_id_seed(0x0102_0304_0506_0708), [8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(round_id_seed(u64::MAX), [0xff; 8]);
+    }
+}
@@ -12,6 +12,7 @@ use std::fs::File;
 use std::io::Read;
 // use serde_json::Value; // Unused import
 use anyhow::Result;
+use solana_lottery_core::seeds::{lottery_id_seed, FEE_VAULT_SEED, LOTTERY_CONFIG_SEED, LOTTERY_STATS_SEED, PLATFORM_CONFIG_SEED, TREASURY_SEED};
 
 fn main() -> Result<()> {
     // 1. Connect to Solana Devnet
@@ -32,11 +33,13 @@ fn main() -> Result<()> {
     let program_id = Pubkey::from_str("58Tp7nzzrUej7RTdWGSavSdGp1YHArexN19pYFnTABxv")?;
     println!("Lottery Program ID: {}", program_id);
 
-    // 4. Define LotteryConfig PDA
+    // 4. Define the lottery ID and its LotteryConfig PDA
+    let lottery_id: u64 = 0;
     let (lottery_config_pda, _bump_seed) = Pubkey::find_program_address(
-        &[b"lottery_config"],
+        &[LOTTERY_CONFIG_SEED, &lottery_id_seed(lottery_id)],
         &program_id,
     );
+    println!("Lottery ID: {}", lottery_id);
     println!("Lottery Config PDA: {}", lottery_config_pda);
 
     // 5. Define Development Fee Receiver Public Key
@@ -52,12 +55,16 @@ fn main() -> Result<()> {
 
     let mut instruction_data = Vec::new();
     instruction_data.extend_from_slice(&instruction_discriminator);
+    instruction_data.extend_from_slice(&lottery_id.to_le_bytes());
     instruction_data.extend_from_slice(dev_fee_receiver_pubkey.as_ref());
     instruction_data.extend_from_slice(&entry_fee_lamports.to_le_bytes());
 
     // 8. Define accounts for the instruction
+    let (platform_config_pda, _platform_bump) = Pubkey::find_program_address(&[PLATFORM_CONFIG_SEED], &program_id);
+    println!("Platform Config PDA: {}", platform_config_pda);
     let accounts = vec![
         AccountMeta::new(lottery_config_pda, false), // lottery_config account
+        AccountMeta::new_readonly(platform_config_pda, false), // platform_config account (admin must be its authority)
         AccountMeta::new(admin_keypair.pubkey(), true), // admin account (signer)
         AccountMeta::new_readonly(system_program::id(), false), // system_program
     ];
@@ -69,20 +76,87 @@ fn main() -> Result<()> {
         data: instruction_data,
     };
 
-    // 10. Create and send a transaction
+    // 10. Create the initialize_treasury instruction; rounds cannot start until the treasury exists.
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[TREASURY_SEED, lottery_config_pda.as_ref()], &program_id);
+    println!("Treasury PDA: {}", treasury_pda);
+    let treasury_instruction = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(lottery_config_pda, false), // lottery_config account
+            AccountMeta::new(treasury_pda, false), // treasury account
+            AccountMeta::new(admin_keypair.pubkey(), true), // admin account (signer)
+            AccountMeta::new_readonly(system_program::id(), false), // system_program
+        ],
+        data: vec![124, 186, 211, 195, 85, 165, 129, 166],
+    };
+
+    // 10b. Create the initialize_fee_vault instruction; rounds cannot be distributed until the fee vault exists.
+    let (fee_vault_pda, _fee_vault_bump) = Pubkey::find_program_address(&[FEE_VAULT_SEED, lottery_config_pda.as_ref()], &program_id);
+    println!("Fee Vault PDA: {}", fee_vault_pda);
+    let fee_vault_instruction = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(lottery_config_pda, false), // lottery_config account
+            AccountMeta::new(fee_vault_pda, false), // fee_vault account
+            AccountMeta::new(admin_keypair.pubkey(), true), // admin account (signer)
+            AccountMeta::new_readonly(system_program::id(), false), // system_program
+        ],
+        data: vec![185, 140, 228, 234, 79, 203, 252, 50],
+    };
+
+    // 10c. Create the initialize_lottery_stats instruction; tickets cannot be sold until the stats account exists.
+    let (lottery_stats_pda, _lottery_stats_bump) = Pubkey::find_program_address(&[LOTTERY_STATS_SEED, lottery_config_pda.as_ref()], &program_id);
+    println!("Lottery Stats PDA: {}", lottery_stats_pda);
+    let lottery_stats_instruction = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(lottery_config_pda, false), // lottery_config account
+            AccountMeta::new(lottery_stats_pda, false), // lottery_stats account
+            AccountMeta::new(admin_keypair.pubkey(), true), // admin account (signer)
+            AccountMeta::new_readonly(system_program::id(), false), // system_program
+        ],
+        data: vec![216, 86, 32, 23, 216, 71, 26, 28],
+    };
+
+    // 11. On a fresh deployment, first make the admin the platform authority
+    //     (2.5% platform fee on created lotteries, entry fees of 0.01-5 SOL, dev fee up to 20%, main prize at least 30%).
+    let mut instructions = Vec::new();
+    if client.get_account(&platform_config_pda).is_err() {
+        let mut platform_data = vec![119, 201, 101, 45, 75, 122, 89, 3]; // initialize_platform discriminator
+        platform_data.extend_from_slice(&250u16.to_le_bytes()); // platform_fee_bps
+        platform_data.extend_from_slice(&10_000_000u64.to_le_bytes()); // min_entry_fee_lamports
+        platform_data.extend_from_slice(&5_000_000_000u64.to_le_bytes()); // max_entry_fee_lamports
+        platform_data.extend_from_slice(&2_000u16.to_le_bytes()); // max_dev_fee_bps
+        platform_data.extend_from_slice(&3_000u16.to_le_bytes()); // min_main_winner_bps
+        instructions.push(Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(platform_config_pda, false), // platform_config account
+                AccountMeta::new(admin_keypair.pubkey(), true), // authority account (signer)
+                AccountMeta::new_readonly(system_program::id(), false), // system_program
+            ],
+            data: platform_data,
+        });
+    }
+    instructions.push(instruction);
+    instructions.push(treasury_instruction);
+    instructions.push(fee_vault_instruction);
+    instructions.push(lottery_stats_instruction);
+
+    // 12. Create and send a transaction
     let recent_blockhash = client.get_latest_blockhash()?;
     let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
+        &instructions,
         Some(&admin_keypair.pubkey()),
         &[&admin_keypair],
         recent_blockhash,
     );
 
-    println!("Sending transaction to initialize LotteryConfig...");
+    println!("Sending transaction to initialize LotteryConfig, Treasury, Fee Vault and Lottery Stats...");
     match client.send_and_confirm_transaction_with_spinner(&transaction) {
         Ok(signature) => {
             println!(
-                "Transaction successful with signature: {}. LotteryConfig should be initialized.",
+                "Transaction successful with signature: {}. LotteryConfig, Treasury, Fee Vault and Lottery Stats should be initialized.",
                 signature
             );
             println!("Verify on Solana Explorer: https://explorer.solana.com/tx/{}?cluster=devnet", signature);
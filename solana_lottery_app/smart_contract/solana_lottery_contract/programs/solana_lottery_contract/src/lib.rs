@@ -1,5 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::sysvar;
+use solana_lottery_core::seeds::{
+    FEE_SPLIT_SEED, FEE_VAULT_SEED, LOTTERY_CONFIG_SEED, LOTTERY_ROUND_SEED, LOTTERY_STATS_SEED, PLATFORM_CONFIG_SEED, PLAYER_PROFILE_SEED,
+    PRIZE_VAULT_SEED, ROUND_RESULT_SEED, TREASURY_SEED,
+};
+use solana_lottery_core::{draw, merkle, payout, schedule, CoreError};
 
 // Program ID - This should be updated with the actual Program ID after the first successful build and deployment.
 // The current ID "58Tp7nzzrUej7RTdWGSavSdGp1YHArexN19pYFnTABxv" is a placeholder or an ID from a previous deployment.
@@ -9,46 +16,293 @@ declare_id!("58Tp7nzzrUej7RTdWGSavSdGp1YHArexN19pYFnTABxv");
 ///
 /// This program implements a decentralized weekly lottery system on the Solana blockchain.
 /// It allows an administrator to configure the lottery, start new rounds, and manage prize distribution.
+/// One deployment can run several independent lotteries side by side (e.g. daily, weekly and VIP), each with its own
+/// `LotteryConfig` keyed by a lottery ID, and its own admin, fees, rounds and treasury. The platform authority creates
+/// lotteries with `initialize_config`; anyone else can create one through the `create_lottery` factory, within the
+/// bounds and for the platform fee set in the global `PlatformConfig`.
 /// Participants can enter the lottery by paying an entry fee, and winners are selected through a pseudo-random draw mechanism.
 #[program]
 pub mod solana_lottery_contract {
     use super::*; // Imports items from the parent module (e.g., structs defined outside this mod).
 
+    /// ## Initialize Platform
+    ///
+    /// Creates the global `PlatformConfig` account that sets the bounds of lotteries created through `create_lottery`
+    /// and collects their platform fee. Called once after deployment; the signer becomes the platform authority.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `platform_config`: The `PlatformConfig` account to be initialized (PDA: seeds = ["platform_config"]).
+    ///     * `authority`: The signer account that will become the platform authority. Pays the rent.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    /// * `platform_fee_bps` - Platform fee charged on the pot of lotteries created from now on, in basis points (at most `MAX_PLATFORM_FEE_BPS`).
+    /// * `min_entry_fee_lamports` - Lowest entry fee a created lottery may charge, in lamports.
+    /// * `max_entry_fee_lamports` - Highest entry fee a created lottery may charge, in lamports.
+    /// * `max_dev_fee_bps` - Highest dev fee a creator may choose, in basis points of the pot.
+    /// * `min_main_winner_bps` - Lowest main winner share a creator may choose, in basis points of the pot.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the platform is initialized.
+    /// * `Err` if the bounds are inconsistent.
+    pub fn initialize_platform(
+        ctx: Context<InitializePlatform>,
+        platform_fee_bps: u16,
+        min_entry_fee_lamports: u64,
+        max_entry_fee_lamports: u64,
+        max_dev_fee_bps: u16,
+        min_main_winner_bps: u16,
+    ) -> Result<()> {
+        msg!("Instruction: Initialize Platform");
+        validate_platform_bounds(platform_fee_bps, min_entry_fee_lamports, max_entry_fee_lamports, max_dev_fee_bps, min_main_winner_bps)?;
+
+        let platform_config = &mut ctx.accounts.platform_config;
+        platform_config.authority = ctx.accounts.authority.key();
+        platform_config.platform_fee_bps = platform_fee_bps;
+        platform_config.min_entry_fee_lamports = min_entry_fee_lamports;
+        platform_config.max_entry_fee_lamports = max_entry_fee_lamports;
+        platform_config.max_dev_fee_bps = max_dev_fee_bps;
+        platform_config.min_main_winner_bps = min_main_winner_bps;
+        platform_config.lottery_count = 0;
+        platform_config.bump = ctx.bumps.platform_config;
+
+        msg!("Platform initialized. Authority: {}, Platform Fee: {} bps", platform_config.authority, platform_fee_bps);
+        emit_platform_config_updated(platform_config);
+        Ok(())
+    }
+
+    /// ## Update Platform
+    ///
+    /// Allows the platform authority to change the bounds and the platform fee applied to lotteries created from now on.
+    /// Existing lotteries keep the values they were created with.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `platform_config`: The `PlatformConfig` account to be updated.
+    ///     * `authority`: The signer account, must match `platform_config.authority`.
+    /// * `platform_fee_bps` - Platform fee, in basis points (at most `MAX_PLATFORM_FEE_BPS`).
+    /// * `min_entry_fee_lamports` - Lowest entry fee a created lottery may charge, in lamports.
+    /// * `max_entry_fee_lamports` - Highest entry fee a created lottery may charge, in lamports.
+    /// * `max_dev_fee_bps` - Highest dev fee a creator may choose, in basis points of the pot.
+    /// * `min_main_winner_bps` - Lowest main winner share a creator may choose, in basis points of the pot.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the update is successful.
+    /// * `Err` if the signer is not the platform authority or the bounds are inconsistent.
+    pub fn update_platform(
+        ctx: Context<UpdatePlatform>,
+        platform_fee_bps: u16,
+        min_entry_fee_lamports: u64,
+        max_entry_fee_lamports: u64,
+        max_dev_fee_bps: u16,
+        min_main_winner_bps: u16,
+    ) -> Result<()> {
+        msg!("Instruction: Update Platform");
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        require!(platform_config.authority == ctx.accounts.authority.key(), LotteryError::UnauthorizedPlatformAuthority);
+        validate_platform_bounds(platform_fee_bps, min_entry_fee_lamports, max_entry_fee_lamports, max_dev_fee_bps, min_main_winner_bps)?;
+
+        platform_config.platform_fee_bps = platform_fee_bps;
+        platform_config.min_entry_fee_lamports = min_entry_fee_lamports;
+        platform_config.max_entry_fee_lamports = max_entry_fee_lamports;
+        platform_config.max_dev_fee_bps = max_dev_fee_bps;
+        platform_config.min_main_winner_bps = min_main_winner_bps;
+        msg!("Platform updated. Platform Fee: {} bps, Entry Fee Bounds: {}..={} lamports",
+             platform_fee_bps, min_entry_fee_lamports, max_entry_fee_lamports);
+        emit_platform_config_updated(platform_config);
+        Ok(())
+    }
+
+    /// ## Withdraw Platform Fees
+    ///
+    /// Allows the platform authority to withdraw the platform fees collected in the `PlatformConfig` account. The
+    /// account always keeps its own rent-exempt minimum.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `platform_config`: The `PlatformConfig` account holding the fees.
+    ///     * `authority`: The signer account, must match `platform_config.authority`. Receives the lamports.
+    /// * `amount_lamports` - The amount to withdraw, in lamports.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the lamports are withdrawn.
+    /// * `Err` if the signer is not the platform authority or less than `amount_lamports` has been collected.
+    pub fn withdraw_platform_fees(ctx: Context<WithdrawPlatformFees>, amount_lamports: u64) -> Result<()> {
+        msg!("Instruction: Withdraw Platform Fees");
+        let platform_config_account_info = ctx.accounts.platform_config.to_account_info();
+
+        require!(ctx.accounts.platform_config.authority == ctx.accounts.authority.key(), LotteryError::UnauthorizedPlatformAuthority);
+        require!(platform_fee_lamports(&platform_config_account_info)? >= amount_lamports, LotteryError::InsufficientPlatformFees);
+
+        **platform_config_account_info.try_borrow_mut_lamports()? -= amount_lamports;
+        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += amount_lamports;
+
+        let remaining_lamports = platform_fee_lamports(&platform_config_account_info)?;
+        msg!("Withdrew {} lamports of platform fees. Remaining: {} lamports", amount_lamports, remaining_lamports);
+        emit!(PlatformFeesWithdrawn { authority: ctx.accounts.authority.key(), amount_lamports, remaining_lamports });
+        Ok(())
+    }
+
     /// ## Initialize Configuration
     ///
-    /// Initializes the main configuration account (`LotteryConfig`) for the lottery program.
-    /// This function can only be called once by the designated admin to set up the initial parameters.
+    /// Initializes the configuration account (`LotteryConfig`) of a new lottery run by the platform itself.
+    /// This function can only be called once per lottery ID, and only by the platform authority, who becomes that
+    /// lottery's admin. Such lotteries pay no platform fee and use the default split (10% dev fee, 50% main winner,
+    /// 4 x 10% other winners). Anyone else creates lotteries with `create_lottery`.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - The context containing accounts for this instruction.
-    ///     * `lottery_config`: The `LotteryConfig` account to be initialized (PDA: seeds = ["lottery_config"]).
-    ///     * `admin`: The signer account that will become the administrator of the lottery.
+    ///     * `lottery_config`: The `LotteryConfig` account to be initialized (PDA: seeds = ["lottery_config", lottery_id]).
+    ///     * `platform_config`: The global `PlatformConfig` account (for the platform authority).
+    ///     * `admin`: The signer account that will become the administrator of the lottery. Must be the platform authority.
     ///     * `system_program`: Required by Anchor for account initialization.
-    /// * `dev_fee_receiver` - The public key of the account that will receive development fees.
+    /// * `lottery_id` - The identifier of the new lottery, used as a seed for its `LotteryConfig` PDA.
+    /// * `dev_fee_receiver` - The public key of the account that will receive development fees. May not be the
+    ///                        program or the system program; fees are only paid to a System Program account.
     /// * `entry_fee_lamports` - The cost in lamports for one lottery ticket.
     ///
     /// ### Returns
     ///
     /// * `Ok(())` if the initialization is successful.
+    /// * `Err(LotteryError::UnauthorizedPlatformAuthority)` if the signer is not the platform authority.
+    /// * `Err(LotteryError::InvalidDevFeeReceiver)` if the dev fee receiver is not allowed.
     pub fn initialize_config(
         ctx: Context<InitializeConfig>,
+        lottery_id: u64,               // The identifier of the lottery, used in PDA derivation.
         dev_fee_receiver: Pubkey,      // The public key for the development fee receiver account.
         entry_fee_lamports: u64,       // The entry fee for the lottery in lamports (e.g., 0.1 SOL = 100,000,000 lamports).
     ) -> Result<()> {
         msg!("Instruction: Initialize Lottery Config");
+        require!(
+            ctx.accounts.platform_config.authority == *ctx.accounts.admin.key,
+            LotteryError::UnauthorizedPlatformAuthority
+        );
+        validate_dev_fee_receiver(&dev_fee_receiver)?;
         let lottery_config = &mut ctx.accounts.lottery_config; // Get a mutable reference to the lottery_config account.
+        init_lottery_config(lottery_config, lottery_id, *ctx.accounts.admin.key, dev_fee_receiver, entry_fee_lamports, ctx.bumps.lottery_config);
+
+        msg!("Lottery Config {} Initialized. Admin: {}, Dev Fee Receiver: {}, Entry Fee: {}", 
+             lottery_config.lottery_id, lottery_config.admin_key, lottery_config.dev_fee_receiver, lottery_config.entry_fee_lamports);
+        emit!(ConfigInitialized {
+            lottery_id: lottery_config.lottery_id,
+            lottery_config: lottery_config.key(),
+            admin: lottery_config.admin_key,
+            dev_fee_receiver: lottery_config.dev_fee_receiver,
+            entry_fee_lamports: lottery_config.entry_fee_lamports,
+        });
+        Ok(())
+    }
+
+    /// ## Create Lottery
+    ///
+    /// Creates a new lottery for anyone who wants to run one. The signer becomes the lottery's admin and chooses its
+    /// entry fee and payout split within the bounds of the `PlatformConfig`. The platform fee in effect is recorded on
+    /// the lottery and paid to the `PlatformConfig` account out of every distributed pot.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be initialized (PDA: seeds = ["lottery_config", lottery_id]).
+    ///     * `platform_config`: The global `PlatformConfig` account (mutable to count the new lottery).
+    ///     * `creator`: The signer account that will become the administrator of the lottery. Pays the rent.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    /// * `lottery_id` - The identifier of the new lottery, used as a seed for its `LotteryConfig` PDA. Must be unused.
+    /// * `dev_fee_receiver` - The public key of the account that will receive the creator's dev fee. May not be the
+    ///                        program or the system program; fees are only paid to a System Program account.
+    /// * `entry_fee_lamports` - The cost in lamports for one lottery ticket, within the platform's entry fee bounds.
+    /// * `dev_fee_bps` - The creator's dev fee, in basis points of the pot (at most `platform_config.max_dev_fee_bps`).
+    /// * `main_winner_bps` - The main winner's share, in basis points of the pot (at least `platform_config.min_main_winner_bps`).
+    ///                       Whatever the fees and the main winner leave is shared equally by the 4 other winners.
+    /// * `metadata_uri` - A URI of at most `MAX_METADATA_URI_LEN` bytes pointing to the lottery's branding metadata.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the lottery is created.
+    /// * `Err` if the entry fee or the split is outside the platform's bounds, the metadata URI is too long or the
+    ///   dev fee receiver is not allowed.
+    pub fn create_lottery(
+        ctx: Context<CreateLottery>,
+        lottery_id: u64,
+        dev_fee_receiver: Pubkey,
+        entry_fee_lamports: u64,
+        dev_fee_bps: u16,
+        main_winner_bps: u16,
+        metadata_uri: String,
+    ) -> Result<()> {
+        msg!("Instruction: Create Lottery");
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        require!(
+            (platform_config.min_entry_fee_lamports..=platform_config.max_entry_fee_lamports).contains(&entry_fee_lamports),
+            LotteryError::EntryFeeOutOfBounds
+        );
+        require!(
+            dev_fee_bps <= platform_config.max_dev_fee_bps && main_winner_bps >= platform_config.min_main_winner_bps,
+            LotteryError::InvalidPayoutSplit
+        );
+        let split = payout::SplitBps { platform_fee_bps: platform_config.platform_fee_bps, dev_fee_bps, main_winner_bps };
+        split.other_winner_bps().map_err(LotteryError::from)?; // The fees and the main winner may not exceed the whole pot.
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, LotteryError::MetadataUriTooLong);
+        validate_dev_fee_receiver(&dev_fee_receiver)?;
+
+        let lottery_config = &mut ctx.accounts.lottery_config;
+        init_lottery_config(lottery_config, lottery_id, ctx.accounts.creator.key(), dev_fee_receiver, entry_fee_lamports, ctx.bumps.lottery_config);
+        lottery_config.platform_fee_bps = split.platform_fee_bps; // Fixed for the lifetime of the lottery.
+        lottery_config.dev_fee_bps = split.dev_fee_bps;
+        lottery_config.main_winner_bps = split.main_winner_bps;
+        lottery_config.min_entry_fee_lamports = platform_config.min_entry_fee_lamports; // `update_config` stays within the bounds.
+        lottery_config.max_entry_fee_lamports = platform_config.max_entry_fee_lamports;
+        lottery_config.metadata_uri = metadata_uri;
+        platform_config.lottery_count = platform_config.lottery_count.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
 
-        // Set the initial values for the LotteryConfig account.
-        lottery_config.admin_key = *ctx.accounts.admin.key; // The admin who initialized the config.
-        lottery_config.dev_fee_receiver = dev_fee_receiver;  // Wallet to receive a percentage of the pot.
-        lottery_config.entry_fee_lamports = entry_fee_lamports; // Cost per ticket.
-        lottery_config.is_paused = false;                    // Lottery is active by default.
-        lottery_config.current_lottery_round_id = 0;         // No rounds have occurred yet.
-        lottery_config.bump = ctx.bumps.lottery_config;      // Store the bump seed for the PDA.
+        msg!("Lottery {} created by {}. Entry Fee: {}, Split: platform {} / dev {} / main {} bps",
+             lottery_id, lottery_config.admin_key, entry_fee_lamports, split.platform_fee_bps, dev_fee_bps, main_winner_bps);
+        emit!(LotteryCreated {
+            lottery_id,
+            lottery_config: lottery_config.key(),
+            creator: lottery_config.admin_key,
+            dev_fee_receiver,
+            entry_fee_lamports,
+            platform_fee_bps: split.platform_fee_bps,
+            dev_fee_bps,
+            main_winner_bps,
+            metadata_uri: lottery_config.metadata_uri.clone(),
+        });
+        Ok(())
+    }
+
+    /// ## Set Lottery Metadata
+    ///
+    /// Allows the administrator to publish or replace the URI of the lottery's branding metadata (name, logo, ...).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `metadata_uri` - The new URI, at most `MAX_METADATA_URI_LEN` bytes. Pass an empty string to remove it.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the update is successful.
+    /// * `Err` if the signer is not the admin or the URI is too long.
+    pub fn set_lottery_metadata(ctx: Context<UpdateConfig>, metadata_uri: String) -> Result<()> {
+        msg!("Instruction: Set Lottery Metadata");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, LotteryError::MetadataUriTooLong);
 
-        msg!("Lottery Config Initialized. Admin: {}, Dev Fee Receiver: {}, Entry Fee: {}", 
-             lottery_config.admin_key, lottery_config.dev_fee_receiver, lottery_config.entry_fee_lamports);
+        lottery_config.metadata_uri = metadata_uri;
+        msg!("Lottery metadata URI set to {}", lottery_config.metadata_uri);
+        emit!(LotteryMetadataUpdated { lottery_config: lottery_config.key(), metadata_uri: lottery_config.metadata_uri.clone() });
         Ok(())
     }
 
@@ -61,14 +315,18 @@ pub mod solana_lottery_contract {
     /// * `ctx` - The context containing accounts for this instruction.
     ///     * `lottery_config`: The `LotteryConfig` account to be updated.
     ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
-    /// * `new_dev_fee_receiver` - (Optional) New public key for the development fee receiver.
-    /// * `new_entry_fee_lamports` - (Optional) New entry fee in lamports.
+    /// * `new_dev_fee_receiver` - (Optional) New public key for the development fee receiver. May not be the program
+    ///                            or the system program; fees are only paid to a System Program account.
+    /// * `new_entry_fee_lamports` - (Optional) New entry fee in lamports. Lotteries created with `create_lottery` stay
+    ///                              within the platform's entry fee bounds recorded at creation.
     /// * `new_is_paused` - (Optional) New pause status for the lottery.
     ///
     /// ### Returns
     ///
     /// * `Ok(())` if the update is successful.
     /// * `Err(LotteryError::UnauthorizedAdmin)` if the signer is not the admin.
+    /// * `Err(LotteryError::EntryFeeOutOfBounds)` if the new entry fee is outside the lottery's bounds.
+    /// * `Err(LotteryError::InvalidDevFeeReceiver)` if the new dev fee receiver is not allowed.
     pub fn update_config(
         ctx: Context<UpdateConfig>,
         new_dev_fee_receiver: Option<Pubkey>,
@@ -81,12 +339,22 @@ pub mod solana_lottery_contract {
         // Ensure the signer is the admin.
         require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
 
+        // Remember the previous values for the `ConfigUpdated` event.
+        let old_dev_fee_receiver = lottery_config.dev_fee_receiver;
+        let old_entry_fee_lamports = lottery_config.entry_fee_lamports;
+        let old_is_paused = lottery_config.is_paused;
+
         // Update fields if new values are provided.
         if let Some(receiver) = new_dev_fee_receiver {
+            validate_dev_fee_receiver(&receiver)?;
             lottery_config.dev_fee_receiver = receiver;
             msg!("Updated dev_fee_receiver to: {}", receiver);
         }
         if let Some(fee) = new_entry_fee_lamports {
+            require!(
+                (lottery_config.min_entry_fee_lamports..=lottery_config.max_entry_fee_lamports).contains(&fee),
+                LotteryError::EntryFeeOutOfBounds
+            );
             lottery_config.entry_fee_lamports = fee;
             msg!("Updated entry_fee_lamports to: {}", fee);
         }
@@ -95,6 +363,15 @@ pub mod solana_lottery_contract {
             msg!("Updated is_paused to: {}", paused_status);
         }
         msg!("Lottery Config Updated Successfully");
+        emit!(ConfigUpdated {
+            admin: lottery_config.admin_key,
+            old_dev_fee_receiver,
+            new_dev_fee_receiver: lottery_config.dev_fee_receiver,
+            old_entry_fee_lamports,
+            new_entry_fee_lamports: lottery_config.entry_fee_lamports,
+            old_is_paused,
+            new_is_paused: lottery_config.is_paused,
+        });
         Ok(())
     }
 
@@ -102,21 +379,33 @@ pub mod solana_lottery_contract {
     ///
     /// Allows the administrator to start a new lottery round.
     /// Initializes a new `LotteryRound` account and increments the `current_lottery_round_id` in `LotteryConfig`.
+    /// As with `start_scheduled_round`, the previous round must have been paid out, cancelled or closed first.
+    /// The round's pot is held apart from its data in a prize vault PDA, whose rent the admin pays as well.
+    /// The round records the lottery's entry fee, payout split, crank bounty and dispute window at this point; later
+    /// changes to the config only apply to rounds started afterwards.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - The context containing accounts for this instruction.
     ///     * `lottery_config`: The main `LotteryConfig` account (mutable to update `current_lottery_round_id`).
-    ///     * `lottery_round`: The new `LotteryRound` account to be initialized (PDA: seeds = ["lottery_round", next_round_id_arg]).
+    ///     * `previous_round`: The `LotteryRound` PDA of the current round ID. May already be closed (or, for the
+    ///                         first round, never have existed).
+    ///     * `lottery_round`: The new `LotteryRound` account to be initialized (PDA: seeds = ["lottery_round", lottery_config, next_round_id_arg]).
+    ///     * `prize_vault`: The new round's prize vault (PDA: seeds = ["prize_vault", lottery_round]), funded with its rent.
     ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
-    ///     * `system_program`: Required by Anchor for account initialization.
-    /// * `draw_timestamp_offset_seconds` - The duration of the lottery round in seconds from the current time.
+    ///     * `treasury`: The `Treasury` account (mutable to commit the round's guaranteed main prize).
+    ///     * `system_program`: Required by Anchor for account initialization and the vault rent transfer.
+    /// * `draw_timestamp_offset_seconds` - The duration of the lottery round in seconds from the current time. If a draw
+    ///                                     calendar is set (see `set_draw_calendar`), the minimum duration instead; the
+    ///                                     draw then happens at the first calendar slot after it.
     /// * `next_round_id_arg` - The ID for the new round, used as a seed for the `LotteryRound` PDA. Must be `current_lottery_round_id + 1`.
     ///
     /// ### Returns
     ///
     /// * `Ok(())` if the new round is started successfully.
-    /// * `Err` for various conditions like unauthorized admin, lottery paused, numeric overflow, or incorrect round ID argument.
+    /// * `Err` for various conditions like unauthorized admin, lottery paused, numeric overflow, incorrect round ID argument,
+    ///   a previous round that is not paid out, cancelled or closed yet, or a treasury that cannot cover the guaranteed
+    ///   main prize.
     pub fn start_new_lottery_round(
         ctx: Context<StartNewLotteryRound>,
         draw_timestamp_offset_seconds: i64, // Duration of the round in seconds.
@@ -135,46 +424,78 @@ pub mod solana_lottery_contract {
         let expected_next_round_id = lottery_config.current_lottery_round_id.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
         // Ensure the provided argument matches the expected next round ID for PDA consistency.
         require!(next_round_id_arg == expected_next_round_id, LotteryError::IncorrectNextRoundIdArg);
+        require_previous_round_finished(&ctx.accounts.previous_round, ctx.program_id)?;
 
         // Update the current round ID in the global config.
         lottery_config.current_lottery_round_id = expected_next_round_id;
         
-        // Initialize the new LotteryRound account.
-        lottery_round.round_id = expected_next_round_id;
-        lottery_round.start_timestamp = clock.unix_timestamp;
-        lottery_round.draw_timestamp = clock.unix_timestamp.checked_add(draw_timestamp_offset_seconds).ok_or(LotteryError::NumericOverflow)?;
-        lottery_round.total_pot_lamports = 0;
-        lottery_round.participants = Vec::new(); // Initialize with an empty list of participants.
-        lottery_round.is_active = true;           // Mark the round as active.
-        lottery_round.winners_drawn = false;
-        lottery_round.prizes_distributed = false;
-        lottery_round.main_winner = None;
-        lottery_round.other_winners = Vec::new();
-        lottery_round.randomness_seed = None;
-        lottery_round.bump = ctx.bumps.lottery_round; // Store the bump seed for the PDA.
+        lottery_config.last_round_start_timestamp = clock.unix_timestamp;
+
+        // Initialize the new LotteryRound account. The admin paid its rent and gets it back in `close_round`.
+        let draw_timestamp = compute_draw_timestamp(lottery_config, clock.unix_timestamp, draw_timestamp_offset_seconds)?;
+        let sales_close_timestamp = compute_sales_close_timestamp(lottery_config, clock.unix_timestamp, draw_timestamp)?;
+        commit_guarantee(&mut ctx.accounts.treasury, lottery_config.guaranteed_main_prize_lamports)?;
+        lottery_round.set_inner(open_round(
+            lottery_config,
+            expected_next_round_id,
+            clock.unix_timestamp,
+            draw_timestamp,
+            sales_close_timestamp,
+            ctx.accounts.admin.key(),
+            ctx.bumps.lottery_round, // Store the bump seed for the PDA.
+        ));
+        lottery_round.prize_vault_bump = ctx.bumps.prize_vault;
 
-        msg!("Lottery Round {} started. Draw scheduled for timestamp: {}. Admin: {}", 
-             expected_next_round_id, lottery_round.draw_timestamp, ctx.accounts.admin.key);
+        // The admin also pays the rent of the prize vault, which holds the pot apart from the round data.
+        let vault_rent_lamports = prize_vault_rent_due(&ctx.accounts.prize_vault)?;
+        if vault_rent_lamports > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: ctx.accounts.prize_vault.to_account_info(),
+                },
+            );
+            system_program::transfer(cpi_context, vault_rent_lamports)?;
+        }
+
+        msg!("Lottery Round {} started. Sales close at timestamp: {}. Draw scheduled for timestamp: {}. Admin: {}", 
+             expected_next_round_id, lottery_round.sales_close_timestamp, lottery_round.draw_timestamp, ctx.accounts.admin.key);
+        emit!(RoundStarted {
+            lottery_config: lottery_config.key(),
+            round_id: expected_next_round_id,
+            start_timestamp: lottery_round.start_timestamp,
+            draw_timestamp: lottery_round.draw_timestamp,
+            entry_fee_lamports: lottery_round.entry_fee_lamports,
+        });
         Ok(())
     }
 
     /// ## Enter Lottery
     ///
     /// Allows a participant to enter the current active lottery round by paying the entry fee.
-    /// The entry fee is transferred from the participant's account to the `LotteryRound` account.
+    /// The entry fee recorded on the round when it started is transferred from the participant's account to the
+    /// round's prize vault.
+    ///
+    /// The entry is recorded in the participant's `PlayerProfile`. The first entry of a wallet creates the profile;
+    /// the participant pays its rent.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - The context containing accounts for this instruction.
-    ///     * `lottery_config`: The main `LotteryConfig` account (to check fee and pause status).
+    ///     * `lottery_config`: The main `LotteryConfig` account (to check the pause status).
     ///     * `lottery_round`: The current `LotteryRound` account (mutable to add participant and update pot).
+    ///     * `prize_vault`: The round's prize vault, which receives the entry fee.
+    ///     * `lottery_stats`: The lottery's `LotteryStats` account, which counts the ticket.
     ///     * `participant`: The signer account entering the lottery.
-    ///     * `system_program`: Required for the SOL transfer (CPI).
+    ///     * `player_profile`: The participant's `PlayerProfile` (PDA: seeds = ["player_profile", participant]), created
+    ///       if it does not exist yet.
+    ///     * `system_program`: Required for the SOL transfers and the profile creation (CPI).
     ///
     /// ### Returns
     ///
     /// * `Ok(())` if the participant successfully enters the lottery.
-    /// * `Err` for various conditions like lottery paused, round inactive, draw time passed, already entered, or incorrect round.
+    /// * `Err` for various conditions like lottery paused, round inactive, sales closed, round sold out, already entered, or incorrect round.
     pub fn enter_lottery(ctx: Context<EnterLottery>) -> Result<()> {
         msg!("Instruction: Enter Lottery");
         let lottery_config = &ctx.accounts.lottery_config;
@@ -184,49 +505,323 @@ pub mod solana_lottery_contract {
 
         // Pre-condition checks.
         require!(!lottery_config.is_paused, LotteryError::LotteryPaused);
-        require!(lottery_round.is_active, LotteryError::LotteryRoundNotActive);
-        require!(clock.unix_timestamp < lottery_round.draw_timestamp, LotteryError::LotteryDrawTimePassed);
+        require!(lottery_round.status == RoundStatus::Open, LotteryError::LotteryRoundNotActive);
+        require!(clock.unix_timestamp < lottery_round.sales_close_timestamp, LotteryError::SalesPeriodEnded);
+        require!(!lottery_round.is_sold_out, LotteryError::RoundSoldOut);
         require!(!lottery_round.participants.contains(participant_signer.key), LotteryError::ParticipantAlreadyEntered);
         // Ensure this is the correct, currently active lottery round PDA being interacted with.
         require!(lottery_round.round_id == lottery_config.current_lottery_round_id, LotteryError::IncorrectLotteryRound);
 
-        // CPI: Transfer entry fee from participant to the lottery round account.
+        // CPI: Transfer entry fee from participant to the round's prize vault.
         let cpi_context = CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
             system_program::Transfer {
                 from: participant_signer.to_account_info(),
-                to: lottery_round.to_account_info(), // Transfer to the LotteryRound PDA itself.
+                to: ctx.accounts.prize_vault.to_account_info(),
             },
         );
-        system_program::transfer(cpi_context, lottery_config.entry_fee_lamports)?;
+        system_program::transfer(cpi_context, lottery_round.entry_fee_lamports)?;
 
         // Add participant to the list and update the total pot.
         lottery_round.participants.push(*participant_signer.key);
-        lottery_round.total_pot_lamports = lottery_round.total_pot_lamports.checked_add(lottery_config.entry_fee_lamports).ok_or(LotteryError::NumericOverflow)?;
+        lottery_round.total_pot_lamports = lottery_round.total_pot_lamports.checked_add(lottery_round.entry_fee_lamports).ok_or(LotteryError::NumericOverflow)?;
+        // The last ticket sells the round out; from now on it can be closed and drawn without waiting.
+        lottery_round.is_sold_out = lottery_round.participants.len() >= round_capacity(lottery_config);
+        let lottery_stats = &mut ctx.accounts.lottery_stats;
+        lottery_stats.total_tickets_sold = lottery_stats.total_tickets_sold.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
+
+        // Record the entry in the participant's profile, creating it on their first entry.
+        let player_profile_account_info = ctx.accounts.player_profile.to_account_info();
+        let mut player_profile = load_or_create_player_profile(
+            &player_profile_account_info,
+            participant_signer,
+            &ctx.accounts.system_program,
+            ctx.bumps.player_profile,
+        )?;
+        player_profile.rounds_entered = player_profile.rounds_entered.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
+        player_profile.tickets_bought = player_profile.tickets_bought.checked_add(1).ok_or(LotteryError::NumericOverflow)?; // One ticket per entry.
+        player_profile.last_lottery_config = lottery_config.key();
+        player_profile.last_round_id = lottery_round.round_id;
+        player_profile.try_serialize(&mut &mut player_profile_account_info.try_borrow_mut_data()?[..])?;
 
         msg!("Participant {} entered round {}. Current pot: {} lamports.", 
              participant_signer.key(), lottery_round.round_id, lottery_round.total_pot_lamports);
+        emit!(TicketPurchased {
+            round_id: lottery_round.round_id,
+            buyer: participant_signer.key(),
+            count: 1, // One ticket per participant per round.
+            price_lamports: lottery_round.entry_fee_lamports,
+            pot_after_lamports: lottery_round.total_pot_lamports,
+            sold_out: lottery_round.is_sold_out,
+        });
+        if lottery_round.is_sold_out {
+            msg!("Round {} sold out with {} participants", lottery_round.round_id, lottery_round.participants.len());
+            emit!(RoundSoldOut {
+                round_id: lottery_round.round_id,
+                participant_count: lottery_round.participants.len() as u32,
+                total_pot_lamports: lottery_round.total_pot_lamports,
+            });
+        }
+        check_round_solvency(lottery_round, &ctx.accounts.prize_vault)
+    }
+
+    /// ## Set Payout Address
+    ///
+    /// Lets a player choose the account `distribute_prizes` pays their prizes to, e.g. a cold wallet. The preference is
+    /// kept in the player's `PlayerProfile` and applies to every lottery run by the program. Refunds of cancelled
+    /// rounds still go to the wallet that entered, and so do prizes the payout address cannot receive when they are
+    /// paid (see `can_receive_prize`).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `player_profile`: The signer's `PlayerProfile` account, created by their first `enter_lottery`.
+    ///     * `player`: The signer owning the profile.
+    ///     * `payout_account`: The account at `payout_address`, to check that it can receive prizes. Omitted when
+    ///                         `payout_address` is `None`.
+    /// * `payout_address` - The account to pay prizes to, or `None` to pay them to the player's wallet again.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the preference is saved.
+    /// * `Err` if the signer has no profile yet, the payout account does not match the address, or the address is the
+    ///   program, the system program, the profile itself, an executable account or a sysvar.
+    pub fn set_payout_address(ctx: Context<SetPayoutAddress>, payout_address: Option<Pubkey>) -> Result<()> {
+        msg!("Instruction: Set Payout Address");
+        if let Some(address) = payout_address {
+            require!(
+                ![crate::ID, system_program::ID, ctx.accounts.player_profile.key()].contains(&address),
+                LotteryError::InvalidPayoutAddress
+            );
+            let payout_account = ctx.accounts.payout_account.as_ref().ok_or(LotteryError::IncorrectPayoutAccount)?;
+            require!(payout_account.key() == address, LotteryError::IncorrectPayoutAccount);
+            require!(!payout_account.executable && *payout_account.owner != sysvar::ID, LotteryError::InvalidPayoutAddress);
+        }
+        let player_profile = &mut ctx.accounts.player_profile;
+        player_profile.preferred_payout_address = payout_address;
+        msg!("Prizes of {} are now paid to {}", player_profile.player, player_profile.payout_address());
+        emit!(PayoutAddressUpdated { player: player_profile.player, payout_address });
         Ok(())
     }
 
+    /// ## Contribute To Pot
+    ///
+    /// Adds lamports to the current round's pot without buying a ticket, e.g. to sponsor a jackpot. Contributions are
+    /// tracked per contributor in `lottery_round.contributions`, apart from ticket sales, so that a cancelled round
+    /// refunds each contributor exactly what they put in and shares only the ticket sales among participants.
+    ///
+    /// Anyone can contribute until the round is drawn.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (to check pause status).
+    ///     * `lottery_round`: The current `LotteryRound` account (mutable to record the contribution).
+    ///     * `prize_vault`: The round's prize vault, which receives the lamports.
+    ///     * `contributor`: The signer paying the contribution, e.g. a sponsor or the treasury.
+    ///     * `system_program`: Required for the SOL transfer (CPI).
+    /// * `amount_lamports` - The contribution, in lamports.
+    /// * `memo` - An optional note of at most `MAX_CONTRIBUTION_MEMO_LEN` bytes, recorded in the `PotContributed` event.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the contribution is added to the pot.
+    /// * `Err` if the lottery is paused, the round is past its draw, the amount is zero, the memo is too long, or the
+    ///   round already has `MAX_POT_CONTRIBUTORS` other contributors.
+    pub fn contribute_to_pot(ctx: Context<ContributeToPot>, amount_lamports: u64, memo: Option<String>) -> Result<()> {
+        msg!("Instruction: Contribute To Pot");
+        let lottery_config = &ctx.accounts.lottery_config;
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        let contributor = &ctx.accounts.contributor;
+
+        require!(!lottery_config.is_paused, LotteryError::LotteryPaused);
+        require!(
+            matches!(lottery_round.status, RoundStatus::Open | RoundStatus::SalesClosed),
+            LotteryError::LotteryRoundNotActive
+        );
+        require!(amount_lamports > 0, LotteryError::InvalidContributionAmount);
+        require!(
+            memo.as_ref().map_or(true, |memo| memo.len() <= MAX_CONTRIBUTION_MEMO_LEN),
+            LotteryError::ContributionMemoTooLong
+        );
+
+        // Repeated contributions from the same account are merged into one refundable entry.
+        match lottery_round.contributions.iter_mut().find(|c| c.contributor == contributor.key()) {
+            Some(contribution) => {
+                contribution.amount_lamports = contribution.amount_lamports.checked_add(amount_lamports).ok_or(LotteryError::NumericOverflow)?;
+            }
+            None => {
+                require!(lottery_round.contributions.len() < MAX_POT_CONTRIBUTORS, LotteryError::TooManyContributors);
+                lottery_round.contributions.push(PotContribution { contributor: contributor.key(), amount_lamports });
+            }
+        }
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: contributor.to_account_info(),
+                to: ctx.accounts.prize_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount_lamports)?;
+        lottery_round.total_pot_lamports = lottery_round.total_pot_lamports.checked_add(amount_lamports).ok_or(LotteryError::NumericOverflow)?;
+
+        msg!("Contributor {} added {} lamports to round {}. Current pot: {} lamports.",
+             contributor.key(), amount_lamports, lottery_round.round_id, lottery_round.total_pot_lamports);
+        emit!(PotContributed {
+            round_id: lottery_round.round_id,
+            contributor: contributor.key(),
+            amount_lamports,
+            memo,
+            pot_after_lamports: lottery_round.total_pot_lamports,
+        });
+        check_round_solvency(lottery_round, &ctx.accounts.prize_vault)
+    }
+
+    /// ## Close Round Sales
+    ///
+    /// Ends ticket sales for the current round once its `sales_close_timestamp` has passed, or right away if the round
+    /// has sold out. This commits the final
+    /// participant list (`participants_root`) and records the slot in which sales closed; `conduct_draw` then only
+    /// accepts randomness from a slot hash produced at or after that slot, so no entrant can know it in advance.
+    ///
+    /// Anyone can call this instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (to check the current round ID).
+    ///     * `lottery_round`: The current `LotteryRound` account (mutable to update its status).
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if sales are closed.
+    /// * `Err` if the round is not open, or its sales close time has not been reached and it has not sold out.
+    pub fn close_round_sales(ctx: Context<CloseRoundSales>) -> Result<()> {
+        msg!("Instruction: Close Round Sales for Round {}", ctx.accounts.lottery_round.round_id);
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        let clock = Clock::get()?;
+
+        require!(lottery_round.status == RoundStatus::Open, LotteryError::LotteryRoundNotActive);
+        require!(
+            lottery_round.is_sold_out || clock.unix_timestamp >= lottery_round.sales_close_timestamp,
+            LotteryError::SalesCloseTimeNotReached
+        );
+
+        close_sales(lottery_round, clock.slot)
+    }
+
+    /// ## Extend Round
+    ///
+    /// Handles a round that has reached its draw time with fewer than `WINNER_COUNT` participants, if extensions are
+    /// enabled with `set_round_extensions`. While the round has been extended fewer than `max_round_extensions` times,
+    /// its draw moves to `extension_interval_seconds` from now (aligned to the draw calendar, if set) and ticket sales
+    /// reopen until the same cutoff before the new draw. Each extension is recorded in `lottery_round.extensions`.
+    /// Once no extensions are left, the round is cancelled instead and its participants can be refunded.
+    ///
+    /// Anyone can call this instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the extension rule).
+    ///     * `lottery_round`: The current `LotteryRound` account (mutable to move its draw or cancel it).
+    ///     * `treasury`: The `Treasury` account (mutable to release the guaranteed main prize of a cancelled round).
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the round is extended or cancelled.
+    /// * `Err` if extensions are disabled, the round is past its draw or has not reached its draw time, or it has enough participants.
+    pub fn extend_round(ctx: Context<ExtendRound>) -> Result<()> {
+        msg!("Instruction: Extend Round {}", ctx.accounts.lottery_round.round_id);
+        let lottery_config = &ctx.accounts.lottery_config;
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        let clock = Clock::get()?;
+
+        require!(lottery_config.extension_interval_seconds > 0, LotteryError::RoundExtensionsDisabled);
+        require!(lottery_round.status != RoundStatus::Cancelled, LotteryError::RoundCancelled);
+        require!(
+            matches!(lottery_round.status, RoundStatus::Open | RoundStatus::SalesClosed),
+            LotteryError::WinnersAlreadyDrawn
+        );
+        require!(clock.unix_timestamp >= lottery_round.draw_timestamp, LotteryError::DrawTimeNotReached);
+        require!(lottery_round.participants.len() < draw::WINNER_COUNT, LotteryError::RoundHasEnoughParticipants);
+
+        // No extensions left: give up on the round and refund its participants.
+        if lottery_round.extensions.len() >= lottery_config.max_round_extensions as usize {
+            mark_cancelled(lottery_round, &mut ctx.accounts.treasury)?;
+            msg!("Round {} cancelled after {} extensions. Refund per participant: {} lamports",
+                 lottery_round.round_id, lottery_round.extensions.len(), lottery_round.refund_per_participant_lamports);
+            emit!(RoundCancelled {
+                round_id: lottery_round.round_id,
+                participant_count: lottery_round.participants.len() as u32,
+                refund_per_participant_lamports: lottery_round.refund_per_participant_lamports,
+            });
+            return Ok(());
+        }
+
+        // Sales may already have been closed at the sales close time; reopen them with the commitment cleared.
+        if lottery_round.status == RoundStatus::SalesClosed {
+            transition_round_status(lottery_round, RoundStatus::Open)?;
+            lottery_round.participants_root = [0u8; 32];
+            lottery_round.sales_closed_slot = 0;
+        }
+
+        // Keep the round's gap between the end of sales and the draw.
+        let sales_cutoff_seconds = lottery_round
+            .draw_timestamp
+            .checked_sub(lottery_round.sales_close_timestamp)
+            .ok_or(LotteryError::NumericOverflow)?;
+        let previous_draw_timestamp = lottery_round.draw_timestamp;
+        let new_draw_timestamp = compute_draw_timestamp(lottery_config, clock.unix_timestamp, lottery_config.extension_interval_seconds)?;
+        lottery_round.draw_timestamp = new_draw_timestamp;
+        lottery_round.sales_close_timestamp = new_draw_timestamp.checked_sub(sales_cutoff_seconds).ok_or(LotteryError::NumericOverflow)?;
+        let participant_count = lottery_round.participants.len() as u32;
+        lottery_round.extensions.push(RoundExtension {
+            extended_at: clock.unix_timestamp,
+            previous_draw_timestamp,
+            new_draw_timestamp,
+            participant_count,
+        });
+
+        msg!("Round {} extended ({} of {}). New draw timestamp: {}",
+             lottery_round.round_id, lottery_round.extensions.len(), lottery_config.max_round_extensions, new_draw_timestamp);
+        emit!(RoundExtended {
+            round_id: lottery_round.round_id,
+            extension_number: lottery_round.extensions.len() as u8,
+            previous_draw_timestamp,
+            new_draw_timestamp,
+            sales_close_timestamp: lottery_round.sales_close_timestamp,
+            participant_count,
+        });
+        Ok(())
+    }
 
     /// ## Conduct Draw
     ///
-    /// Conducts the draw for the current active lottery round. This function should be called after the `draw_timestamp` has passed.
-    /// It uses a pseudo-random seed generated from slot hashes and other data to select winners.
+    /// Conducts the draw for the current lottery round. This function should be called after the `draw_timestamp` has passed
+    /// (or at any time once the round has sold out), in a later slot than `close_round_sales`.
+    /// It uses a pseudo-random seed generated from slot hashes and other data to select winners. Only the most recent slot
+    /// hash is used, and it must be from a slot at or after the one in which sales closed.
     /// Five winners are selected: one main winner and four other winners.
+    /// Anyone can call this instruction; the caller (keeper) is paid the crank bounty recorded on the round out of the pot.
+    /// The dispute window that opens after the draw also has the length recorded on the round when it started.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - The context containing accounts for this instruction.
     ///     * `lottery_config`: The main `LotteryConfig` account (to check pause status and current round ID).
     ///     * `lottery_round`: The current `LotteryRound` account (mutable to update winner information, randomness seed, and status).
+    ///     * `prize_vault`: The round's prize vault, which pays the crank bounty.
+    ///     * `lottery_stats`: The lottery's `LotteryStats` account, which counts the drawn round.
     ///     * `slot_hashes`: The SlotHashes sysvar, used as a source of on-chain randomness.
+    ///     * `keeper`: The signer running the draw, receives the crank bounty.
+    ///     * `system_program`: Required for the transfer out of the prize vault (CPI).
     ///
     /// ### Returns
     ///
     /// * `Ok(())` if the draw is conducted successfully and winners are selected.
-    /// * `Err` for various conditions like lottery paused, round still active, winners already drawn, draw time not reached, not enough participants, or issues during winner selection.
+    /// * `Err` for various conditions like lottery paused, sales not closed, winners already drawn, draw time not reached, not enough participants, or issues during winner selection.
     pub fn conduct_draw(ctx: Context<ConductDraw>) -> Result<()> {
         msg!("Instruction: Conduct Draw for Round {}", ctx.accounts.lottery_round.round_id);
         let lottery_config = &ctx.accounts.lottery_config; // Read-only access to config.
@@ -236,96 +831,104 @@ pub mod solana_lottery_contract {
 
         // Pre-condition checks for conducting the draw.
         require!(!lottery_config.is_paused, LotteryError::LotteryPaused);
-        // require!(lottery_round.is_active, LotteryError::LotteryRoundStillActive); // This check might be too strict if draw_timestamp has passed. The below check is better.
-        require!(!lottery_round.winners_drawn, LotteryError::WinnersAlreadyDrawn);
-        require!(clock.unix_timestamp >= lottery_round.draw_timestamp, LotteryError::DrawTimeNotReached);
+        require!(lottery_round.status != RoundStatus::Cancelled, LotteryError::RoundCancelled);
+        require!(
+            matches!(lottery_round.status, RoundStatus::Open | RoundStatus::SalesClosed),
+            LotteryError::WinnersAlreadyDrawn
+        );
+        require!(lottery_round.status == RoundStatus::SalesClosed, LotteryError::SalesNotClosed);
+        require!(
+            lottery_round.is_sold_out || clock.unix_timestamp >= lottery_round.draw_timestamp,
+            LotteryError::DrawTimeNotReached
+        );
         require!(lottery_round.participants.len() >= 5, LotteryError::NotEnoughParticipants); // Must have at least 5 participants to select 5 winners.
         require!(lottery_round.round_id == lottery_config.current_lottery_round_id, LotteryError::IncorrectLotteryRound);
 
-        // Generate randomness seed from slot hash and other data.
-        // Note: This is a pseudo-random method and may have limitations for high-value lotteries. Consider oracles for stronger randomness if needed.
-        let slot_hash_data = slot_hashes.data.borrow();
-        // According to Solana docs, the first 12 bytes are metadata (e.g., number of hashes), then hashes follow.
-        // We take a slice of the slot hash data as part of the seed.
-        let most_recent_slot_hash = &slot_hash_data[12..44]; // Using a 32-byte slice from slot hashes.
-        let mut seed_material = Vec::new();
-        seed_material.extend_from_slice(most_recent_slot_hash);
-        seed_material.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
-        seed_material.extend_from_slice(&(lottery_round.participants.len() as u64).to_le_bytes());
-        let randomness_seed = anchor_lang::solana_program::keccak::hash(&seed_material).to_bytes();
-        lottery_round.randomness_seed = Some(randomness_seed);
-
-        // Select 5 unique winners.
-        let num_participants = lottery_round.participants.len();
-        let mut winners_pubkeys: Vec<Pubkey> = Vec::new();
-        let mut selected_indices: Vec<usize> = Vec::new(); // To ensure unique winners.
-
-        // Loop to select 5 winners.
-        for i_val in 0..5u64 { // Iterate 5 times for 5 winners.
-            let mut attempts: u64 = 0; // Counter for attempts to find a unique winner for this slot.
-            loop {
-                // Create a unique seed for each winner selection attempt by incorporating the loop index and attempt count.
-                let mut index_seed_material = Vec::new();
-                index_seed_material.extend_from_slice(&randomness_seed);
-                index_seed_material.extend_from_slice(&i_val.to_le_bytes()); // Differentiate seed for each of the 5 winners.
-                index_seed_material.extend_from_slice(&attempts.to_le_bytes()); // Differentiate seed for each attempt if a collision occurs.
-                
-                let index_hash = anchor_lang::solana_program::keccak::hash(&index_seed_material).to_bytes();
-                // Convert the first 8 bytes of the hash to a u64.
-                let index_u64 = u64::from_le_bytes(index_hash[0..8].try_into().unwrap());
-                let winner_index = (index_u64 % num_participants as u64) as usize; // Modulo to get an index within the participants list.
-
-                if !selected_indices.contains(&winner_index) {
-                    selected_indices.push(winner_index);
-                    winners_pubkeys.push(lottery_round.participants[winner_index]);
-                    break; // Found a unique winner for this slot.
-                }
-                attempts += 1;
-                // Safety break to prevent infinite loops in unlikely scenarios (e.g., extreme hash collisions or logic error).
-                if attempts > (num_participants * 2) as u64 { 
-                    msg!("Error: Could not select unique winner after {} attempts for winner slot {}.", attempts, i_val);
-                    return err!(LotteryError::WinnerSelectionFailed); 
-                }
-            }
-        }
+        // Generate the randomness seed, select 5 unique winners and open the dispute window (if configured).
+        let sales_closed_slot = lottery_round.sales_closed_slot;
+        select_winners(
+            lottery_round,
+            slot_hashes,
+            &clock,
+            sales_closed_slot,
+            LotteryError::RandomnessPredatesSalesClose,
+        )?;
+        transition_round_status(lottery_round, RoundStatus::Drawn)?;
 
-        // Assign winners.
-        lottery_round.main_winner = Some(winners_pubkeys[0]);
-        lottery_round.other_winners = winners_pubkeys[1..5].to_vec(); // The next 4 are other winners.
-        lottery_round.winners_drawn = true;
-        lottery_round.is_active = false; // Mark the round as inactive after the draw.
+        // Pay the keeper who ran the draw.
+        let bounty = pay_crank_bounty(
+            lottery_round,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+        lottery_round.draw_keeper = Some(ctx.accounts.keeper.key());
+        lottery_round.draw_bounty_lamports = bounty;
+        let lottery_stats = &mut ctx.accounts.lottery_stats;
+        lottery_stats.total_rounds_drawn = lottery_stats.total_rounds_drawn.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
 
         msg!("Draw Conducted for Round {}. Main Winner: {}. Other Winners: {:?}", 
              lottery_round.round_id, lottery_round.main_winner.unwrap(), lottery_round.other_winners);
+        emit_draw_conducted(lottery_round, false);
         Ok(())
     }
 
     /// ## Distribute Prizes
     ///
-    /// Distributes the prize pool to the winners and the development fee receiver.
-    /// This function should be called after `conduct_draw` has successfully run and winners are set.
-    /// The prize distribution is as follows:
-    /// - 10% to Dev Fee Receiver
+    /// Distributes the prize pool to the winners and the platform, and credits the development fee to the lottery's
+    /// `FeeVault`, from which the administrator withdraws it with `withdraw_fees`.
+    /// This function should be called after `conduct_draw` has successfully run and winners are set,
+    /// and only once the round's dispute window (if any) has elapsed without the round being flagged.
+    /// The prize distribution follows the split recorded on the round when it started, by default:
+    /// - 10% Dev Fee, credited to the Fee Vault
     /// - 50% to Main Winner
     /// - 10% to each of the 4 Other Winners (total 40%)
     ///
+    /// Lotteries created with `create_lottery` use the split chosen by their creator instead, and pay the platform fee
+    /// to the `PlatformConfig` account.
+    ///
+    /// The rounding remainder of the split goes to the main winner, so the whole pot is paid out and the round's prize
+    /// vault is left with exactly its rent-exempt minimum. Every payment out of the vault is a System Program transfer
+    /// signed with the vault's seeds.
+    ///
+    /// If the main winner's share is below the round's `guaranteed_main_prize_lamports`, the treasury pays the difference.
+    ///
+    /// Each winner is paid at the payout address set in their `PlayerProfile` (their wallet by default), and the prize
+    /// is added to the profile's lifetime winnings. Winners without a profile, e.g. participants of a migrated legacy
+    /// round, are paid at their wallet. So is a winner whose payout address cannot receive the prize (see
+    /// `can_receive_prize`), so that one payout address cannot hold up the whole distribution.
+    ///
+    /// The lottery's `LotteryStats` are credited with the dev fee and the winners' prizes, and record the main prize
+    /// (including any top-up) if it is the largest jackpot so far.
+    ///
+    /// Anyone can call this instruction; the caller (keeper) is paid the crank bounty recorded on the round out of the
+    /// pot before the remainder is split.
+    ///
     /// ### Arguments
     ///
     /// * `ctx` - The context containing accounts for this instruction.
     ///     * `lottery_config`: The main `LotteryConfig` account.
-    ///     * `lottery_round`: The `LotteryRound` account from which prizes are distributed (mutable for lamport transfers and status update).
-    ///     * `dev_fee_receiver`: The account to receive development fees (must match `lottery_config.dev_fee_receiver`).
+    ///     * `lottery_round`: The `LotteryRound` account from which prizes are distributed (mutable for the status update).
+    ///     * `prize_vault`: The round's prize vault, which pays out the pot.
+    ///     * `fee_vault`: The lottery's `FeeVault` account, credited with the development fee.
+    ///     * `lottery_stats`: The lottery's `LotteryStats` account, updated with the round's payouts.
+    ///     * `treasury`: The `Treasury` account, which tops up the main prize to the guarantee.
+    ///     * `platform_config`: The global `PlatformConfig` account, which receives the platform fee.
+    ///     * `keeper`: The signer running the distribution, receives the crank bounty.
+    ///     * `system_program`: Required for the transfers out of the prize vault (CPI).
     ///     * `remaining_accounts`: A list of 5 `AccountInfo`s for the winners, in the order: Main Winner, Other Winner 1, Other Winner 2, Other Winner 3, Other Winner 4.
-    ///                          These accounts must be mutable and match the pubkeys stored in `lottery_round`.
+    ///                          These accounts must be mutable and be the payout addresses of the winners stored in `lottery_round`.
+    ///                          They are followed by the 5 winners' `PlayerProfile` PDAs in the same order (mutable, and passed
+    ///                          even if the winner has no profile), and then by the 5 winners' wallets in the same order
+    ///                          (mutable), which are paid instead of payout addresses that cannot receive the prize.
     ///
     /// ### Returns
     ///
     /// * `Ok(())` if prizes are distributed successfully.
-    /// * `Err` for various conditions like lottery paused, incorrect round, winners not drawn, prizes already distributed, incorrect winner accounts, or numeric overflow.
-    pub fn distribute_prizes(ctx: Context<DistributePrizes>) -> Result<()> {
+    /// * `Err` for various conditions like lottery paused, incorrect round, winners not drawn, prizes already distributed, dispute window still open, round disputed, incorrect winner accounts, or numeric overflow.
+    pub fn distribute_prizes<'info>(ctx: Context<'_, '_, 'info, 'info, DistributePrizes<'info>>) -> Result<()> {
         msg!("Instruction: Distribute Prizes for Round {}", ctx.accounts.lottery_round.round_id);
-        let lottery_config = &ctx.accounts.lottery_config;
-        let lottery_round_account_info = ctx.accounts.lottery_round.to_account_info(); // For direct lamport manipulation.
+        let round_id = ctx.accounts.lottery_round.round_id;
     
         // --- Read-only section for lottery_round data to avoid borrowing conflicts ---
         let main_winner_pubkey: Pubkey;
@@ -340,129 +943,2173 @@ pub mod solana_lottery_contract {
             total_pot = lottery_round_data.total_pot_lamports;
 
             // Pre-condition checks based on read-only data.
+            // Payouts wait until the dispute window has elapsed. When no window is configured the deadline equals the draw time.
+            require!(Clock::get()?.unix_timestamp >= lottery_round_data.dispute_deadline, LotteryError::DisputeWindowOpen);
             require!(other_winners_len == 4, LotteryError::IncorrectNumberOfWinnersSet); // Ensure 4 other winners are set.
             require!(total_pot > 0, LotteryError::ZeroPotAmount); // Ensure there's something to distribute.
         }
         // --- End of read-only section ---
 
         // Validate the number of winner accounts passed in `remaining_accounts`.
-        // Expecting 1 main winner + 4 other winners = 5 accounts.
-        require!(ctx.remaining_accounts.len() == 1 + 4, LotteryError::IncorrectNumberOfWinnerAccounts);
+        // Expecting 1 main winner + 4 other winners = 5 payout accounts, followed by the 5 winners' profiles and wallets.
+        require!(ctx.remaining_accounts.len() == 3 * WINNER_ACCOUNT_COUNT, LotteryError::IncorrectNumberOfWinnerAccounts);
+        let profile_accounts = &ctx.remaining_accounts[WINNER_ACCOUNT_COUNT..2 * WINNER_ACCOUNT_COUNT];
+        let wallet_accounts = &ctx.remaining_accounts[2 * WINNER_ACCOUNT_COUNT..];
+
+        // Load the winners' profiles; a winner is paid at the payout address set in their profile.
+        let mut winner_profiles = Vec::with_capacity(WINNER_ACCOUNT_COUNT);
+        for (winner, profile_account_info) in core::iter::once(&main_winner_pubkey).chain(&other_winners_snapshot).zip(profile_accounts) {
+            let profile = load_winner_profile(profile_account_info, winner)?;
+            winner_profiles.push((profile.as_ref().map_or(*winner, PlayerProfile::payout_address), profile));
+        }
 
-        // Validate the main winner account.
-        let main_winner_account_info = &ctx.remaining_accounts[0];
-        require!(main_winner_account_info.key() == main_winner_pubkey, LotteryError::IncorrectMainWinnerAccount);
+        // Validate the main winner accounts.
+        require!(ctx.remaining_accounts[0].key() == winner_profiles[0].0, LotteryError::IncorrectMainWinnerAccount);
+        require!(wallet_accounts[0].key() == main_winner_pubkey, LotteryError::IncorrectMainWinnerAccount);
 
         // Validate the other winner accounts.
-        for i in 0..4 {
-            let expected_pubkey = other_winners_snapshot[i];
-            let actual_account_info = &ctx.remaining_accounts[i + 1];
-            require!(actual_account_info.key() == expected_pubkey, LotteryError::IncorrectOtherWinnerAccount);
+        for (i, (payout_address, _)) in winner_profiles.iter().enumerate().skip(1) {
+            let actual_account_info = &ctx.remaining_accounts[i];
+            require!(actual_account_info.key() == *payout_address, LotteryError::IncorrectOtherWinnerAccount);
+            require!(wallet_accounts[i].key() == other_winners_snapshot[i - 1], LotteryError::IncorrectOtherWinnerAccount);
         }
         
-        // Calculate prize shares.
-        // Dev fee: 10%
-        let dev_share = total_pot.checked_mul(10).ok_or(LotteryError::NumericOverflow)?.checked_div(100).ok_or(LotteryError::NumericOverflow)?;
-        // Main winner: 50%
-        let main_winner_share = total_pot.checked_mul(50).ok_or(LotteryError::NumericOverflow)?.checked_div(100).ok_or(LotteryError::NumericOverflow)?;
-        // Each other winner: 10% (total 40% for 4 winners)
-        let other_winner_share = total_pot.checked_mul(10).ok_or(LotteryError::NumericOverflow)?.checked_div(100).ok_or(LotteryError::NumericOverflow)?;
-        
-        // Verify that the sum of shares does not exceed the total pot (due to potential rounding).
-        let mut total_to_distribute = dev_share.checked_add(main_winner_share).ok_or(LotteryError::NumericOverflow)?;
-        total_to_distribute = total_to_distribute.checked_add(other_winner_share.checked_mul(4).ok_or(LotteryError::NumericOverflow)?).ok_or(LotteryError::NumericOverflow)?;
-        require!(total_to_distribute <= total_pot, LotteryError::DistributionExceedsPot);
+        // Pay the keeper who runs the distribution before splitting the rest of the pot.
+        let bounty = pay_crank_bounty(
+            &mut ctx.accounts.lottery_round,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+        let prize_pot = total_pot.checked_sub(bounty).ok_or(LotteryError::NumericOverflow)?;
+
+        // Calculate prize shares according to the round's split (by default 10% dev fee, 50% main winner, 10% to each other winner).
+        // `split_pot_bps_exact` uses checked math and gives the rounding remainder to the main winner, so the shares add up to the pot.
+        let payout::PrizeSplit { platform_share, dev_share, main_winner_share, other_winner_share } =
+            payout::split_pot_bps_exact(prize_pot, &ctx.accounts.lottery_round.split()).map_err(LotteryError::from)?;
+        let guaranteed_main_prize = ctx.accounts.lottery_round.guaranteed_main_prize_lamports;
+        let top_up = guaranteed_main_prize.saturating_sub(main_winner_share);
+        let main_prize = main_winner_share.checked_add(top_up).ok_or(LotteryError::NumericOverflow)?;
+
+        // Pay each winner at their payout address, or at their wallet if the payout address cannot receive the prize.
+        let mut recipients = Vec::with_capacity(WINNER_ACCOUNT_COUNT);
+        for (i, (payout_account_info, wallet_account_info)) in ctx.remaining_accounts.iter().zip(wallet_accounts).enumerate() {
+            let prize = if i == 0 { main_prize } else { other_winner_share };
+            if payout_account_info.key() == wallet_account_info.key() || can_receive_prize(payout_account_info, prize)? {
+                recipients.push(payout_account_info);
+            } else {
+                msg!("Payout address {} cannot receive the prize; paying wallet {} instead", payout_account_info.key(), wallet_account_info.key());
+                recipients.push(wallet_account_info);
+            }
+        }
+        let main_winner_account_info = recipients[0];
 
         // --- Perform lamport transfers --- 
-        // Note: Direct lamport transfers require the accounts to be owned by the system program or the current program.
-        // The LotteryRound PDA holds the funds and is owned by this program.
+        // Note: The prize vault holds the funds and is owned by the System Program, so every payment out of it is a
+        // System Program transfer signed with the vault's seeds (see `pay_from_prize_vault`).
         // Winner accounts are external and must be mutable.
+        let lottery_round = &ctx.accounts.lottery_round;
+        let prize_vault = &ctx.accounts.prize_vault;
+        let system_program = &ctx.accounts.system_program;
 
-        // Transfer to dev fee receiver.
-        **lottery_round_account_info.try_borrow_mut_lamports()? -= dev_share;
-        **ctx.accounts.dev_fee_receiver.to_account_info().try_borrow_mut_lamports()? += dev_share;
-        msg!("Transferred {} lamports to dev fee receiver {}", dev_share, ctx.accounts.dev_fee_receiver.key());
+        // Credit the dev fee to the fee vault.
+        pay_from_prize_vault(lottery_round, prize_vault, &ctx.accounts.fee_vault.to_account_info(), system_program, dev_share)?;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        fee_vault.total_accrued_lamports = fee_vault.total_accrued_lamports.checked_add(dev_share).ok_or(LotteryError::NumericOverflow)?;
+        msg!("Credited {} lamports of dev fee to the fee vault", dev_share);
+        emit!(PrizePaid { round_id, recipient: fee_vault.key(), amount_lamports: dev_share, kind: PrizeKind::DevFee });
+
+        // Transfer the platform fee, if the lottery pays one.
+        if platform_share > 0 {
+            pay_from_prize_vault(lottery_round, prize_vault, &ctx.accounts.platform_config.to_account_info(), system_program, platform_share)?;
+            msg!("Transferred {} lamports of platform fee", platform_share);
+            emit!(PrizePaid { round_id, recipient: ctx.accounts.platform_config.key(), amount_lamports: platform_share, kind: PrizeKind::PlatformFee });
+        }
 
         // Transfer to main winner.
-        **lottery_round_account_info.try_borrow_mut_lamports()? -= main_winner_share;
-        **main_winner_account_info.try_borrow_mut_lamports()? += main_winner_share;
+        pay_from_prize_vault(lottery_round, prize_vault, main_winner_account_info, system_program, main_winner_share)?;
         msg!("Transferred {} lamports to main winner {}", main_winner_share, main_winner_account_info.key());
+        emit!(PrizePaid { round_id, recipient: main_winner_account_info.key(), amount_lamports: main_winner_share, kind: PrizeKind::MainWinner });
+
+        // Top up the main prize from the treasury if it falls short of the round's guarantee.
+        if top_up > 0 {
+            **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= top_up;
+            **main_winner_account_info.try_borrow_mut_lamports()? += top_up;
+            msg!("Topped up main prize with {} lamports from the treasury", top_up);
+            emit!(PrizePaid { round_id, recipient: main_winner_account_info.key(), amount_lamports: top_up, kind: PrizeKind::GuaranteeTopUp });
+        }
+        release_guarantee(&mut ctx.accounts.treasury, guaranteed_main_prize);
 
         // Transfer to other winners.
-        for i in 0..4 {
-            let other_winner_account_info = &ctx.remaining_accounts[i + 1];
-            **lottery_round_account_info.try_borrow_mut_lamports()? -= other_winner_share;
-            **other_winner_account_info.try_borrow_mut_lamports()? += other_winner_share;
+        for other_winner_account_info in &recipients[1..] {
+            pay_from_prize_vault(lottery_round, prize_vault, other_winner_account_info, system_program, other_winner_share)?;
             msg!("Transferred {} lamports to other winner {}", other_winner_share, other_winner_account_info.key());
+            emit!(PrizePaid { round_id, recipient: other_winner_account_info.key(), amount_lamports: other_winner_share, kind: PrizeKind::OtherWinner });
         }
         // --- End of lamport transfers ---
-        
+
+        // Add the prizes to the winners' lifetime winnings and the round's payouts to the lottery's lifetime statistics.
+        for (i, ((_, profile), profile_account_info)) in winner_profiles.iter_mut().zip(profile_accounts).enumerate() {
+            if let Some(profile) = profile {
+                let prize = if i == 0 { main_prize } else { other_winner_share };
+                profile.lifetime_winnings_lamports = profile.lifetime_winnings_lamports.checked_add(prize).ok_or(LotteryError::NumericOverflow)?;
+                profile.try_serialize(&mut &mut profile_account_info.try_borrow_mut_data()?[..])?;
+            }
+        }
+        let paid_to_winners = other_winner_share
+            .checked_mul(4)
+            .and_then(|others| others.checked_add(main_prize))
+            .ok_or(LotteryError::NumericOverflow)?;
+        let lottery_stats = &mut ctx.accounts.lottery_stats;
+        lottery_stats.total_paid_to_winners_lamports =
+            lottery_stats.total_paid_to_winners_lamports.checked_add(paid_to_winners).ok_or(LotteryError::NumericOverflow)?;
+        lottery_stats.total_dev_fees_lamports = lottery_stats.total_dev_fees_lamports.checked_add(dev_share).ok_or(LotteryError::NumericOverflow)?;
+        if main_prize > lottery_stats.largest_jackpot_lamports {
+            lottery_stats.largest_jackpot_lamports = main_prize;
+            lottery_stats.largest_jackpot_round_id = round_id;
+        }
+
         // Mark prizes as distributed in the LotteryRound account.
         let lottery_round_data_mut = &mut ctx.accounts.lottery_round; // Mutable borrow for writing the final flag.
-        lottery_round_data_mut.prizes_distributed = true;
+        transition_round_status(lottery_round_data_mut, RoundStatus::Distributed)?;
+        lottery_round_data_mut.distribution_keeper = Some(ctx.accounts.keeper.key());
+        lottery_round_data_mut.distribution_bounty_lamports = bounty;
         msg!("Prizes Distributed Successfully for Round {}", lottery_round_data_mut.round_id);
-        Ok(())
+        emit!(RoundClosed {
+            round_id,
+            total_pot_lamports: total_pot,
+            participant_count: lottery_round_data_mut.participants.len() as u32,
+            reason: RoundCloseReason::PrizesDistributed,
+        });
+        check_round_solvency(lottery_round_data_mut, &ctx.accounts.prize_vault)
     }
-}
 
-/// ## InitializeConfig Accounts
-/// Defines the accounts required for the `initialize_config` instruction.
-#[derive(Accounts)]
-pub struct InitializeConfig<'info> { // Added 'info lifetime explicitly for clarity, though often inferred.
-    /// The `LotteryConfig` account to be created and initialized.
-    /// It's a Program Derived Address (PDA) seeded with "lottery_config".
-    /// `init` constraint means this account will be created by this instruction.
-    /// `payer = admin` means the `admin` account will pay for its creation.
-    /// `space` defines the initial disk space allocation for the account.
-    /// `seeds` and `bump` are used for PDA derivation and validation.
-    #[account(init, payer = admin, space = 8 + LotteryConfig::INIT_SPACE, seeds = [b"lottery_config"], bump)]
-    pub lottery_config: Account<'info, LotteryConfig>,
-    /// The signer account that will become the administrator of the lottery.
-    /// `mut` indicates this account's lamports may be debited (for account creation rent).
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    /// The Solana System Program, required by Anchor for account creation and other system-level operations.
-    pub system_program: Program<'info, System>,
-}
+    /// ## Set Crank Bounty
+    ///
+    /// Allows the administrator to configure the bounty paid to whoever runs `conduct_draw` or `distribute_prizes`.
+    /// The bounty is `bounty_lamports + pot * bounty_bps / 10_000`, capped at `bounty_cap_lamports`, and is taken from the pot.
+    /// Whatever the configuration, a single bounty never takes more than `MAX_CRANK_BOUNTY_BPS` of the pot.
+    /// Rounds record the bounty when they start, so the update applies from the next round on.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `bounty_lamports` - Flat part of the bounty, in lamports (at most `MAX_CRANK_BOUNTY_LAMPORTS`).
+    /// * `bounty_bps` - Percentage part of the bounty, in basis points of the pot (at most `MAX_CRANK_BOUNTY_BPS`).
+    /// * `bounty_cap_lamports` - Maximum bounty paid per crank, in lamports (at most `MAX_CRANK_BOUNTY_LAMPORTS`).
+    ///   `0` disables the bounty.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the update is successful.
+    /// * `Err` if the signer is not the admin or the flat amount, basis points or cap exceed their maximum.
+    pub fn set_crank_bounty(
+        ctx: Context<UpdateConfig>,
+        bounty_lamports: u64,
+        bounty_bps: u16,
+        bounty_cap_lamports: u64,
+    ) -> Result<()> {
+        msg!("Instruction: Set Crank Bounty");
+        let lottery_config = &mut ctx.accounts.lottery_config;
 
-/// ## UpdateConfig Accounts
-/// Defines the accounts required for the `update_config` instruction.
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    /// The `LotteryConfig` account to be updated.
-    /// `mut` indicates its data will be modified.
-    /// `seeds` and `bump` ensure we are operating on the correct PDA, using the bump stored in `lottery_config` itself.
-    #[account(mut, seeds = [b"lottery_config"], bump = lottery_config.bump)]
-    pub lottery_config: Account<'info, LotteryConfig>,
-    /// The signer account, must match the `admin_key` stored in `lottery_config`.
-    pub admin: Signer<'info>,
-}
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(bounty_bps <= payout::MAX_CRANK_BOUNTY_BPS, LotteryError::CrankBountyTooHigh);
+        require!(
+            bounty_lamports <= payout::MAX_CRANK_BOUNTY_LAMPORTS && bounty_cap_lamports <= payout::MAX_CRANK_BOUNTY_LAMPORTS,
+            LotteryError::CrankBountyTooHigh
+        );
 
-/// ## StartNewLotteryRound Accounts
-/// Defines the accounts required for the `start_new_lottery_round` instruction.
-#[derive(Accounts)]
-#[instruction(draw_timestamp_offset_seconds: i64, next_round_id_arg: u64)] // Instruction arguments used in account constraints/seeds.
-pub struct StartNewLotteryRound<'info> {
-    /// The main `LotteryConfig` account.
-    /// `mut` because `current_lottery_round_id` is updated.
-    #[account(mut, seeds = [b"lottery_config"], bump = lottery_config.bump)]
-    pub lottery_config: Account<'info, LotteryConfig>,
-    /// The new `LotteryRound` account to be initialized.
-    /// It's a PDA seeded with "lottery_round" and the `next_round_id_arg`.
-    /// `init` constraint means this account will be created.
+        emit!(CrankBountyUpdated {
+            old_bounty_lamports: lottery_config.crank_bounty_lamports,
+            new_bounty_lamports: bounty_lamports,
+            old_bounty_bps: lottery_config.crank_bounty_bps,
+            new_bounty_bps: bounty_bps,
+            old_bounty_cap_lamports: lottery_config.crank_bounty_cap_lamports,
+            new_bounty_cap_lamports: bounty_cap_lamports,
+        });
+        lottery_config.crank_bounty_lamports = bounty_lamports;
+        lottery_config.crank_bounty_bps = bounty_bps;
+        lottery_config.crank_bounty_cap_lamports = bounty_cap_lamports;
+        msg!("Crank bounty updated. Flat: {} lamports, Percentage: {} bps, Cap: {} lamports",
+             bounty_lamports, bounty_bps, bounty_cap_lamports);
+        Ok(())
+    }
+
+    /// ## Set Dispute Configuration
+    ///
+    /// Allows the administrator to configure the challenge period that follows every draw.
+    /// While the window is open, `distribute_prizes` is blocked and the admin or the auditor can flag the round.
+    /// Rounds record the window length when they start, so a new length applies from the next round on.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `auditor_key` - The designated auditor allowed to flag rounds in addition to the admin. `None` removes the auditor.
+    /// * `dispute_window_seconds` - Length of the challenge period after a draw, in seconds. `0` disables the window.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the update is successful.
+    /// * `Err` if the signer is not the admin or the window is negative.
+    pub fn set_dispute_config(
+        ctx: Context<UpdateConfig>,
+        auditor_key: Option<Pubkey>,
+        dispute_window_seconds: i64,
+    ) -> Result<()> {
+        msg!("Instruction: Set Dispute Config");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(dispute_window_seconds >= 0, LotteryError::InvalidDisputeWindow);
+
+        emit!(DisputeConfigUpdated {
+            old_auditor_key: lottery_config.auditor_key,
+            new_auditor_key: auditor_key,
+            old_dispute_window_seconds: lottery_config.dispute_window_seconds,
+            new_dispute_window_seconds: dispute_window_seconds,
+        });
+        lottery_config.auditor_key = auditor_key;
+        lottery_config.dispute_window_seconds = dispute_window_seconds;
+        msg!("Dispute config updated. Auditor: {:?}, Window: {} seconds", auditor_key, dispute_window_seconds);
+        Ok(())
+    }
+
+    /// ## Flag Round
+    ///
+    /// Flags the current round as disputed during its dispute window. A flagged round cannot be paid out
+    /// until the admin resolves it with `redraw_round` or `cancel_round`. The slot of the flag is recorded, so that a
+    /// redraw uses randomness produced after it. A round that was already redrawn can only be cancelled.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (to check the admin and auditor keys).
+    ///     * `lottery_round`: The current `LotteryRound` account (mutable to set the dispute flag).
+    ///     * `authority`: The signer account, must be the admin or the designated auditor.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the round is flagged.
+    /// * `Err` if the signer is not authorized, the round is not drawn, already paid out, already flagged, or the window has closed.
+    pub fn flag_round(ctx: Context<FlagRound>) -> Result<()> {
+        msg!("Instruction: Flag Round {}", ctx.accounts.lottery_round.round_id);
+        let lottery_config = &ctx.accounts.lottery_config;
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        let authority = ctx.accounts.authority.key();
+        let clock = Clock::get()?;
+
+        require!(
+            authority == lottery_config.admin_key || Some(authority) == lottery_config.auditor_key,
+            LotteryError::UnauthorizedDisputeAuthority
+        );
+        require!(!lottery_round.is_disputed, LotteryError::RoundDisputed);
+        require!(clock.unix_timestamp < lottery_round.dispute_deadline, LotteryError::DisputeWindowClosed);
+
+        lottery_round.is_disputed = true;
+        lottery_round.flagged_slot = clock.slot;
+        msg!("Round {} flagged as disputed by {} in slot {}", lottery_round.round_id, authority, clock.slot);
+        emit!(RoundFlagged { round_id: lottery_round.round_id, flagged_by: authority, slot: clock.slot });
+        Ok(())
+    }
+
+    /// ## Redraw Round
+    ///
+    /// Resolves a disputed round by discarding the flagged result and drawing new winners from fresh randomness.
+    /// The slot hash used must come from a slot after the flag, and its slot is recorded on the round.
+    /// A new dispute window is opened for the new result. Each round can be redrawn only once, so the admin cannot keep
+    /// flagging and redrawing until a winner they like comes up; a redrawn round that is flagged again must be
+    /// cancelled with `cancel_round`.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account.
+    ///     * `lottery_round`: The disputed `LotteryRound` account (mutable to replace the winners and seed).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    ///     * `slot_hashes`: The SlotHashes sysvar, used as a source of on-chain randomness.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if new winners are drawn.
+    /// * `Err` if the signer is not the admin, the round is not disputed or was already redrawn, the most recent slot
+    ///   hash does not come after the flag, or winner selection fails.
+    pub fn redraw_round(ctx: Context<RedrawRound>) -> Result<()> {
+        msg!("Instruction: Redraw Round {}", ctx.accounts.lottery_round.round_id);
+        let lottery_config = &ctx.accounts.lottery_config;
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        let clock = Clock::get()?;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(lottery_round.redraw_slot == 0, LotteryError::RoundAlreadyRedrawn);
+
+        let previous_seed = lottery_round.randomness_seed;
+        let earliest_slot = lottery_round.flagged_slot.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
+        lottery_round.redraw_slot = select_winners(
+            lottery_round,
+            &ctx.accounts.slot_hashes,
+            &clock,
+            earliest_slot,
+            LotteryError::RandomnessPredatesFlag,
+        )?;
+        require!(lottery_round.randomness_seed != previous_seed, LotteryError::StaleRandomness);
+        lottery_round.is_disputed = false;
+
+        msg!("Round {} redrawn. Main Winner: {}. Other Winners: {:?}",
+             lottery_round.round_id, lottery_round.main_winner.unwrap(), lottery_round.other_winners);
+        emit_draw_conducted(lottery_round, true);
+        Ok(())
+    }
+
+    /// ## Cancel Round
+    ///
+    /// Resolves a disputed round by cancelling it. No prizes are paid; instead every participant can be refunded
+    /// an equal share of the pot with `refund_participants`.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account.
+    ///     * `lottery_round`: The disputed `LotteryRound` account (mutable to mark it cancelled).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    ///     * `treasury`: The `Treasury` account (mutable to release the round's guaranteed main prize).
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the round is cancelled.
+    /// * `Err` if the signer is not the admin or the round is not disputed.
+    pub fn cancel_round(ctx: Context<ResolveDispute>) -> Result<()> {
+        msg!("Instruction: Cancel Round {}", ctx.accounts.lottery_round.round_id);
+        let lottery_config = &ctx.accounts.lottery_config;
+        let lottery_round = &mut ctx.accounts.lottery_round;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        mark_cancelled(lottery_round, &mut ctx.accounts.treasury)?;
+        lottery_round.is_disputed = false;
+        msg!("Round {} cancelled. Refund per participant: {} lamports",
+             lottery_round.round_id, lottery_round.refund_per_participant_lamports);
+        emit!(RoundCancelled {
+            round_id: lottery_round.round_id,
+            participant_count: lottery_round.participants.len() as u32,
+            refund_per_participant_lamports: lottery_round.refund_per_participant_lamports,
+        });
+        Ok(())
+    }
+
+    /// ## Refund Participants
+    ///
+    /// Refunds the next batch of a cancelled round. This instruction is permissionless so that refunds can be cranked
+    /// by anyone. Participants are refunded first, in entry order, each receiving `refund_per_participant_lamports`
+    /// (the first participant also receives the rounding remainder of the equal split); then every pot contributor, in
+    /// order of `lottery_round.contributions`, receives their contribution back. A crank bounty already paid out of the
+    /// pot is borne by participants and contributors in proportion to what they put in.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_round`: The cancelled `LotteryRound` account (PDA: seeds = ["lottery_round", lottery_round.lottery_config, round_id]).
+    ///     * `prize_vault`: The round's prize vault, which pays the refunds.
+    ///     * `system_program`: Required for the transfers out of the prize vault (CPI).
+    ///     * `remaining_accounts`: The next accounts to refund, starting at `lottery_round.refunds_processed`, in the
+    ///                          order above (see `LotteryRound::refund_recipient`). These accounts must be mutable.
+    /// * `round_id` - The ID of the cancelled round, used to derive its PDA.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the batch is refunded.
+    /// * `Err` if the round is not cancelled, all refunds were already processed, or an account is out of order.
+    pub fn refund_participants<'info>(ctx: Context<'_, '_, 'info, 'info, RefundParticipants<'info>>, round_id: u64) -> Result<()> {
+        msg!("Instruction: Refund Participants for Round {}", round_id);
+        let lottery_round = &mut ctx.accounts.lottery_round;
+
+        let start = lottery_round.refunds_processed as usize;
+        let refund_count = lottery_round.refund_count();
+        require!(start < refund_count, LotteryError::RefundsComplete);
+        require!(
+            !ctx.remaining_accounts.is_empty() && start + ctx.remaining_accounts.len() <= refund_count,
+            LotteryError::IncorrectNumberOfParticipantAccounts
+        );
+
+        for (offset, recipient_account_info) in ctx.remaining_accounts.iter().enumerate() {
+            let (recipient, refund, is_contribution) =
+                lottery_round.refund_recipient(start + offset).ok_or(LotteryError::IncorrectNumberOfParticipantAccounts)?;
+            require!(recipient_account_info.key() == recipient, LotteryError::IncorrectParticipantAccount);
+            pay_from_prize_vault(lottery_round, &ctx.accounts.prize_vault, recipient_account_info, &ctx.accounts.system_program, refund)?;
+            emit!(RefundPaid { round_id, participant: recipient, amount_lamports: refund, is_contribution });
+        }
+        lottery_round.refunds_processed = (start + ctx.remaining_accounts.len()) as u32;
+
+        msg!("Refunded {} accounts of round {} ({} of {} done)",
+             ctx.remaining_accounts.len(), round_id, lottery_round.refunds_processed, refund_count);
+        if lottery_round.refunds_processed as usize == refund_count {
+            emit!(RoundClosed {
+                round_id,
+                total_pot_lamports: lottery_round.total_pot_lamports,
+                participant_count: lottery_round.participants.len() as u32,
+                reason: RoundCloseReason::Refunded,
+            });
+        }
+        check_round_solvency(lottery_round, &ctx.accounts.prize_vault)
+    }
+
+    /// ## Reconcile Round
+    ///
+    /// Compares the lamports held by a round's prize vault with what it must hold: its rent-exempt minimum plus the part
+    /// of the pot it has not paid out yet (see `LotteryRound::escrowed_lamports`). Any surplus, e.g. from a transfer
+    /// sent straight to the vault, is moved to the lottery's treasury. A shortfall cannot be made up by the program
+    /// and is only reported. Either way a `RoundReconciled` event records the outcome.
+    ///
+    /// Anyone can call this instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (to derive the round and treasury PDAs).
+    ///     * `lottery_round`: The `LotteryRound` account to reconcile (PDA: seeds = ["lottery_round", lottery_config, round_id]).
+    ///     * `prize_vault`: The round's prize vault, whose balance is reconciled.
+    ///     * `treasury`: The lottery's `Treasury` account, which receives any surplus.
+    ///     * `system_program`: Required for the transfer out of the prize vault (CPI).
+    /// * `round_id` - The ID of the round to reconcile, used to derive its PDA.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` once the balance is reconciled or the shortfall reported.
+    pub fn reconcile_round(ctx: Context<ReconcileRound>, round_id: u64) -> Result<()> {
+        msg!("Instruction: Reconcile Round {}", round_id);
+        let prize_vault = &ctx.accounts.prize_vault;
+        let balance_lamports = prize_vault.lamports();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(prize_vault.data_len());
+        let escrowed_lamports = ctx.accounts.lottery_round.escrowed_lamports().ok_or(LotteryError::NumericOverflow)?;
+
+        let (surplus_lamports, shortfall_lamports) =
+            match payout::reconcile_escrow(balance_lamports, rent_exempt_minimum, escrowed_lamports).map_err(LotteryError::from)? {
+                payout::EscrowBalance::Exact => (0, 0),
+                payout::EscrowBalance::Surplus(surplus) => (surplus, 0),
+                payout::EscrowBalance::Shortfall(shortfall) => (0, shortfall),
+            };
+        if surplus_lamports > 0 {
+            pay_from_prize_vault(
+                &ctx.accounts.lottery_round,
+                prize_vault,
+                &ctx.accounts.treasury.to_account_info(),
+                &ctx.accounts.system_program,
+                surplus_lamports,
+            )?;
+            msg!("Moved a surplus of {} lamports from round {} to the treasury", surplus_lamports, round_id);
+        }
+        if shortfall_lamports > 0 {
+            msg!("Round {} is short of {} lamports", round_id, shortfall_lamports);
+        }
+        emit!(RoundReconciled {
+            round_id,
+            balance_lamports,
+            rent_exempt_minimum_lamports: rent_exempt_minimum,
+            escrowed_lamports,
+            surplus_lamports,
+            shortfall_lamports,
+        });
+        Ok(())
+    }
+
+    /// ## Migrate Config
+    ///
+    /// Moves the singleton `LotteryConfig` of program versions before lotteries had IDs (PDA: seeds = ["lottery_config"])
+    /// to a `LotteryConfig` keyed by `lottery_id`. The admin, dev fee receiver, entry fee, paused flag and round counter
+    /// carry over; everything that did not exist yet starts out as in `initialize_config`. The new lottery is marked as
+    /// the legacy lottery, which lets `migrate_round` adopt the rounds of the old singleton config.
+    /// The legacy account is closed and its rent returned to the admin, so the migration can happen only once.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `legacy_lottery_config`: The singleton `LotteryConfig` account in the legacy layout (PDA: seeds = ["lottery_config"]).
+    ///     * `lottery_config`: The `LotteryConfig` account to be created (PDA: seeds = ["lottery_config", lottery_id]).
+    ///     * `admin`: The signer account, must match the legacy config's `admin_key`. Pays the rent of the new config.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    /// * `lottery_id` - The identifier the legacy lottery continues under. Must be unused.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the config is migrated.
+    /// * `Err` if the signer is not the legacy admin or the account is not a valid legacy config.
+    pub fn migrate_config(ctx: Context<MigrateConfig>, lottery_id: u64) -> Result<()> {
+        msg!("Instruction: Migrate Config to Lottery {}", lottery_id);
+        let legacy_account_info = ctx.accounts.legacy_lottery_config.to_account_info();
+        let legacy = {
+            let data = legacy_account_info.try_borrow_data()?;
+            require!(
+                data.len() == 8 + LegacyLotteryConfig::INIT_SPACE && data[..8] == *LotteryConfig::DISCRIMINATOR,
+                LotteryError::UnrecognizedConfigLayout
+            );
+            LegacyLotteryConfig::deserialize(&mut &data[8..]).map_err(|_| LotteryError::UnrecognizedConfigLayout)?
+        };
+        require!(legacy.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        let lottery_config = &mut ctx.accounts.lottery_config;
+        init_lottery_config(lottery_config, lottery_id, legacy.admin_key, legacy.dev_fee_receiver, legacy.entry_fee_lamports, ctx.bumps.lottery_config);
+        lottery_config.is_paused = legacy.is_paused;
+        lottery_config.current_lottery_round_id = legacy.current_lottery_round_id; // New rounds continue the legacy numbering.
+        lottery_config.is_legacy_lottery = true;
+
+        close_legacy_account(&legacy_account_info, &ctx.accounts.admin.to_account_info())?;
+
+        msg!("Legacy config migrated to lottery {} at round {}", lottery_id, lottery_config.current_lottery_round_id);
+        emit!(ConfigMigrated {
+            lottery_id,
+            lottery_config: lottery_config.key(),
+            admin: lottery_config.admin_key,
+            current_lottery_round_id: lottery_config.current_lottery_round_id,
+        });
+        Ok(())
+    }
+
+    /// ## Migrate Round
+    ///
+    /// Moves a `LotteryRound` created before `RoundStatus` existed (PDA: seeds = ["lottery_round", round_id]) into the
+    /// current layout at the address the legacy lottery's rounds use now. Only the lottery created by `migrate_config`
+    /// can adopt legacy rounds.
+    /// The legacy `is_active`, `winners_drawn` and `prizes_distributed` flags are mapped to a status (see
+    /// `LegacyLotteryRound::status`). The admin pays the rent of the new round account and of the round's prize vault,
+    /// which receives the pot in full. The legacy account is then closed and the rest of its balance, its rent, returned
+    /// to the admin who paid it. Fields that did not exist yet start out empty; in particular the migrated round has
+    /// no dispute window.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` created by `migrate_config` (to check the admin).
+    ///     * `legacy_lottery_round`: The legacy `LotteryRound` account (PDA: seeds = ["lottery_round", round_id]).
+    ///     * `lottery_round`: The `LotteryRound` account to be created (PDA: seeds = ["lottery_round", lottery_config, round_id]).
+    ///     * `prize_vault`: The round's prize vault (PDA: seeds = ["prize_vault", lottery_round]), which receives the pot.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Pays the rent of the new accounts.
+    ///     * `system_program`: Required for account initialization and the vault rent transfer (CPI).
+    /// * `round_id` - The ID of the round to migrate, used to derive both PDAs.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the round is migrated.
+    /// * `Err` if the lottery is not the legacy lottery, the signer is not the admin, the round is already migrated, or
+    ///   its data is not a valid legacy round.
+    pub fn migrate_round(ctx: Context<MigrateRound>, round_id: u64) -> Result<()> {
+        msg!("Instruction: Migrate Round {}", round_id);
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        let legacy_account_info = ctx.accounts.legacy_lottery_round.to_account_info();
+        let legacy = {
+            let data = legacy_account_info.try_borrow_data()?;
+            require!(
+                data.len() == 8 + LegacyLotteryRound::INIT_SPACE && data[..8] == *LotteryRound::DISCRIMINATOR,
+                LotteryError::UnrecognizedRoundLayout
+            );
+            LegacyLotteryRound::deserialize(&mut &data[8..]).map_err(|_| LotteryError::UnrecognizedRoundLayout)?
+        };
+        require!(legacy.round_id == round_id, LotteryError::IncorrectLotteryRound);
+
+        // Rounds past their sales close get the participant commitment they would have received at that point.
+        let status = legacy.status()?;
+        let participants_root = match status {
+            RoundStatus::Open => [0u8; 32],
+            _ => participants_merkle_root(&legacy.participants)?,
+        };
+        let is_sold_out = legacy.participants.len() >= MAX_PARTICIPANTS;
+        let lottery_config = &ctx.accounts.lottery_config;
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        lottery_round.set_inner(LotteryRound {
+            lottery_config: lottery_config.key(),
+            round_id: legacy.round_id,
+            start_timestamp: legacy.start_timestamp,
+            draw_timestamp: legacy.draw_timestamp,
+            sales_close_timestamp: legacy.draw_timestamp, // Legacy rounds sold tickets until the draw.
+            total_pot_lamports: legacy.total_pot_lamports,
+            status,
+            participants: legacy.participants,
+            main_winner: legacy.main_winner,
+            other_winners: legacy.other_winners,
+            randomness_seed: legacy.randomness_seed,
+            bump: ctx.bumps.lottery_round,
+            dispute_deadline: 0,
+            is_disputed: false,
+            refund_per_participant_lamports: 0,
+            refunds_processed: 0,
+            draw_keeper: None,
+            draw_bounty_lamports: 0,
+            distribution_keeper: None,
+            distribution_bounty_lamports: 0,
+            rent_payer: lottery_config.admin_key, // The admin pays the rent of the migrated round.
+            participants_root,
+            sales_closed_slot: 0,
+            extensions: Vec::new(),
+            is_sold_out,
+            contributions: Vec::new(),
+            guaranteed_main_prize_lamports: 0,
+            // Legacy rounds did not record their parameters; they take the lottery's current ones.
+            entry_fee_lamports: lottery_config.entry_fee_lamports,
+            platform_fee_bps: lottery_config.platform_fee_bps,
+            dev_fee_bps: lottery_config.dev_fee_bps,
+            main_winner_bps: lottery_config.main_winner_bps,
+            prize_vault_bump: ctx.bumps.prize_vault,
+            flagged_slot: 0,
+            redraw_slot: 0,
+            dispute_window_seconds: lottery_config.dispute_window_seconds,
+            crank_bounty_lamports: lottery_config.crank_bounty_lamports,
+            crank_bounty_bps: lottery_config.crank_bounty_bps,
+            crank_bounty_cap_lamports: lottery_config.crank_bounty_cap_lamports,
+        });
+
+        // Move the pot into the prize vault. The admin pays the vault's rent, so the pot moves over in full.
+        let vault_rent_lamports = prize_vault_rent_due(&ctx.accounts.prize_vault)?;
+        if vault_rent_lamports > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: ctx.accounts.prize_vault.to_account_info(),
+                },
+            );
+            system_program::transfer(cpi_context, vault_rent_lamports)?;
+        }
+        let escrowed_lamports = lottery_round.escrowed_lamports().ok_or(LotteryError::NumericOverflow)?;
+        let legacy_lamports = legacy_account_info.lamports();
+        **legacy_account_info.try_borrow_mut_lamports()? =
+            legacy_lamports.checked_sub(escrowed_lamports).ok_or(LotteryError::LegacyRoundInsolvent)?;
+        **ctx.accounts.prize_vault.to_account_info().try_borrow_mut_lamports()? += escrowed_lamports;
+
+        // Legacy rounds were always paid for by the admin in `start_new_lottery_round`.
+        close_legacy_account(&legacy_account_info, &ctx.accounts.admin.to_account_info())?;
+
+        msg!("Round {} migrated with status {:?}", round_id, lottery_round.status);
+        emit!(RoundMigrated { round_id, status: lottery_round.status });
+        Ok(())
+    }
+
+    /// ## Close Round
+    ///
+    /// Closes a finished `LotteryRound` and returns its rent to whoever paid for it. A round is finished once its
+    /// prizes have been distributed, or once it was cancelled and every participant has been refunded.
+    /// Before the large round account is closed, a compact `RoundResult` PDA is written with the round's outcome,
+    /// winners, randomness seed and a hash of its participant list, so the result stays on-chain.
+    /// The round's prize vault no longer escrows anything by then; its rent and any lamports sent to it go to the rent
+    /// payer together with the rent of the round account.
+    ///
+    /// This instruction is permissionless; the caller only pays the rent of the small `RoundResult` account.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_round`: The finished `LotteryRound` account to close (PDA: seeds = ["lottery_round", lottery_round.lottery_config, round_id]).
+    ///     * `round_result`: The `RoundResult` account to create (PDA: seeds = ["round_result", lottery_round.lottery_config, round_id]).
+    ///     * `prize_vault`: The round's prize vault, emptied to the rent payer.
+    ///     * `rent_payer`: The account that paid the round's rent (must match `lottery_round.rent_payer`).
+    ///     * `payer`: The signer paying for the `RoundResult` account.
+    ///     * `system_program`: Required by Anchor for account initialization and the transfer out of the prize vault.
+    /// * `round_id` - The ID of the round to close, used to derive its PDAs.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the result is recorded and the round is closed.
+    /// * `Err` if the round is not finished, refunds are still pending, or the rent payer does not match.
+    pub fn close_round(ctx: Context<CloseRound>, round_id: u64) -> Result<()> {
+        msg!("Instruction: Close Round {}", round_id);
+        let lottery_round = &mut ctx.accounts.lottery_round;
+        let round_result = &mut ctx.accounts.round_result;
+
+        let outcome = lottery_round.status;
+        require!(
+            matches!(outcome, RoundStatus::Distributed | RoundStatus::Cancelled),
+            LotteryError::RoundNotFinished
+        );
+        if outcome == RoundStatus::Cancelled {
+            require!(
+                lottery_round.refunds_processed as usize == lottery_round.refund_count(),
+                LotteryError::RefundsIncomplete
+            );
+        }
+
+        round_result.lottery_config = lottery_round.lottery_config;
+        round_result.round_id = round_id;
+        round_result.outcome = outcome;
+        round_result.total_pot_lamports = lottery_round.total_pot_lamports;
+        round_result.participant_count = lottery_round.participants.len() as u32;
+        round_result.participants_hash = participants_hash(&lottery_round.participants);
+        round_result.participants_root = lottery_round.participants_root;
+        round_result.main_winner = lottery_round.main_winner;
+        round_result.other_winners = lottery_round.other_winners.clone();
+        round_result.randomness_seed = lottery_round.randomness_seed;
+        round_result.closed_timestamp = Clock::get()?.unix_timestamp;
+        round_result.bump = ctx.bumps.round_result;
+
+        // The account itself is closed to `rent_payer` by the `close` constraint once the instruction returns.
+        transition_round_status(lottery_round, RoundStatus::Closed)?;
+
+        // A distributed or fully refunded round escrows nothing, so only the vault's rent and stray lamports are left.
+        let vault_lamports = ctx.accounts.prize_vault.lamports();
+        if vault_lamports > 0 {
+            pay_from_prize_vault(lottery_round, &ctx.accounts.prize_vault, &ctx.accounts.rent_payer, &ctx.accounts.system_program, vault_lamports)?;
+        }
+        let reclaimed_lamports = lottery_round.to_account_info().lamports().checked_add(vault_lamports).ok_or(LotteryError::NumericOverflow)?;
+
+        msg!("Round {} closed. {} lamports returned to {}", round_id, reclaimed_lamports, ctx.accounts.rent_payer.key());
+        emit!(RoundResultRecorded {
+            round_id,
+            outcome,
+            round_result: round_result.key(),
+            rent_payer: ctx.accounts.rent_payer.key(),
+            reclaimed_lamports,
+        });
+        Ok(())
+    }
+
+    /// ## Set Round Schedule
+    ///
+    /// Allows the administrator to configure automatic round rollover. Once set, `start_scheduled_round` opens a
+    /// new round every `round_cadence_seconds`, each accepting entries for `round_duration_seconds`.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `round_duration_seconds` - Time from a scheduled round's start to its draw, in seconds.
+    /// * `round_cadence_seconds` - Time between the starts of consecutive scheduled rounds, in seconds.
+    ///                             Must be at least the duration. Pass `0` for both to disable the schedule.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the schedule is updated.
+    /// * `Err` if the signer is not the admin or the schedule is invalid.
+    pub fn set_round_schedule(ctx: Context<UpdateConfig>, round_duration_seconds: i64, round_cadence_seconds: i64) -> Result<()> {
+        msg!("Instruction: Set Round Schedule");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        let disabled = round_duration_seconds == 0 && round_cadence_seconds == 0;
+        require!(
+            disabled || (round_duration_seconds > 0 && round_cadence_seconds >= round_duration_seconds),
+            LotteryError::InvalidRoundSchedule
+        );
+
+        emit!(RoundScheduleUpdated {
+            old_round_duration_seconds: lottery_config.round_duration_seconds,
+            new_round_duration_seconds: round_duration_seconds,
+            old_round_cadence_seconds: lottery_config.round_cadence_seconds,
+            new_round_cadence_seconds: round_cadence_seconds,
+        });
+        lottery_config.round_duration_seconds = round_duration_seconds;
+        lottery_config.round_cadence_seconds = round_cadence_seconds;
+        msg!("Round schedule set. Duration: {}s, Cadence: {}s", round_duration_seconds, round_cadence_seconds);
+        Ok(())
+    }
+
+    /// ## Set Draw Calendar
+    ///
+    /// Allows the administrator to align draws to a calendar, e.g. "every Friday 20:00 UTC". Draws then happen only at
+    /// Unix timestamps `t` with `t % draw_period_seconds == draw_period_offset_seconds`: a new round's draw is the first
+    /// such slot after its requested duration has passed. Clients can preview the upcoming slots from the same fields.
+    /// Rounds that are already running keep their draw time.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `draw_period_seconds` - Length of the calendar period, e.g. 604800 for weekly draws. Pass `0` to disable the calendar.
+    /// * `draw_period_offset_seconds` - Position of the draw within each period, counted from the Unix epoch
+    ///                                  (a Thursday, 00:00 UTC). Must be less than the period.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the calendar is updated.
+    /// * `Err` if the signer is not the admin or the calendar is invalid.
+    pub fn set_draw_calendar(ctx: Context<UpdateConfig>, draw_period_seconds: i64, draw_period_offset_seconds: i64) -> Result<()> {
+        msg!("Instruction: Set Draw Calendar");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        let disabled = draw_period_seconds == 0 && draw_period_offset_seconds == 0;
+        if !disabled {
+            schedule::validate_calendar(draw_period_seconds, draw_period_offset_seconds).map_err(LotteryError::from)?;
+        }
+
+        emit!(DrawCalendarUpdated {
+            old_draw_period_seconds: lottery_config.draw_period_seconds,
+            new_draw_period_seconds: draw_period_seconds,
+            old_draw_period_offset_seconds: lottery_config.draw_period_offset_seconds,
+            new_draw_period_offset_seconds: draw_period_offset_seconds,
+        });
+        lottery_config.draw_period_seconds = draw_period_seconds;
+        lottery_config.draw_period_offset_seconds = draw_period_offset_seconds;
+        msg!("Draw calendar set. Period: {}s, Offset: {}s", draw_period_seconds, draw_period_offset_seconds);
+        Ok(())
+    }
+
+    /// ## Set Sales Cutoff
+    ///
+    /// Allows the administrator to end ticket sales some time before the draw. Rounds started afterwards get a
+    /// `sales_close_timestamp` of `draw_timestamp - sales_cutoff_seconds`; rounds that are already running keep theirs.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `sales_cutoff_seconds` - Time between the end of sales and the draw, in seconds. Pass `0` to sell until the draw.
+    ///                            Starting a round fails if its sales would close before it starts.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the cutoff is updated.
+    /// * `Err` if the signer is not the admin or the cutoff is negative.
+    pub fn set_sales_cutoff(ctx: Context<UpdateConfig>, sales_cutoff_seconds: i64) -> Result<()> {
+        msg!("Instruction: Set Sales Cutoff");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(sales_cutoff_seconds >= 0, LotteryError::InvalidSalesCutoff);
+
+        emit!(SalesCutoffUpdated {
+            old_sales_cutoff_seconds: lottery_config.sales_cutoff_seconds,
+            new_sales_cutoff_seconds: sales_cutoff_seconds,
+        });
+        lottery_config.sales_cutoff_seconds = sales_cutoff_seconds;
+        msg!("Sales cutoff set to {}s before the draw", sales_cutoff_seconds);
+        Ok(())
+    }
+
+    /// ## Set Round Extensions
+    ///
+    /// Allows the administrator to opt in to extending under-subscribed rounds. When a round has fewer than
+    /// `WINNER_COUNT` participants at its draw time, `extend_round` moves its draw `extension_interval_seconds`
+    /// into the future, at most `max_round_extensions` times; after that it cancels the round.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `extension_interval_seconds` - How far each extension moves the draw past the time of extension, in seconds.
+    /// * `max_round_extensions` - How often a round may be extended, at most `MAX_ROUND_EXTENSIONS`.
+    ///                            Pass `0` for both to disable extensions.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the rule is updated.
+    /// * `Err` if the signer is not the admin or the rule is invalid.
+    pub fn set_round_extensions(ctx: Context<UpdateConfig>, extension_interval_seconds: i64, max_round_extensions: u8) -> Result<()> {
+        msg!("Instruction: Set Round Extensions");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        let disabled = extension_interval_seconds == 0 && max_round_extensions == 0;
+        require!(
+            disabled
+                || (extension_interval_seconds > 0
+                    && max_round_extensions > 0
+                    && max_round_extensions as usize <= MAX_ROUND_EXTENSIONS),
+            LotteryError::InvalidRoundExtensionRule
+        );
+
+        emit!(RoundExtensionRuleUpdated {
+            old_extension_interval_seconds: lottery_config.extension_interval_seconds,
+            new_extension_interval_seconds: extension_interval_seconds,
+            old_max_round_extensions: lottery_config.max_round_extensions,
+            new_max_round_extensions: max_round_extensions,
+        });
+        lottery_config.extension_interval_seconds = extension_interval_seconds;
+        lottery_config.max_round_extensions = max_round_extensions;
+        msg!("Round extensions set. Interval: {}s, Maximum: {}", extension_interval_seconds, max_round_extensions);
+        Ok(())
+    }
+
+    /// ## Set Max Participants
+    ///
+    /// Allows the administrator to cap the number of participants per round below the account capacity of
+    /// `MAX_PARTICIPANTS`. A round that reaches the cap is sold out: it accepts no more entries, and its sales can be
+    /// closed and its draw conducted straight away instead of waiting for `draw_timestamp`.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `max_participants_per_round` - The cap, between `WINNER_COUNT` and `MAX_PARTICIPANTS`. Pass `0` to only sell
+    ///                                  out at the account capacity. Applies to running rounds from their next entry.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the cap is updated.
+    /// * `Err` if the signer is not the admin or the cap is out of range.
+    pub fn set_max_participants(ctx: Context<UpdateConfig>, max_participants_per_round: u32) -> Result<()> {
+        msg!("Instruction: Set Max Participants");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(
+            max_participants_per_round == 0
+                || (max_participants_per_round as usize >= draw::WINNER_COUNT
+                    && max_participants_per_round as usize <= MAX_PARTICIPANTS),
+            LotteryError::InvalidMaxParticipants
+        );
+
+        emit!(MaxParticipantsUpdated {
+            old_max_participants_per_round: lottery_config.max_participants_per_round,
+            new_max_participants_per_round: max_participants_per_round,
+        });
+        lottery_config.max_participants_per_round = max_participants_per_round;
+        msg!("Max participants per round set to {}", max_participants_per_round);
+        Ok(())
+    }
+
+    /// ## Initialize Treasury
+    ///
+    /// Creates the `Treasury` account that backs guaranteed main prizes. Called once by the administrator.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key).
+    ///     * `treasury`: The `Treasury` account to be initialized (PDA: seeds = ["treasury", lottery_config]).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Pays the rent.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the treasury is created.
+    /// * `Err` if the signer is not the admin.
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
+        msg!("Instruction: Initialize Treasury");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.lottery_config = ctx.accounts.lottery_config.key();
+        treasury.committed_lamports = 0;
+        treasury.bump = ctx.bumps.treasury;
+        msg!("Treasury initialized at {}", treasury.key());
+        Ok(())
+    }
+
+    /// ## Set Guaranteed Main Prize
+    ///
+    /// Allows the administrator to guarantee a minimum main prize for rounds started from now on. Each new round
+    /// records the guarantee and reserves it in the treasury; a round cannot start unless the uncommitted treasury
+    /// balance covers it. At distribution the treasury pays the difference if the main winner's share falls short.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `guaranteed_main_prize_lamports` - The guaranteed main prize, in lamports. Pass `0` to disable the guarantee.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the guarantee is updated.
+    /// * `Err` if the signer is not the admin.
+    pub fn set_guaranteed_main_prize(ctx: Context<UpdateConfig>, guaranteed_main_prize_lamports: u64) -> Result<()> {
+        msg!("Instruction: Set Guaranteed Main Prize");
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        emit!(GuaranteedMainPrizeUpdated {
+            old_guaranteed_main_prize_lamports: lottery_config.guaranteed_main_prize_lamports,
+            new_guaranteed_main_prize_lamports: guaranteed_main_prize_lamports,
+        });
+        lottery_config.guaranteed_main_prize_lamports = guaranteed_main_prize_lamports;
+        msg!("Guaranteed main prize set to {} lamports", guaranteed_main_prize_lamports);
+        Ok(())
+    }
+
+    /// ## Fund Treasury
+    ///
+    /// Adds lamports to the treasury that backs guaranteed main prizes. Anyone can fund the treasury.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `treasury`: The `Treasury` account receiving the lamports.
+    ///     * `funder`: The signer paying the lamports.
+    ///     * `system_program`: Required for the SOL transfer (CPI).
+    /// * `amount_lamports` - The amount to add, in lamports.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the treasury is funded.
+    pub fn fund_treasury(ctx: Context<FundTreasury>, amount_lamports: u64) -> Result<()> {
+        msg!("Instruction: Fund Treasury");
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount_lamports)?;
+
+        let available_lamports = treasury_available_lamports(&ctx.accounts.treasury)?;
+        msg!("Treasury funded with {} lamports by {}. Available: {} lamports", amount_lamports, ctx.accounts.funder.key(), available_lamports);
+        emit!(TreasuryChanged {
+            amount_lamports: amount_lamports as i64,
+            available_lamports,
+            committed_lamports: ctx.accounts.treasury.committed_lamports,
+        });
+        Ok(())
+    }
+
+    /// ## Withdraw Treasury
+    ///
+    /// Allows the administrator to withdraw treasury lamports that are not committed to a running round. The
+    /// `Treasury` account always keeps its own rent-exempt minimum.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key).
+    ///     * `treasury`: The `Treasury` account holding the lamports.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Receives the lamports.
+    /// * `amount_lamports` - The amount to withdraw, in lamports.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the lamports are withdrawn.
+    /// * `Err` if the signer is not the admin or less than `amount_lamports` is uncommitted.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount_lamports: u64) -> Result<()> {
+        msg!("Instruction: Withdraw Treasury");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(treasury_available_lamports(&ctx.accounts.treasury)? >= amount_lamports, LotteryError::InsufficientTreasury);
+
+        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount_lamports;
+        **ctx.accounts.admin.to_account_info().try_borrow_mut_lamports()? += amount_lamports;
+
+        let available_lamports = treasury_available_lamports(&ctx.accounts.treasury)?;
+        msg!("Withdrew {} lamports from the treasury. Available: {} lamports", amount_lamports, available_lamports);
+        emit!(TreasuryChanged {
+            amount_lamports: -(amount_lamports as i64),
+            available_lamports,
+            committed_lamports: ctx.accounts.treasury.committed_lamports,
+        });
+        Ok(())
+    }
+
+    /// ## Initialize Fee Split
+    ///
+    /// Creates the lottery's `FeeSplit` account, which divides the dev fee between up to `MAX_FEE_BENEFICIARIES`
+    /// beneficiaries by weight, and makes it the lottery's dev fee receiver. `withdraw_fees` then pays the withdrawn
+    /// fees out through it; `update_config` can point the dev fee at a single receiver again.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (mutable to set the dev fee receiver).
+    ///     * `fee_split`: The `FeeSplit` account to be initialized (PDA: seeds = ["fee_split", lottery_config]).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Pays the rent.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    /// * `beneficiaries` - The beneficiary table. Weights are in basis points of the dev fee and must add up to 10,000.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the fee split is created.
+    /// * `Err` if the signer is not the admin or the table is invalid. Beneficiaries may not be the fee split, the
+    ///   program or the system program.
+    pub fn initialize_fee_split(ctx: Context<InitializeFeeSplit>, beneficiaries: Vec<FeeBeneficiary>) -> Result<()> {
+        msg!("Instruction: Initialize Fee Split");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        validate_fee_beneficiaries(&ctx.accounts.fee_split.key(), &beneficiaries)?;
+
+        let fee_split = &mut ctx.accounts.fee_split;
+        fee_split.lottery_config = ctx.accounts.lottery_config.key();
+        fee_split.beneficiaries = beneficiaries;
+        fee_split.bump = ctx.bumps.fee_split;
+        ctx.accounts.lottery_config.dev_fee_receiver = fee_split.key(); // Withdrawn fees are split from now on.
+
+        msg!("Fee split initialized at {} with {} beneficiaries", fee_split.key(), fee_split.beneficiaries.len());
+        emit!(FeeBeneficiariesUpdated { lottery_config: fee_split.lottery_config, beneficiaries: fee_split.beneficiaries.clone() });
+        Ok(())
+    }
+
+    /// ## Set Fee Beneficiaries
+    ///
+    /// Allows the administrator to replace the beneficiary table of the lottery's `FeeSplit`. The table in effect
+    /// at each `withdraw_fees` is used; it only decides how the dev fee is shared, not what players pay or win.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key).
+    ///     * `fee_split`: The `FeeSplit` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `beneficiaries` - The new beneficiary table. Weights are in basis points of the dev fee and must add up to 10,000.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the table is replaced.
+    /// * `Err` if the signer is not the admin or the table is invalid. Beneficiaries may not be the fee split, the
+    ///   program or the system program.
+    pub fn set_fee_beneficiaries(ctx: Context<UpdateFeeSplit>, beneficiaries: Vec<FeeBeneficiary>) -> Result<()> {
+        msg!("Instruction: Set Fee Beneficiaries");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        validate_fee_beneficiaries(&ctx.accounts.fee_split.key(), &beneficiaries)?;
+
+        let fee_split = &mut ctx.accounts.fee_split;
+        fee_split.beneficiaries = beneficiaries;
+        msg!("Fee split now has {} beneficiaries", fee_split.beneficiaries.len());
+        emit!(FeeBeneficiariesUpdated { lottery_config: fee_split.lottery_config, beneficiaries: fee_split.beneficiaries.clone() });
+        Ok(())
+    }
+
+    /// ## Initialize Fee Vault
+    ///
+    /// Creates the `FeeVault` account in which `distribute_prizes` accrues the lottery's dev fees. Called once by the
+    /// administrator; rounds cannot be distributed until it exists.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key).
+    ///     * `fee_vault`: The `FeeVault` account to be initialized (PDA: seeds = ["fee_vault", lottery_config]).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Pays the rent.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the fee vault is created.
+    /// * `Err` if the signer is not the admin.
+    pub fn initialize_fee_vault(ctx: Context<InitializeFeeVault>) -> Result<()> {
+        msg!("Instruction: Initialize Fee Vault");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        fee_vault.lottery_config = ctx.accounts.lottery_config.key();
+        fee_vault.total_accrued_lamports = 0;
+        fee_vault.total_withdrawn_lamports = 0;
+        fee_vault.withdrawal_limit_lamports = 0; // Withdrawals are unlimited by default.
+        fee_vault.withdrawal_period_seconds = 0;
+        fee_vault.period_start_timestamp = 0;
+        fee_vault.period_withdrawn_lamports = 0;
+        fee_vault.bump = ctx.bumps.fee_vault;
+        msg!("Fee vault initialized at {}", fee_vault.key());
+        Ok(())
+    }
+
+    /// ## Set Fee Withdrawal Limit
+    ///
+    /// Allows the administrator to cap how much `withdraw_fees` may pay out per period. A period starts with the first
+    /// withdrawal after the previous period ended. Changing the limit starts a new period with the next withdrawal.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key).
+    ///     * `fee_vault`: The `FeeVault` account to be updated.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    /// * `withdrawal_limit_lamports` - The most that may be withdrawn per period, in lamports. `0` removes the limit.
+    /// * `withdrawal_period_seconds` - The length of a period, in seconds. `0` removes the limit.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the limit is updated.
+    /// * `Err` if the signer is not the admin or the period is negative.
+    pub fn set_fee_withdrawal_limit(
+        ctx: Context<UpdateFeeVault>,
+        withdrawal_limit_lamports: u64,
+        withdrawal_period_seconds: i64,
+    ) -> Result<()> {
+        msg!("Instruction: Set Fee Withdrawal Limit");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(withdrawal_period_seconds >= 0, LotteryError::InvalidFeeWithdrawalLimit);
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        fee_vault.withdrawal_limit_lamports = withdrawal_limit_lamports;
+        fee_vault.withdrawal_period_seconds = withdrawal_period_seconds;
+        fee_vault.period_start_timestamp = i64::MIN; // The next withdrawal starts a new period.
+        fee_vault.period_withdrawn_lamports = 0;
+        msg!("Fee withdrawal limit set to {} lamports per {} seconds", withdrawal_limit_lamports, withdrawal_period_seconds);
+        emit!(FeeWithdrawalLimitUpdated { lottery_config: fee_vault.lottery_config, withdrawal_limit_lamports, withdrawal_period_seconds });
+        Ok(())
+    }
+
+    /// ## Withdraw Fees
+    ///
+    /// Allows the administrator to withdraw accrued dev fees from the `FeeVault` to the lottery's current dev fee
+    /// receiver. If the receiver is the lottery's `FeeSplit`, the amount is divided between its beneficiaries by
+    /// weight instead, with the rounding remainder going to the first beneficiary.
+    ///
+    /// Every recipient must be a System Program account that is writable, not executable and rent-exempt once paid, and a
+    /// receiver owned by this program must be the lottery's `FeeSplit`. If one is not, nothing is paid and the fees stay
+    /// in the vault until the admin points the dev fee at a valid receiver.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key and the dev fee receiver).
+    ///     * `fee_vault`: The `FeeVault` account holding the fees.
+    ///     * `dev_fee_receiver`: The account receiving the fees (must match `lottery_config.dev_fee_receiver`).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`.
+    ///     * `remaining_accounts`: When the dev fee receiver is a `FeeSplit`, its beneficiaries in table order (mutable).
+    /// * `amount_lamports` - The amount to withdraw, in lamports.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the fees are paid out.
+    /// * `Err` if the signer is not the admin, a recipient cannot receive the fees, less than `amount_lamports` has
+    ///   accrued, the amount exceeds what is left of the period's withdrawal limit, or the beneficiary accounts do not
+    ///   match the fee split.
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount_lamports: u64) -> Result<()> {
+        msg!("Instruction: Withdraw Fees");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        let lottery_config = ctx.accounts.lottery_config.key();
+        let fee_split = load_fee_split(&ctx.accounts.dev_fee_receiver, &lottery_config)?;
+        if fee_split.is_none() {
+            require!(ctx.remaining_accounts.is_empty(), LotteryError::IncorrectFeeBeneficiaryAccounts);
+            check_fee_recipient(&ctx.accounts.dev_fee_receiver, amount_lamports)?;
+        }
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let available_lamports = fee_vault.total_accrued_lamports.saturating_sub(fee_vault.total_withdrawn_lamports);
+        require!(available_lamports >= amount_lamports, LotteryError::InsufficientFees);
+        let (period_start, allowance) = payout::withdrawal_allowance(
+            Clock::get()?.unix_timestamp,
+            fee_vault.period_start_timestamp,
+            fee_vault.withdrawal_period_seconds,
+            fee_vault.withdrawal_limit_lamports,
+            fee_vault.period_withdrawn_lamports,
+        );
+        require!(amount_lamports <= allowance, LotteryError::FeeWithdrawalLimitExceeded);
+        if period_start != fee_vault.period_start_timestamp {
+            fee_vault.period_start_timestamp = period_start;
+            fee_vault.period_withdrawn_lamports = 0;
+        }
+        fee_vault.period_withdrawn_lamports = fee_vault.period_withdrawn_lamports.saturating_add(amount_lamports);
+        fee_vault.total_withdrawn_lamports = fee_vault.total_withdrawn_lamports.checked_add(amount_lamports).ok_or(LotteryError::NumericOverflow)?;
+
+        let fee_vault_account_info = ctx.accounts.fee_vault.to_account_info();
+        match fee_split {
+            Some(fee_split) => pay_fee_beneficiaries(lottery_config, &fee_vault_account_info, &fee_split, ctx.remaining_accounts, amount_lamports)?,
+            None => {
+                **fee_vault_account_info.try_borrow_mut_lamports()? -= amount_lamports;
+                **ctx.accounts.dev_fee_receiver.try_borrow_mut_lamports()? += amount_lamports;
+                msg!("Transferred {} lamports of fees to dev fee receiver {}", amount_lamports, ctx.accounts.dev_fee_receiver.key());
+                emit!(FeesWithdrawn { lottery_config, recipient: ctx.accounts.dev_fee_receiver.key(), amount_lamports });
+            }
+        }
+        msg!("Withdrew {} lamports of fees. Total withdrawn: {} of {} lamports accrued",
+             amount_lamports, ctx.accounts.fee_vault.total_withdrawn_lamports, ctx.accounts.fee_vault.total_accrued_lamports);
+        Ok(())
+    }
+
+    /// ## Initialize Lottery Stats
+    ///
+    /// Creates the `LotteryStats` account in which `enter_lottery`, `conduct_draw` and `distribute_prizes` keep the
+    /// lottery's lifetime statistics. Called once by the administrator; tickets cannot be sold until it exists.
+    /// Clients read the account directly, no instruction is needed to query it.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (for the admin key).
+    ///     * `lottery_stats`: The `LotteryStats` account to be initialized (PDA: seeds = ["lottery_stats", lottery_config]).
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Pays the rent.
+    ///     * `system_program`: Required by Anchor for account initialization.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the statistics account is created.
+    /// * `Err` if the signer is not the admin.
+    pub fn initialize_lottery_stats(ctx: Context<InitializeLotteryStats>) -> Result<()> {
+        msg!("Instruction: Initialize Lottery Stats");
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+
+        let lottery_stats = &mut ctx.accounts.lottery_stats;
+        lottery_stats.lottery_config = ctx.accounts.lottery_config.key();
+        lottery_stats.total_rounds_drawn = 0;
+        lottery_stats.total_tickets_sold = 0;
+        lottery_stats.total_paid_to_winners_lamports = 0;
+        lottery_stats.total_dev_fees_lamports = 0;
+        lottery_stats.largest_jackpot_lamports = 0;
+        lottery_stats.largest_jackpot_round_id = 0;
+        lottery_stats.bump = ctx.bumps.lottery_stats;
+        msg!("Lottery stats initialized at {}", lottery_stats.key());
+        Ok(())
+    }
+
+    /// ## Fund Rent Reserve
+    ///
+    /// Adds lamports to the rent reserve held by the `LotteryConfig` account. The reserve pays the rent of rounds
+    /// opened by `start_scheduled_round`; rounds it paid for return their rent to it in `close_round`.
+    /// Anyone can fund the reserve.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account holding the reserve.
+    ///     * `funder`: The signer paying into the reserve.
+    ///     * `system_program`: Required for the SOL transfer (CPI).
+    /// * `amount_lamports` - The amount to add, in lamports.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the reserve is funded.
+    pub fn fund_rent_reserve(ctx: Context<FundRentReserve>, amount_lamports: u64) -> Result<()> {
+        msg!("Instruction: Fund Rent Reserve");
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.lottery_config.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount_lamports)?;
+
+        let reserve_lamports = rent_reserve_lamports(&ctx.accounts.lottery_config.to_account_info())?;
+        msg!("Rent reserve funded with {} lamports by {}. Reserve: {} lamports", amount_lamports, ctx.accounts.funder.key(), reserve_lamports);
+        emit!(RentReserveChanged { amount_lamports: amount_lamports as i64, reserve_lamports });
+        Ok(())
+    }
+
+    /// ## Withdraw Rent Reserve
+    ///
+    /// Allows the administrator to withdraw lamports from the rent reserve. The `LotteryConfig` account always keeps
+    /// its own rent-exempt minimum.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The `LotteryConfig` account holding the reserve.
+    ///     * `admin`: The signer account, must match `lottery_config.admin_key`. Receives the lamports.
+    /// * `amount_lamports` - The amount to withdraw, in lamports.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the lamports are withdrawn.
+    /// * `Err` if the signer is not the admin or the reserve holds less than `amount_lamports`.
+    pub fn withdraw_rent_reserve(ctx: Context<WithdrawRentReserve>, amount_lamports: u64) -> Result<()> {
+        msg!("Instruction: Withdraw Rent Reserve");
+        let lottery_config_account_info = ctx.accounts.lottery_config.to_account_info();
+
+        require!(ctx.accounts.lottery_config.admin_key == *ctx.accounts.admin.key, LotteryError::UnauthorizedAdmin);
+        require!(rent_reserve_lamports(&lottery_config_account_info)? >= amount_lamports, LotteryError::InsufficientRentReserve);
+
+        **lottery_config_account_info.try_borrow_mut_lamports()? -= amount_lamports;
+        **ctx.accounts.admin.to_account_info().try_borrow_mut_lamports()? += amount_lamports;
+
+        let reserve_lamports = rent_reserve_lamports(&lottery_config_account_info)?;
+        msg!("Withdrew {} lamports from the rent reserve. Reserve: {} lamports", amount_lamports, reserve_lamports);
+        emit!(RentReserveChanged { amount_lamports: -(amount_lamports as i64), reserve_lamports });
+        Ok(())
+    }
+
+    /// ## Start Scheduled Round
+    ///
+    /// Opens the next round according to the schedule set with `set_round_schedule`, without the admin.
+    /// The round can be started once the previous round has been paid out, cancelled or closed, and at least
+    /// `round_cadence_seconds` have passed since the previous round started. Until then the previous round is still the
+    /// current one, which the instructions that draw, dispute and pay out a round operate on. Its draw is `round_duration_seconds`
+    /// after its start, or the first draw calendar slot after that if a calendar is set. The rent of the new round and
+    /// of its prize vault is paid from the rent reserve held by `LotteryConfig`, which also becomes the round's rent payer.
+    ///
+    /// Anyone can call this instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - The context containing accounts for this instruction.
+    ///     * `lottery_config`: The main `LotteryConfig` account (mutable to advance the round ID and pay the rent).
+    ///     * `previous_round`: The `LotteryRound` PDA of the current round ID. May already be closed (or, for the
+    ///                         first round, never have existed).
+    ///     * `lottery_round`: The `LotteryRound` PDA of the next round ID, created by this instruction.
+    ///     * `prize_vault`: The new round's prize vault (PDA: seeds = ["prize_vault", lottery_round]), funded with its rent.
+    ///     * `treasury`: The `Treasury` account (mutable to commit the round's guaranteed main prize).
+    ///     * `system_program`: Required to allocate and assign the new account (CPI).
+    /// * `next_round_id_arg` - The expected ID of the new round, used in PDA derivation. Must be the current round ID + 1.
+    ///
+    /// ### Returns
+    ///
+    /// * `Ok(())` if the next round is started.
+    /// * `Err` if the lottery is paused, no schedule is set, `next_round_id_arg` is not the next round ID, the previous
+    ///   round is not paid out, cancelled or closed yet, the round is not due yet, the reserve cannot cover the rent,
+    ///   or the treasury cannot cover the guaranteed main prize.
+    pub fn start_scheduled_round(ctx: Context<StartScheduledRound>, next_round_id_arg: u64) -> Result<()> {
+        msg!("Instruction: Start Scheduled Round. Argument next_round_id_arg: {}", next_round_id_arg);
+        let clock = Clock::get()?;
+        let lottery_config_account_info = ctx.accounts.lottery_config.to_account_info();
+        let lottery_round_account_info = ctx.accounts.lottery_round.to_account_info();
+        let lottery_config = &mut ctx.accounts.lottery_config;
+
+        require!(!lottery_config.is_paused, LotteryError::LotteryPaused);
+        require!(lottery_config.round_cadence_seconds > 0, LotteryError::RoundScheduleNotSet);
+        let next_round_id = lottery_config.current_lottery_round_id.checked_add(1).ok_or(LotteryError::NumericOverflow)?;
+        require!(next_round_id_arg == next_round_id, LotteryError::IncorrectNextRoundIdArg);
+
+        require_previous_round_finished(&ctx.accounts.previous_round, ctx.program_id)?;
+
+        let due_timestamp = lottery_config
+            .last_round_start_timestamp
+            .checked_add(lottery_config.round_cadence_seconds)
+            .ok_or(LotteryError::NumericOverflow)?;
+        require!(clock.unix_timestamp >= due_timestamp, LotteryError::RoundNotDue);
+
+        let draw_timestamp = compute_draw_timestamp(lottery_config, clock.unix_timestamp, lottery_config.round_duration_seconds)?;
+        let sales_close_timestamp = compute_sales_close_timestamp(lottery_config, clock.unix_timestamp, draw_timestamp)?;
+        commit_guarantee(&mut ctx.accounts.treasury, lottery_config.guaranteed_main_prize_lamports)?;
+
+        // Pay the rent of the round and its prize vault from the reserve, then allocate and assign the round PDA to this program.
+        let space = 8 + LotteryRound::INIT_SPACE;
+        let round_rent_lamports = Rent::get()?.minimum_balance(space).saturating_sub(lottery_round_account_info.lamports());
+        let vault_rent_lamports = prize_vault_rent_due(&ctx.accounts.prize_vault)?;
+        let rent_lamports = round_rent_lamports.checked_add(vault_rent_lamports).ok_or(LotteryError::NumericOverflow)?;
+        require!(rent_reserve_lamports(&lottery_config_account_info)? >= rent_lamports, LotteryError::InsufficientRentReserve);
+        **lottery_config_account_info.try_borrow_mut_lamports()? -= rent_lamports;
+        **lottery_round_account_info.try_borrow_mut_lamports()? += round_rent_lamports;
+        **ctx.accounts.prize_vault.to_account_info().try_borrow_mut_lamports()? += vault_rent_lamports;
+
+        let next_round_id_bytes = next_round_id.to_le_bytes();
+        let lottery_config_key = lottery_config.key();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[LOTTERY_ROUND_SEED, lottery_config_key.as_ref(), next_round_id_bytes.as_ref(), &[ctx.bumps.lottery_round]]];
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Allocate { account_to_allocate: lottery_round_account_info.clone() },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Assign { account_to_assign: lottery_round_account_info.clone() },
+                signer_seeds,
+            ),
+            ctx.program_id,
+        )?;
+
+        let mut lottery_round = open_round(
+            lottery_config,
+            next_round_id,
+            clock.unix_timestamp,
+            draw_timestamp,
+            sales_close_timestamp,
+            lottery_config.key(),
+            ctx.bumps.lottery_round,
+        );
+        lottery_round.prize_vault_bump = ctx.bumps.prize_vault;
+        lottery_round.try_serialize(&mut &mut lottery_round_account_info.try_borrow_mut_data()?[..])?;
+
+        lottery_config.current_lottery_round_id = next_round_id;
+        lottery_config.last_round_start_timestamp = clock.unix_timestamp;
+
+        msg!("Scheduled Lottery Round {} started. Draw scheduled for timestamp: {}. Rent paid from reserve: {} lamports",
+             next_round_id, draw_timestamp, rent_lamports);
+        emit!(RoundStarted {
+            lottery_config: lottery_config.key(),
+            round_id: next_round_id,
+            start_timestamp: clock.unix_timestamp,
+            draw_timestamp,
+            entry_fee_lamports: lottery_round.entry_fee_lamports,
+        });
+        Ok(())
+    }
+}
+
+/// Returns the draw time of a round started at `now` that runs for at least `min_duration_seconds`: exactly that long
+/// without a draw calendar, otherwise until the next calendar slot.
+fn compute_draw_timestamp(lottery_config: &LotteryConfig, now: i64, min_duration_seconds: i64) -> Result<i64> {
+    let earliest = now.checked_add(min_duration_seconds).ok_or(LotteryError::NumericOverflow)?;
+    if lottery_config.draw_period_seconds == 0 {
+        return Ok(earliest);
+    }
+    let draw_timestamp = schedule::next_aligned_timestamp(
+        earliest,
+        lottery_config.draw_period_seconds,
+        lottery_config.draw_period_offset_seconds,
+    )
+    .map_err(LotteryError::from)?;
+    Ok(draw_timestamp)
+}
+
+/// Returns the treasury lamports not yet promised to any round, keeping the account's rent-exempt minimum.
+fn treasury_available_lamports(treasury: &Account<Treasury>) -> Result<u64> {
+    let treasury_account_info = treasury.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_account_info.data_len());
+    Ok(treasury_account_info
+        .lamports()
+        .saturating_sub(rent_exempt_minimum)
+        .saturating_sub(treasury.committed_lamports))
+}
+
+/// Reserves `guaranteed_main_prize_lamports` of the treasury for a new round, failing if it cannot be covered.
+/// The reservation is released once the round is paid out or cancelled.
+fn commit_guarantee(treasury: &mut Account<Treasury>, guaranteed_main_prize_lamports: u64) -> Result<()> {
+    if guaranteed_main_prize_lamports == 0 {
+        return Ok(());
+    }
+    require!(
+        treasury_available_lamports(treasury)? >= guaranteed_main_prize_lamports,
+        LotteryError::InsufficientTreasury
+    );
+    treasury.committed_lamports = treasury.committed_lamports.checked_add(guaranteed_main_prize_lamports).ok_or(LotteryError::NumericOverflow)?;
+    Ok(())
+}
+
+/// Releases the treasury reservation made by `commit_guarantee` for a round.
+fn release_guarantee(treasury: &mut Treasury, guaranteed_main_prize_lamports: u64) {
+    treasury.committed_lamports = treasury.committed_lamports.saturating_sub(guaranteed_main_prize_lamports);
+}
+
+/// Returns how many participants a round can take before it is sold out: the configured cap, or the account capacity.
+fn round_capacity(lottery_config: &LotteryConfig) -> usize {
+    match lottery_config.max_participants_per_round {
+        0 => MAX_PARTICIPANTS,
+        cap => (cap as usize).min(MAX_PARTICIPANTS),
+    }
+}
+
+/// Returns when ticket sales of a round started at `now` with its draw at `draw_timestamp` end: the configured
+/// `sales_cutoff_seconds` before the draw, but not before the round starts.
+fn compute_sales_close_timestamp(lottery_config: &LotteryConfig, now: i64, draw_timestamp: i64) -> Result<i64> {
+    let sales_close_timestamp =
+        draw_timestamp.checked_sub(lottery_config.sales_cutoff_seconds).ok_or(LotteryError::NumericOverflow)?;
+    require!(sales_close_timestamp >= now, LotteryError::InvalidSalesCutoff);
+    Ok(sales_close_timestamp)
+}
+
+/// Checks that `previous_round`, the round of the current round ID, has been paid out, cancelled or closed, so that
+/// starting the next round does not leave its pot behind. A closed round (or, before the first round, a round that
+/// never existed) no longer has an account. Shared by `start_new_lottery_round` and `start_scheduled_round`.
+fn require_previous_round_finished(previous_round: &AccountInfo, program_id: &Pubkey) -> Result<()> {
+    if previous_round.data_is_empty() {
+        return Ok(());
+    }
+    require!(previous_round.owner == program_id, LotteryError::UnrecognizedRoundLayout);
+    require!(previous_round.data_len() == 8 + LotteryRound::INIT_SPACE, LotteryError::UnrecognizedRoundLayout);
+    let previous_round = LotteryRound::try_deserialize(&mut &previous_round.try_borrow_data()?[..])?;
+    require!(
+        matches!(previous_round.status, RoundStatus::Distributed | RoundStatus::Cancelled | RoundStatus::Closed),
+        LotteryError::PreviousRoundNotFinished
+    );
+    Ok(())
+}
+
+/// Returns the state of a freshly started round of the lottery at `lottery_config`, with the lottery's current
+/// guaranteed main prize. Shared by `start_new_lottery_round` and `start_scheduled_round`.
+fn open_round(
+    lottery_config: &Account<LotteryConfig>,
+    round_id: u64,
+    start_timestamp: i64,
+    draw_timestamp: i64,
+    sales_close_timestamp: i64,
+    rent_payer: Pubkey,
+    bump: u8,
+) -> LotteryRound {
+    LotteryRound {
+        lottery_config: lottery_config.key(),
+        round_id,
+        start_timestamp,
+        draw_timestamp,
+        sales_close_timestamp,
+        total_pot_lamports: 0,
+        participants: Vec::new(), // Initialize with an empty list of participants.
+        status: RoundStatus::Open, // The round accepts entries until its sales close time.
+        main_winner: None,
+        other_winners: Vec::new(),
+        randomness_seed: None,
+        bump,
+        dispute_deadline: 0,
+        is_disputed: false,
+        refund_per_participant_lamports: 0,
+        refunds_processed: 0,
+        draw_keeper: None,
+        draw_bounty_lamports: 0,
+        distribution_keeper: None,
+        distribution_bounty_lamports: 0,
+        rent_payer,                   // Receives the rent back in `close_round`.
+        participants_root: [0u8; 32], // Committed when sales close.
+        sales_closed_slot: 0,
+        extensions: Vec::new(),
+        is_sold_out: false,
+        contributions: Vec::new(),
+        // Committed in the treasury until the round is paid out or cancelled.
+        guaranteed_main_prize_lamports: lottery_config.guaranteed_main_prize_lamports,
+        // The rules the round is sold under; `update_config` does not change them for the running round.
+        entry_fee_lamports: lottery_config.entry_fee_lamports,
+        platform_fee_bps: lottery_config.platform_fee_bps,
+        dev_fee_bps: lottery_config.dev_fee_bps,
+        main_winner_bps: lottery_config.main_winner_bps,
+        prize_vault_bump: 0, // Set by the caller, which derives the prize vault.
+        flagged_slot: 0,
+        redraw_slot: 0,
+        dispute_window_seconds: lottery_config.dispute_window_seconds,
+        crank_bounty_lamports: lottery_config.crank_bounty_lamports,
+        crank_bounty_bps: lottery_config.crank_bounty_bps,
+        crank_bounty_cap_lamports: lottery_config.crank_bounty_cap_lamports,
+    }
+}
+
+/// Returns the lamports `prize_vault` still needs to be rent-exempt. Whoever opens a round pays them, so that the
+/// vault's balance above its rent-exempt minimum is exactly the pot it escrows.
+fn prize_vault_rent_due(prize_vault: &AccountInfo) -> Result<u64> {
+    Ok(Rent::get()?.minimum_balance(prize_vault.data_len()).saturating_sub(prize_vault.lamports()))
+}
+
+/// Closes an account in a legacy layout: its lamports go to `destination`, its data is zeroed and it is handed back to
+/// the System Program, so it cannot be read or migrated again.
+fn close_legacy_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination.lamports().checked_add(lamports).ok_or(LotteryError::NumericOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+    account.assign(&system_program::ID);
+    Ok(())
+}
+
+/// Returns the lamports held by the `LotteryConfig` account above its rent-exempt minimum: the reserve that pays
+/// the rent of scheduled rounds.
+fn rent_reserve_lamports(lottery_config: &AccountInfo) -> Result<u64> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(lottery_config.data_len());
+    Ok(lottery_config.lamports().saturating_sub(rent_exempt_minimum))
+}
+
+/// Returns the platform fees collected in the `PlatformConfig` account: its lamports above its own rent-exempt minimum.
+fn platform_fee_lamports(platform_config: &AccountInfo) -> Result<u64> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(platform_config.data_len());
+    Ok(platform_config.lamports().saturating_sub(rent_exempt_minimum))
+}
+
+/// Checks the bounds set by `initialize_platform` and `update_platform`: the platform fee is at most
+/// `MAX_PLATFORM_FEE_BPS`, the entry fee range is not empty, and a split within the bounds can exist.
+fn validate_platform_bounds(
+    platform_fee_bps: u16,
+    min_entry_fee_lamports: u64,
+    max_entry_fee_lamports: u64,
+    max_dev_fee_bps: u16,
+    min_main_winner_bps: u16,
+) -> Result<()> {
+    require!(
+        platform_fee_bps <= MAX_PLATFORM_FEE_BPS
+            && min_entry_fee_lamports <= max_entry_fee_lamports
+            && max_dev_fee_bps as u64 <= payout::BPS_DENOMINATOR
+            && platform_fee_bps as u64 + min_main_winner_bps as u64 <= payout::BPS_DENOMINATOR,
+        LotteryError::InvalidPlatformBounds
+    );
+    Ok(())
+}
+
+/// Returns whether `address` can never receive fees: the system program or this program. Other accounts that cannot,
+/// such as the lottery's own PDAs, are rejected when fees are paid (see `check_fee_recipient`).
+fn is_reserved_fee_recipient(address: &Pubkey) -> bool {
+    *address == system_program::ID || *address == crate::ID
+}
+
+/// Rejects a dev fee receiver that can never receive fees (see `is_reserved_fee_recipient`).
+fn validate_dev_fee_receiver(dev_fee_receiver: &Pubkey) -> Result<()> {
+    require!(!is_reserved_fee_recipient(dev_fee_receiver), LotteryError::InvalidDevFeeReceiver);
+    Ok(())
+}
+
+/// Checks that `recipient` can be paid `amount_lamports` of fees: it must be owned by the System Program (so it is
+/// neither one of this program's accounts nor another program's), writable, not executable and rent-exempt once paid.
+fn check_fee_recipient(recipient: &AccountInfo, amount_lamports: u64) -> Result<()> {
+    require!(*recipient.owner == system_program::ID, LotteryError::FeeRecipientNotSystemOwned);
+    require!(recipient.is_writable, LotteryError::FeeRecipientNotWritable);
+    require!(!recipient.executable, LotteryError::FeeRecipientExecutable);
+    let balance_after = recipient.lamports().checked_add(amount_lamports).ok_or(LotteryError::NumericOverflow)?;
+    require!(Rent::get()?.is_exempt(balance_after, recipient.data_len()), LotteryError::FeeRecipientNotRentExempt);
+    Ok(())
+}
+
+/// Returns whether a winner's payout address can receive a prize of `amount_lamports`. Like a fee recipient (see
+/// `check_fee_recipient`) it must be writable, not executable and rent-exempt once paid, and it may not be a sysvar.
+fn can_receive_prize(recipient: &AccountInfo, amount_lamports: u64) -> Result<bool> {
+    let balance_after = recipient.lamports().checked_add(amount_lamports).ok_or(LotteryError::NumericOverflow)?;
+    Ok(recipient.is_writable
+        && !recipient.executable
+        && *recipient.owner != sysvar::ID
+        && Rent::get()?.is_exempt(balance_after, recipient.data_len()))
+}
+
+/// Checks a fee beneficiary table: 1 to `MAX_FEE_BENEFICIARIES` distinct addresses with non-zero weights that add up
+/// to the whole dev fee. Neither the fee split itself nor an `is_reserved_fee_recipient` address may be a beneficiary.
+fn validate_fee_beneficiaries(fee_split: &Pubkey, beneficiaries: &[FeeBeneficiary]) -> Result<()> {
+    require!((1..=MAX_FEE_BENEFICIARIES).contains(&beneficiaries.len()), LotteryError::InvalidFeeBeneficiaries);
+    let total_weight: u64 = beneficiaries.iter().map(|beneficiary| beneficiary.weight_bps as u64).sum();
+    require!(total_weight == payout::BPS_DENOMINATOR, LotteryError::InvalidFeeBeneficiaries);
+    for (i, beneficiary) in beneficiaries.iter().enumerate() {
+        require!(beneficiary.weight_bps > 0, LotteryError::InvalidFeeBeneficiaries);
+        require!(
+            beneficiary.address != *fee_split && !is_reserved_fee_recipient(&beneficiary.address),
+            LotteryError::InvalidFeeBeneficiaries
+        );
+        require!(
+            !beneficiaries[..i].iter().any(|other| other.address == beneficiary.address),
+            LotteryError::InvalidFeeBeneficiaries
+        );
+    }
+    Ok(())
+}
+
+/// Returns the lottery's `FeeSplit` if `dev_fee_receiver` is one, or `None` if it is a plain receiver account.
+fn load_fee_split(dev_fee_receiver: &AccountInfo, lottery_config: &Pubkey) -> Result<Option<FeeSplit>> {
+    if dev_fee_receiver.owner != &crate::ID {
+        return Ok(None);
+    }
+    let data = dev_fee_receiver.try_borrow_data()?;
+    let fee_split = FeeSplit::try_deserialize(&mut &data[..]).map_err(|_| LotteryError::IncorrectDevFeeReceiver)?;
+    require!(fee_split.lottery_config == *lottery_config, LotteryError::IncorrectDevFeeReceiver);
+    Ok(Some(fee_split))
+}
+
+/// Divides `amount_lamports` between the beneficiaries of `fee_split`, moving the lamports out of `fee_vault`.
+/// `beneficiary_accounts` must list the beneficiaries in table order, and each must pass `check_fee_recipient`.
+/// The first beneficiary receives the rounding remainder.
+fn pay_fee_beneficiaries(
+    lottery_config: Pubkey,
+    fee_vault: &AccountInfo,
+    fee_split: &FeeSplit,
+    beneficiary_accounts: &[AccountInfo],
+    amount_lamports: u64,
+) -> Result<()> {
+    require!(beneficiary_accounts.len() == fee_split.beneficiaries.len(), LotteryError::IncorrectFeeBeneficiaryAccounts);
+    let weights: Vec<u16> = fee_split.beneficiaries.iter().map(|beneficiary| beneficiary.weight_bps).collect();
+    let mut shares = [0u64; MAX_FEE_BENEFICIARIES];
+    let shares = &mut shares[..weights.len()];
+    payout::split_by_weights(amount_lamports, &weights, shares).map_err(LotteryError::from)?;
+
+    for ((beneficiary, account), &share) in fee_split.beneficiaries.iter().zip(beneficiary_accounts).zip(shares.iter()) {
+        require!(account.key() == beneficiary.address, LotteryError::IncorrectFeeBeneficiaryAccounts);
+        check_fee_recipient(account, share)?;
+        **fee_vault.try_borrow_mut_lamports()? -= share;
+        **account.try_borrow_mut_lamports()? += share;
+        msg!("Transferred {} lamports of fees to beneficiary {}", share, account.key());
+        emit!(FeesWithdrawn { lottery_config, recipient: account.key(), amount_lamports: share });
+    }
+    Ok(())
+}
+
+/// Emits `PlatformConfigUpdated` with the current values of `platform_config`.
+fn emit_platform_config_updated(platform_config: &PlatformConfig) {
+    emit!(PlatformConfigUpdated {
+        authority: platform_config.authority,
+        platform_fee_bps: platform_config.platform_fee_bps,
+        min_entry_fee_lamports: platform_config.min_entry_fee_lamports,
+        max_entry_fee_lamports: platform_config.max_entry_fee_lamports,
+        max_dev_fee_bps: platform_config.max_dev_fee_bps,
+        min_main_winner_bps: platform_config.min_main_winner_bps,
+    });
+}
+
+/// Sets every field of a new `LotteryConfig` to its default: the default split without a platform fee, no entry fee
+/// bounds, no metadata and every optional feature disabled. Shared by `initialize_config` and `create_lottery`.
+fn init_lottery_config(
+    lottery_config: &mut LotteryConfig,
+    lottery_id: u64,
+    admin_key: Pubkey,
+    dev_fee_receiver: Pubkey,
+    entry_fee_lamports: u64,
+    bump: u8,
+) {
+    lottery_config.lottery_id = lottery_id;              // Distinguishes this lottery from others in the same program.
+    lottery_config.admin_key = admin_key;                // The admin who initialized the config.
+    lottery_config.dev_fee_receiver = dev_fee_receiver;  // Wallet to receive a percentage of the pot.
+    lottery_config.entry_fee_lamports = entry_fee_lamports; // Cost per ticket.
+    lottery_config.is_paused = false;                    // Lottery is active by default.
+    lottery_config.current_lottery_round_id = 0;         // No rounds have occurred yet.
+    lottery_config.auditor_key = None;                   // No auditor until the admin designates one.
+    lottery_config.dispute_window_seconds = 0;           // Dispute window disabled by default.
+    lottery_config.crank_bounty_lamports = 0;            // No keeper bounty by default.
+    lottery_config.crank_bounty_bps = 0;
+    lottery_config.crank_bounty_cap_lamports = 0;
+    lottery_config.round_duration_seconds = 0;           // No automatic schedule by default.
+    lottery_config.round_cadence_seconds = 0;
+    lottery_config.last_round_start_timestamp = 0;
+    lottery_config.draw_period_seconds = 0;              // Draws are not aligned to a calendar by default.
+    lottery_config.draw_period_offset_seconds = 0;
+    lottery_config.sales_cutoff_seconds = 0;             // Sales run until the draw time by default.
+    lottery_config.extension_interval_seconds = 0;       // Under-subscribed rounds are not extended by default.
+    lottery_config.max_round_extensions = 0;
+    lottery_config.max_participants_per_round = 0;       // Rounds sell out only when the account is full.
+    lottery_config.guaranteed_main_prize_lamports = 0;   // No guaranteed jackpot by default.
+    lottery_config.platform_fee_bps = payout::DEFAULT_SPLIT.platform_fee_bps; // 10% dev fee, 50% main winner, no platform fee.
+    lottery_config.dev_fee_bps = payout::DEFAULT_SPLIT.dev_fee_bps;
+    lottery_config.main_winner_bps = payout::DEFAULT_SPLIT.main_winner_bps;
+    lottery_config.min_entry_fee_lamports = 0;           // Any entry fee by default.
+    lottery_config.max_entry_fee_lamports = u64::MAX;
+    lottery_config.metadata_uri = String::new();         // No branding metadata by default.
+    lottery_config.is_legacy_lottery = false;            // Only `migrate_config` creates the legacy lottery.
+    lottery_config.bump = bump;                          // Store the bump seed for the PDA.
+}
+
+/// Generates a randomness seed from the SlotHashes sysvar, selects 5 unique winners and stores them on `lottery_round`.
+/// Also opens the round's dispute window, of the length recorded on the round. Shared by `conduct_draw` and `redraw_round`.
+/// The most recent slot hash must be of `earliest_slot` or later, otherwise `stale_error` is returned.
+/// Returns the slot of the slot hash used.
+fn select_winners(
+    lottery_round: &mut LotteryRound,
+    slot_hashes: &UncheckedAccount,
+    clock: &Clock,
+    earliest_slot: u64,
+    stale_error: LotteryError,
+) -> Result<u64> {
+    // Generate randomness seed from slot hash and other data.
+    // Note: This is a pseudo-random method and may have limitations for high-value lotteries. Consider oracles for stronger randomness if needed.
+    let slot_hash_data = slot_hashes.data.borrow();
+    // SlotHashes is a u64 entry count followed by (slot u64, hash [u8; 32]) entries, most recent first.
+    if slot_hash_data.len() < 48 {
+        return Err(stale_error.into());
+    }
+    let most_recent_slot = u64::from_le_bytes(slot_hash_data[8..16].try_into().unwrap());
+    let most_recent_slot_hash = &slot_hash_data[16..48];
+    // The hash of the slot in which sales closed is only produced once that slot is complete, so no entrant
+    // could have known it when entering. Hashes of earlier slots are rejected.
+    if most_recent_slot < earliest_slot {
+        return Err(stale_error.into());
+    }
+    let randomness_seed = draw::randomness_seed(
+        keccak_hashv,
+        most_recent_slot_hash,
+        clock.unix_timestamp,
+        lottery_round.participants.len() as u64,
+    );
+    lottery_round.randomness_seed = Some(randomness_seed);
+
+    // Select 5 unique winners. The index derivation lives in `solana_lottery_core::draw` so that
+    // off-chain verifiers reproduce exactly the same winners from the stored seed.
+    let winner_indices = draw::select_winner_indices(keccak_hashv, &randomness_seed, lottery_round.participants.len())
+        .map_err(|e| {
+            msg!("Error: Could not select unique winners: {:?}", e);
+            LotteryError::from(e)
+        })?;
+    let winners_pubkeys: Vec<Pubkey> = winner_indices.iter().map(|&index| lottery_round.participants[index]).collect();
+
+    // Assign winners.
+    lottery_round.main_winner = Some(winners_pubkeys[0]);
+    lottery_round.other_winners = winners_pubkeys[1..5].to_vec(); // The next 4 are other winners.
+    lottery_round.dispute_deadline =
+        clock.unix_timestamp.checked_add(lottery_round.dispute_window_seconds).ok_or(LotteryError::NumericOverflow)?;
+    Ok(most_recent_slot)
+}
+
+/// Emits `DrawConducted` for the winners currently stored on `lottery_round`.
+fn emit_draw_conducted(lottery_round: &LotteryRound, is_redraw: bool) {
+    emit!(DrawConducted {
+        round_id: lottery_round.round_id,
+        randomness_seed: lottery_round.randomness_seed.unwrap_or_default(),
+        participant_count: lottery_round.participants.len() as u32,
+        main_winner: lottery_round.main_winner.unwrap_or_default(),
+        other_winners: lottery_round.other_winners.clone(),
+        dispute_deadline: lottery_round.dispute_deadline,
+        is_redraw,
+    });
+}
+
+/// Pays the crank bounty recorded on the round from its prize vault to `keeper` and returns the amount paid.
+/// The bounty is clamped to `MAX_CRANK_BOUNTY_BPS` of the pot by `payout::crank_bounty`. The pot recorded on the round
+/// is reduced accordingly.
+fn pay_crank_bounty<'info>(
+    lottery_round: &mut Account<'info, LotteryRound>,
+    prize_vault: &SystemAccount<'info>,
+    keeper: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    let bounty = payout::crank_bounty(
+        lottery_round.total_pot_lamports,
+        lottery_round.crank_bounty_lamports,
+        lottery_round.crank_bounty_bps,
+        lottery_round.crank_bounty_cap_lamports,
+    );
+    if bounty > 0 {
+        pay_from_prize_vault(lottery_round, prize_vault, keeper, system_program, bounty)?;
+        lottery_round.total_pot_lamports = lottery_round.total_pot_lamports.checked_sub(bounty).ok_or(LotteryError::NumericOverflow)?;
+        msg!("Paid crank bounty of {} lamports to keeper {}", bounty, keeper.key());
+        emit!(PrizePaid { round_id: lottery_round.round_id, recipient: keeper.key(), amount_lamports: bounty, kind: PrizeKind::CrankBounty });
+    }
+    Ok(bounty)
+}
+
+/// Transfers `amount_lamports` out of the prize vault of `lottery_round` to `recipient`. The vault is owned by the
+/// System Program, so the program moves its lamports with a System Program transfer signed with the vault's seeds.
+fn pay_from_prize_vault<'info>(
+    lottery_round: &Account<'info, LotteryRound>,
+    prize_vault: &SystemAccount<'info>,
+    recipient: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    amount_lamports: u64,
+) -> Result<()> {
+    let lottery_round_key = lottery_round.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[PRIZE_VAULT_SEED, lottery_round_key.as_ref(), &[lottery_round.prize_vault_bump]]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Transfer { from: prize_vault.to_account_info(), to: recipient.clone() },
+            signer_seeds,
+        ),
+        amount_lamports,
+    )
+}
+
+/// Checks that the prize vault of `lottery_round` holds at least its rent-exempt minimum plus the lamports the round
+/// still owes. A surplus is tolerated, since anyone can send lamports to the vault; `reconcile_round` moves it to the
+/// treasury.
+fn check_round_solvency(lottery_round: &LotteryRound, prize_vault: &AccountInfo) -> Result<()> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(prize_vault.data_len());
+    let escrowed_lamports = lottery_round.escrowed_lamports().ok_or(LotteryError::NumericOverflow)?;
+    let balance = payout::reconcile_escrow(prize_vault.lamports(), rent_exempt_minimum, escrowed_lamports)
+        .map_err(LotteryError::from)?;
+    require!(!matches!(balance, payout::EscrowBalance::Shortfall(_)), LotteryError::RoundInsolvent);
+    Ok(())
+}
+
+/// Returns the `PlayerProfile` stored in `player_profile`, first creating the account if `participant` has none yet.
+/// The participant pays the rent; the PDA is allocated and assigned to this program manually because it is only
+/// created by the participant's first entry.
+fn load_or_create_player_profile<'info>(
+    player_profile: &AccountInfo<'info>,
+    participant: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    bump: u8,
+) -> Result<PlayerProfile> {
+    if !player_profile.data_is_empty() {
+        require!(player_profile.owner == &crate::ID, LotteryError::IncorrectPlayerProfile);
+        return PlayerProfile::try_deserialize(&mut &player_profile.try_borrow_data()?[..]);
+    }
+
+    let space = 8 + PlayerProfile::INIT_SPACE;
+    let rent_lamports = Rent::get()?.minimum_balance(space).saturating_sub(player_profile.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer { from: participant.to_account_info(), to: player_profile.clone() },
+            ),
+            rent_lamports,
+        )?;
+    }
+    let participant_key = participant.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[PLAYER_PROFILE_SEED, participant_key.as_ref(), &[bump]]];
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate { account_to_allocate: player_profile.clone() },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign { account_to_assign: player_profile.clone() },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )?;
+    msg!("Created player profile {} for {}", player_profile.key(), participant_key);
+
+    Ok(PlayerProfile {
+        player: participant_key,
+        rounds_entered: 0,
+        tickets_bought: 0,
+        lifetime_winnings_lamports: 0,
+        last_lottery_config: Pubkey::default(),
+        last_round_id: 0,
+        preferred_payout_address: None, // Prizes go to the wallet until the player sets a payout address.
+        bump,
+    })
+}
+
+/// Returns the `PlayerProfile` of `winner`, or `None` if the winner has none, e.g. a participant of a migrated legacy
+/// round. `player_profile` must be the winner's profile PDA either way, and writable if the profile exists.
+fn load_winner_profile(player_profile: &AccountInfo, winner: &Pubkey) -> Result<Option<PlayerProfile>> {
+    let (expected_profile, _) = Pubkey::find_program_address(&[PLAYER_PROFILE_SEED, winner.as_ref()], &crate::ID);
+    require!(player_profile.key() == expected_profile, LotteryError::IncorrectPlayerProfile);
+    if player_profile.data_is_empty() {
+        return Ok(None);
+    }
+    require!(player_profile.owner == &crate::ID && player_profile.is_writable, LotteryError::IncorrectPlayerProfile);
+    Ok(Some(PlayerProfile::try_deserialize(&mut &player_profile.try_borrow_data()?[..])?))
+}
+
+/// Marks `lottery_round` as cancelled and fixes the amount each participant is refunded.
+/// The ticket sales, less their share of any crank bounty, are split equally between participants; the first
+/// participant also receives the rounding remainder.
+fn mark_cancelled(lottery_round: &mut LotteryRound, treasury: &mut Treasury) -> Result<()> {
+    transition_round_status(lottery_round, RoundStatus::Cancelled)?;
+    release_guarantee(treasury, lottery_round.guaranteed_main_prize_lamports);
+
+    // Contributions go back to their contributors; participants share what is left, i.e. the ticket sales.
+    let ticket_lamports = lottery_round.ticket_lamports().ok_or(LotteryError::NumericOverflow)?;
+    (lottery_round.refund_per_participant_lamports, _) = payout::split_evenly(ticket_lamports, lottery_round.participants.len() as u64);
+    lottery_round.refunds_processed = 0;
+    Ok(())
+}
+
+/// Closes ticket sales for `lottery_round`: commits to its participant list with a Merkle root (see
+/// `solana_lottery_core::merkle`) and moves it to `SalesClosed`. Every path that ends sales goes through here.
+/// Hashing a full round of 2000 participants takes roughly 450k compute units, so callers closing large rounds
+/// should request a higher compute unit limit.
+fn close_sales(lottery_round: &mut LotteryRound, slot: u64) -> Result<()> {
+    transition_round_status(lottery_round, RoundStatus::SalesClosed)?;
+    lottery_round.participants_root = participants_merkle_root(&lottery_round.participants)?;
+    lottery_round.sales_closed_slot = slot;
+    msg!("Round {} sales closed in slot {} with {} participants", lottery_round.round_id, slot, lottery_round.participants.len());
+    emit!(SalesClosed {
+        round_id: lottery_round.round_id,
+        participant_count: lottery_round.participants.len() as u32,
+        participants_root: lottery_round.participants_root,
+        slot,
+    });
+    Ok(())
+}
+
+/// Moves `lottery_round` to `next`, failing with `InvalidRoundStatusTransition` unless the move is allowed by
+/// `RoundStatus::can_transition_to`. Every status change of a round goes through this function.
+fn transition_round_status(lottery_round: &mut LotteryRound, next: RoundStatus) -> Result<()> {
+    let previous = lottery_round.status;
+    if !previous.can_transition_to(next) {
+        msg!("Error: Round {} cannot move from {:?} to {:?}", lottery_round.round_id, previous, next);
+        return err!(LotteryError::InvalidRoundStatusTransition);
+    }
+    lottery_round.status = next;
+    msg!("Round {} status: {:?} -> {:?}", lottery_round.round_id, previous, next);
+    emit!(RoundStatusChanged { round_id: lottery_round.round_id, previous, next });
+    Ok(())
+}
+
+/// ## InitializeConfig Accounts
+/// Defines the accounts required for the `initialize_config` instruction.
+#[derive(Accounts)]
+#[instruction(lottery_id: u64)]
+pub struct InitializeConfig<'info> { // Added 'info lifetime explicitly for clarity, though often inferred.
+    /// The `LotteryConfig` account to be created and initialized.
+    /// It's a Program Derived Address (PDA) seeded with "lottery_config" and the `lottery_id` argument.
+    /// `init` constraint means this account will be created by this instruction.
+    /// `payer = admin` means the `admin` account will pay for its creation.
+    /// `space` defines the initial disk space allocation for the account.
+    /// `seeds` and `bump` are used for PDA derivation and validation.
+    #[account(init, payer = admin, space = 8 + LotteryConfig::INIT_SPACE, seeds = [LOTTERY_CONFIG_SEED, &lottery_id.to_le_bytes()], bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The global `PlatformConfig` account (read-only, for the platform authority).
+    #[account(seeds = [PLATFORM_CONFIG_SEED], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// The signer account that will become the administrator of the lottery.
+    /// `mut` indicates this account's lamports may be debited (for account creation rent).
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation and other system-level operations.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## InitializePlatform Accounts
+/// Defines the accounts required for the `initialize_platform` instruction.
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    /// The global `PlatformConfig` account to be created.
+    #[account(init, payer = authority, space = 8 + PlatformConfig::INIT_SPACE, seeds = [PLATFORM_CONFIG_SEED], bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// The signer account that will become the platform authority.
+    /// `mut` because it pays for the new account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## UpdatePlatform Accounts
+/// Defines the accounts required for the `update_platform` instruction.
+#[derive(Accounts)]
+pub struct UpdatePlatform<'info> {
+    /// The global `PlatformConfig` account to be updated.
+    #[account(mut, seeds = [PLATFORM_CONFIG_SEED], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// The signer account, must match `platform_config.authority`.
+    pub authority: Signer<'info>,
+}
+
+/// ## WithdrawPlatformFees Accounts
+/// Defines the accounts required for the `withdraw_platform_fees` instruction.
+#[derive(Accounts)]
+pub struct WithdrawPlatformFees<'info> {
+    /// The global `PlatformConfig` account.
+    /// `mut` because lamports are transferred out of it.
+    #[account(mut, seeds = [PLATFORM_CONFIG_SEED], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// The signer account, must match `platform_config.authority`.
+    /// `mut` because it receives the lamports.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// ## CreateLottery Accounts
+/// Defines the accounts required for the `create_lottery` instruction.
+#[derive(Accounts)]
+#[instruction(lottery_id: u64)]
+pub struct CreateLottery<'info> {
+    /// The `LotteryConfig` account to be created, seeded with "lottery_config" and the `lottery_id` argument.
+    #[account(init, payer = creator, space = 8 + LotteryConfig::INIT_SPACE, seeds = [LOTTERY_CONFIG_SEED, &lottery_id.to_le_bytes()], bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The global `PlatformConfig` account.
+    /// `mut` because `lottery_count` is incremented.
+    #[account(mut, seeds = [PLATFORM_CONFIG_SEED], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// The signer account that will become the administrator of the lottery.
+    /// `mut` because it pays for the new account.
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## UpdateConfig Accounts
+/// Defines the accounts required for the `update_config` instruction.
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The `LotteryConfig` account to be updated.
+    /// `mut` indicates its data will be modified.
+    /// `seeds` and `bump` ensure we are operating on the correct PDA, using the bump stored in `lottery_config` itself.
+    #[account(mut, seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The signer account, must match the `admin_key` stored in `lottery_config`.
+    pub admin: Signer<'info>,
+}
+
+/// ## StartNewLotteryRound Accounts
+/// Defines the accounts required for the `start_new_lottery_round` instruction.
+#[derive(Accounts)]
+#[instruction(draw_timestamp_offset_seconds: i64, next_round_id_arg: u64)] // Instruction arguments used in account constraints/seeds.
+pub struct StartNewLotteryRound<'info> {
+    /// The main `LotteryConfig` account.
+    /// `mut` because `current_lottery_round_id` is updated.
+    #[account(mut, seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `LotteryRound` PDA of the current round ID.
+    /// `/// CHECK:` An `UncheckedAccount` because the round may already have been closed; the PDA is validated here,
+    /// and ownership and layout inside the instruction when the account still exists.
+    #[account(seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], bump)]
+    pub previous_round: UncheckedAccount<'info>,
+    /// The new `LotteryRound` account to be initialized.
+    /// It's a PDA seeded with "lottery_round", the `LotteryConfig` and the `next_round_id_arg`.
+    /// `init` constraint means this account will be created.
     /// `payer = admin` means the `admin` account pays for its creation.
     #[account(
         init, 
         payer = admin, 
         space = 8 + LotteryRound::INIT_SPACE, 
-        seeds = [b"lottery_round".as_ref(), &next_round_id_arg.to_le_bytes().as_ref()], // Use next_round_id_arg from instruction args for PDA seed.
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &next_round_id_arg.to_le_bytes().as_ref()], // Use next_round_id_arg from instruction args for PDA seed.
         bump
     )]
     pub lottery_round: Account<'info, LotteryRound>,
+    /// The new round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because the admin funds its rent.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump)]
+    pub prize_vault: SystemAccount<'info>,
     /// The signer account, must be the admin.
-    /// `mut` because it pays for the new `LotteryRound` account.
+    /// `mut` because it pays for the new `LotteryRound` account and the prize vault's rent.
     #[account(mut)]
     pub admin: Signer<'info>,
+    /// The `Treasury` account.
+    /// `mut` because the round's guaranteed main prize is committed in it.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
     /// The Solana System Program.
     pub system_program: Program<'info, System>,
 }
@@ -472,44 +3119,151 @@ pub struct StartNewLotteryRound<'info> {
 #[derive(Accounts)]
 pub struct EnterLottery<'info> {
     /// The main `LotteryConfig` account (read-only access for fee, pause status).
-    #[account(seeds = [b"lottery_config"], bump = lottery_config.bump)]
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
     pub lottery_config: Account<'info, LotteryConfig>,
     /// The current `LotteryRound` account.
-    /// `mut` because its `participants` list and `total_pot_lamports` are updated, and it receives the entry fee.
+    /// `mut` because its `participants` list and `total_pot_lamports` are updated.
     /// Seeds use `lottery_config.current_lottery_round_id` to ensure interaction with the correct active round PDA.
     #[account(
         mut, 
-        seeds = [b"lottery_round".as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], 
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], 
         bump = lottery_round.bump
     )]
     pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because it receives the entry fee.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The lottery's `LotteryStats` account.
+    /// `mut` because the ticket is counted.
+    #[account(mut, seeds = [LOTTERY_STATS_SEED, lottery_config.key().as_ref()], bump = lottery_stats.bump)]
+    pub lottery_stats: Account<'info, LotteryStats>,
     /// The signer account entering the lottery.
-    /// `mut` because it pays the entry fee.
+    /// `mut` because it pays the entry fee and, on its first entry, the rent of its profile.
     #[account(mut)]
     pub participant: Signer<'info>,
+    /// The participant's `PlayerProfile` PDA.
+    /// `mut` because the entry is recorded in it.
+    /// `/// CHECK:` An `UncheckedAccount` because it does not exist before the participant's first entry; the PDA is
+    /// validated here, and ownership and layout inside the instruction when the account exists.
+    #[account(mut, seeds = [PLAYER_PROFILE_SEED, participant.key().as_ref()], bump)]
+    pub player_profile: UncheckedAccount<'info>,
+    /// The Solana System Program, required for the CPI SOL transfers and the profile creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## SetPayoutAddress Accounts
+/// Defines the accounts required for the `set_payout_address` instruction.
+#[derive(Accounts)]
+pub struct SetPayoutAddress<'info> {
+    /// The signer's `PlayerProfile` account.
+    /// `mut` because its payout address is updated.
+    #[account(mut, seeds = [PLAYER_PROFILE_SEED, player.key().as_ref()], bump = player_profile.bump)]
+    pub player_profile: Account<'info, PlayerProfile>,
+    /// The player who owns the profile.
+    pub player: Signer<'info>,
+    /// The account at the new payout address, if one is set.
+    /// `/// CHECK:` Only its key, `executable` flag and owner are read, inside the instruction.
+    pub payout_account: Option<UncheckedAccount<'info>>,
+}
+
+/// ## ContributeToPot Accounts
+/// Defines the accounts required for the `contribute_to_pot` instruction.
+#[derive(Accounts)]
+pub struct ContributeToPot<'info> {
+    /// The main `LotteryConfig` account (read-only access for pause status).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The current `LotteryRound` account.
+    /// `mut` because its `contributions` and `total_pot_lamports` are updated.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because it receives the contribution.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The signer paying the contribution.
+    #[account(mut)]
+    pub contributor: Signer<'info>,
     /// The Solana System Program, required for the CPI SOL transfer.
     pub system_program: Program<'info, System>,
 }
 
+/// ## CloseRoundSales Accounts
+/// Defines the accounts required for the `close_round_sales` instruction.
+#[derive(Accounts)]
+pub struct CloseRoundSales<'info> {
+    /// The main `LotteryConfig` account (read-only, to locate the current round).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The current `LotteryRound` account.
+    /// `mut` because its status, participant commitment and sales close slot are updated.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+}
+
+/// ## ExtendRound Accounts
+/// Defines the accounts required for the `extend_round` instruction.
+#[derive(Accounts)]
+pub struct ExtendRound<'info> {
+    /// The main `LotteryConfig` account (read-only, for the extension rule and to locate the current round).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The current `LotteryRound` account.
+    /// `mut` because its draw time, extension history or status is updated.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The `Treasury` account.
+    /// `mut` because a round cancelled here has its guaranteed main prize released.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
+
 /// ## ConductDraw Accounts
 /// Defines the accounts required for the `conduct_draw` instruction.
 #[derive(Accounts)]
 pub struct ConductDraw<'info> {
     /// The main `LotteryConfig` account (read-only for checks).
-    #[account(seeds = [b"lottery_config"], bump = lottery_config.bump)]
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
     pub lottery_config: Account<'info, LotteryConfig>,
     /// The current `LotteryRound` account.
     /// `mut` because winner information, randomness seed, and status flags are updated.
     #[account(
         mut, 
-        seeds = [b"lottery_round".as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], 
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], 
         bump = lottery_round.bump
     )]
     pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because it pays the crank bounty.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The lottery's `LotteryStats` account.
+    /// `mut` because the drawn round is counted.
+    #[account(mut, seeds = [LOTTERY_STATS_SEED, lottery_config.key().as_ref()], bump = lottery_stats.bump)]
+    pub lottery_stats: Account<'info, LotteryStats>,
     /// The SlotHashes sysvar account, used for pseudo-randomness.
     /// `/// CHECK:` is used because this is a sysvar and doesn't require typical ownership/PDA checks, but its address is validated.
     #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
     pub slot_hashes: UncheckedAccount<'info>,
+    /// The signer running the draw.
+    /// `mut` because it receives the crank bounty.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// The Solana System Program, required for the transfer out of the prize vault.
+    pub system_program: Program<'info, System>,
 }
 
 /// ## DistributePrizes Accounts
@@ -519,37 +3273,508 @@ pub struct DistributePrizes<'info> {
     /// The main `LotteryConfig` account.
     /// Constraints ensure the lottery is not paused.
     #[account(
-        seeds = [b"lottery_config"], 
+        seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], 
         bump = lottery_config.bump, 
         constraint = !lottery_config.is_paused @ LotteryError::LotteryPaused
     )]
     pub lottery_config: Account<'info, LotteryConfig>,
-    /// The `LotteryRound` account from which prizes are distributed.
-    /// `mut` because its lamports are transferred out, and `prizes_distributed` flag is set.
-    /// Constraints ensure it's the correct round, winners have been drawn, and prizes haven't been distributed yet.
+    /// The `LotteryRound` account from which prizes are distributed.
+    /// `mut` because its status moves to `Distributed`.
+    /// Constraints ensure it's the correct round and that it is `Drawn` (winners drawn, prizes not yet distributed).
+    #[account(
+        mut, 
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], 
+        bump = lottery_round.bump,
+        constraint = lottery_round.round_id == lottery_config.current_lottery_round_id @ LotteryError::IncorrectLotteryRound,
+        constraint = lottery_round.status != RoundStatus::Cancelled @ LotteryError::RoundCancelled,
+        constraint = !matches!(lottery_round.status, RoundStatus::Distributed | RoundStatus::Closed) @ LotteryError::PrizesAlreadyDistributed,
+        constraint = lottery_round.status == RoundStatus::Drawn @ LotteryError::WinnersNotYetDrawn,
+        constraint = !lottery_round.is_disputed @ LotteryError::RoundDisputed
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because the pot is paid out of it.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The `FeeVault` account.
+    /// `mut` because it is credited with the dev fee.
+    #[account(mut, seeds = [FEE_VAULT_SEED, lottery_config.key().as_ref()], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+    /// The lottery's `LotteryStats` account.
+    /// `mut` because the round's payouts are recorded.
+    #[account(mut, seeds = [LOTTERY_STATS_SEED, lottery_config.key().as_ref()], bump = lottery_stats.bump)]
+    pub lottery_stats: Account<'info, LotteryStats>,
+    /// The `Treasury` account.
+    /// `mut` because it tops up the main prize and releases the round's commitment.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// The global `PlatformConfig` account.
+    /// `mut` because it receives the platform fee.
+    #[account(mut, seeds = [PLATFORM_CONFIG_SEED], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// The signer running the distribution.
+    /// `mut` because it receives the crank bounty.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// The Solana System Program, required for the transfers out of the prize vault.
+    pub system_program: Program<'info, System>,
+    // Remaining accounts (for winners, then their profiles) are passed dynamically via `ctx.remaining_accounts`.
+    // Their mutability and correctness are checked within the instruction logic.
+    // It's crucial that the client provides these accounts in the correct order and ensures they are mutable.
+}
+
+/// ## FlagRound Accounts
+/// Defines the accounts required for the `flag_round` instruction.
+#[derive(Accounts)]
+pub struct FlagRound<'info> {
+    /// The main `LotteryConfig` account (read-only for the admin and auditor keys).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The current `LotteryRound` account.
+    /// `mut` because the dispute flag is set.
+    /// Constraints ensure the round is `Drawn` (not yet paid out or cancelled).
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump,
+        constraint = lottery_round.status != RoundStatus::Cancelled @ LotteryError::RoundCancelled,
+        constraint = !matches!(lottery_round.status, RoundStatus::Distributed | RoundStatus::Closed) @ LotteryError::PrizesAlreadyDistributed,
+        constraint = lottery_round.status == RoundStatus::Drawn @ LotteryError::WinnersNotYetDrawn
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The signer account, must be the admin or the designated auditor.
+    pub authority: Signer<'info>,
+}
+
+/// ## ResolveDispute Accounts
+/// Defines the accounts required for the `cancel_round` instruction.
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    /// The main `LotteryConfig` account (read-only for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The current `LotteryRound` account.
+    /// `mut` because its cancellation state is updated.
+    /// Constraints ensure the round is currently flagged as disputed.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump,
+        constraint = lottery_round.is_disputed @ LotteryError::RoundNotDisputed
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The signer account, must be the admin.
+    pub admin: Signer<'info>,
+    /// The `Treasury` account.
+    /// `mut` because the cancelled round's guaranteed main prize is released.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
+
+/// ## RedrawRound Accounts
+/// Defines the accounts required for the `redraw_round` instruction.
+#[derive(Accounts)]
+pub struct RedrawRound<'info> {
+    /// The main `LotteryConfig` account (read-only for the admin key and dispute window).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The current `LotteryRound` account.
+    /// `mut` because its winners and randomness seed are replaced.
+    /// Constraints ensure the round is currently flagged as disputed.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump,
+        constraint = lottery_round.is_disputed @ LotteryError::RoundNotDisputed
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The signer account, must be the admin.
+    pub admin: Signer<'info>,
+    /// The SlotHashes sysvar account, used for pseudo-randomness.
+    /// `/// CHECK:` is used because this is a sysvar and doesn't require typical ownership/PDA checks, but its address is validated.
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// ## RefundParticipants Accounts
+/// Defines the accounts required for the `refund_participants` instruction.
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct RefundParticipants<'info> {
+    /// The cancelled `LotteryRound` account.
+    /// `mut` because `refunds_processed` is updated.
+    /// Seeded by its lottery and the `round_id` argument so that refunds remain possible after later rounds have started.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_round.lottery_config.as_ref(), &round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump,
+        constraint = lottery_round.status == RoundStatus::Cancelled @ LotteryError::RoundNotCancelled
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because the refunds are paid out of it.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The Solana System Program, required for the transfers out of the prize vault.
+    pub system_program: Program<'info, System>,
+    // Remaining accounts (participants to refund) are passed dynamically via `ctx.remaining_accounts`.
+}
+
+/// ## MigrateConfig Accounts
+/// Defines the accounts required for the `migrate_config` instruction.
+#[derive(Accounts)]
+#[instruction(lottery_id: u64)]
+pub struct MigrateConfig<'info> {
+    /// The singleton `LotteryConfig` account of program versions before lotteries had IDs.
+    /// `/// CHECK:` An `UncheckedAccount` because the legacy layout cannot be decoded as `LotteryConfig`; the PDA and
+    /// program ownership are validated here, and the discriminator and layout inside the instruction.
+    /// `mut` because it is closed.
+    #[account(mut, owner = crate::ID, seeds = [LOTTERY_CONFIG_SEED], bump)]
+    pub legacy_lottery_config: UncheckedAccount<'info>,
+    /// The `LotteryConfig` account the legacy lottery continues under, seeded with "lottery_config" and `lottery_id`.
+    #[account(init, payer = admin, space = 8 + LotteryConfig::INIT_SPACE, seeds = [LOTTERY_CONFIG_SEED, &lottery_id.to_le_bytes()], bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The signer account, must be the legacy admin.
+    /// `mut` because it pays for the new config and receives the rent of the legacy one.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## MigrateRound Accounts
+/// Defines the accounts required for the `migrate_round` instruction.
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct MigrateRound<'info> {
+    /// The `LotteryConfig` created by `migrate_config` (read-only for the admin key and the round parameters).
+    #[account(
+        seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()],
+        bump = lottery_config.bump,
+        constraint = lottery_config.is_legacy_lottery @ LotteryError::NotLegacyLottery
+    )]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The legacy `LotteryRound` account, seeded with "lottery_round" and the `round_id` only.
+    /// `/// CHECK:` An `UncheckedAccount` because the legacy layout cannot be decoded as `LotteryRound`; the PDA and
+    /// program ownership are validated here, and the discriminator and layout inside the instruction.
+    /// A migrated round's legacy account is closed, so it is no longer owned by the program.
+    /// `mut` because its pot moves to the prize vault and it is closed.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, &round_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = *legacy_lottery_round.owner == crate::ID @ LotteryError::RoundAlreadyMigrated
+    )]
+    pub legacy_lottery_round: UncheckedAccount<'info>,
+    /// The `LotteryRound` account to be created in the current layout.
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + LotteryRound::INIT_SPACE,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &round_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lottery_round: Box<Account<'info, LotteryRound>>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because it receives the pot and its rent.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The signer account, must be the admin.
+    /// `mut` because it pays the rent of the new round and the prize vault, and receives the rent of the legacy round.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required for account creation and the rent transfer.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## ReconcileRound Accounts
+/// Defines the accounts required for the `reconcile_round` instruction.
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct ReconcileRound<'info> {
+    /// The main `LotteryConfig` account (read-only, to derive the round and treasury PDAs).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `LotteryRound` account to reconcile (read-only, for the lamports it still escrows).
+    #[account(
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because any surplus is transferred out of it.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The lottery's `Treasury` account.
+    /// `mut` because it receives any surplus.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// The Solana System Program, required for the transfer out of the prize vault.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## CloseRound Accounts
+/// Defines the accounts required for the `close_round` instruction.
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct CloseRound<'info> {
+    /// The finished `LotteryRound` account.
+    /// `close = rent_payer` transfers all of its lamports to the rent payer and deletes it after the instruction.
+    /// Seeded by its lottery and the `round_id` argument so that old rounds can be closed after later rounds have started.
+    #[account(
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_round.lottery_config.as_ref(), &round_id.to_le_bytes().as_ref()],
+        bump = lottery_round.bump,
+        close = rent_payer
+    )]
+    pub lottery_round: Account<'info, LotteryRound>,
+    /// The round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because its remaining lamports go to the rent payer.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump = lottery_round.prize_vault_bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The `RoundResult` account to be created.
+    /// It's a PDA seeded with "round_result", the round's `LotteryConfig` and the `round_id`.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RoundResult::INIT_SPACE,
+        seeds = [ROUND_RESULT_SEED, lottery_round.lottery_config.as_ref(), &round_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub round_result: Account<'info, RoundResult>,
+    /// The account that paid for the round's rent and its prize vault's rent.
+    /// `mut` because it receives the reclaimed lamports.
+    /// `/// CHECK:` The address is validated against `lottery_round.rent_payer`.
+    #[account(mut, address = lottery_round.rent_payer @ LotteryError::IncorrectRentPayer)]
+    pub rent_payer: AccountInfo<'info>,
+    /// The signer paying for the `RoundResult` account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## FundRentReserve Accounts
+/// Defines the accounts required for the `fund_rent_reserve` instruction.
+#[derive(Accounts)]
+pub struct FundRentReserve<'info> {
+    /// The main `LotteryConfig` account.
+    /// `mut` because it receives the lamports.
+    #[account(mut, seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The signer paying into the reserve.
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    /// The Solana System Program, required for the CPI SOL transfer.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## WithdrawRentReserve Accounts
+/// Defines the accounts required for the `withdraw_rent_reserve` instruction.
+#[derive(Accounts)]
+pub struct WithdrawRentReserve<'info> {
+    /// The main `LotteryConfig` account.
+    /// `mut` because lamports are transferred out of it.
+    #[account(mut, seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The signer account, must be the admin.
+    /// `mut` because it receives the lamports.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+/// ## InitializeTreasury Accounts
+/// Defines the accounts required for the `initialize_treasury` instruction.
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `Treasury` account to be created.
+    #[account(init, payer = admin, space = 8 + Treasury::INIT_SPACE, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// The signer account, must be the admin.
+    /// `mut` because it pays for the new account.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## InitializeFeeSplit Accounts
+/// Defines the accounts required for the `initialize_fee_split` instruction.
+#[derive(Accounts)]
+pub struct InitializeFeeSplit<'info> {
+    /// The main `LotteryConfig` account.
+    /// `mut` because the fee split becomes its dev fee receiver.
+    #[account(mut, seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `FeeSplit` account to be created.
+    #[account(init, payer = admin, space = 8 + FeeSplit::INIT_SPACE, seeds = [FEE_SPLIT_SEED, lottery_config.key().as_ref()], bump)]
+    pub fee_split: Account<'info, FeeSplit>,
+    /// The signer account, must be the admin.
+    /// `mut` because it pays for the new account.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## UpdateFeeSplit Accounts
+/// Defines the accounts required for the `set_fee_beneficiaries` instruction.
+#[derive(Accounts)]
+pub struct UpdateFeeSplit<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `FeeSplit` account to be updated.
+    #[account(mut, seeds = [FEE_SPLIT_SEED, lottery_config.key().as_ref()], bump = fee_split.bump)]
+    pub fee_split: Account<'info, FeeSplit>,
+    /// The signer account, must be the admin.
+    pub admin: Signer<'info>,
+}
+
+/// ## InitializeFeeVault Accounts
+/// Defines the accounts required for the `initialize_fee_vault` instruction.
+#[derive(Accounts)]
+pub struct InitializeFeeVault<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `FeeVault` account to be created.
+    #[account(init, payer = admin, space = 8 + FeeVault::INIT_SPACE, seeds = [FEE_VAULT_SEED, lottery_config.key().as_ref()], bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+    /// The signer account, must be the admin.
+    /// `mut` because it pays for the new account.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## UpdateFeeVault Accounts
+/// Defines the accounts required for the `set_fee_withdrawal_limit` instruction.
+#[derive(Accounts)]
+pub struct UpdateFeeVault<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `FeeVault` account to be updated.
+    #[account(mut, seeds = [FEE_VAULT_SEED, lottery_config.key().as_ref()], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+    /// The signer account, must be the admin.
+    pub admin: Signer<'info>,
+}
+
+/// ## WithdrawFees Accounts
+/// Defines the accounts required for the `withdraw_fees` instruction.
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key and the dev fee receiver).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `FeeVault` account.
+    /// `mut` because lamports are transferred out of it and its accounting is updated.
+    #[account(mut, seeds = [FEE_VAULT_SEED, lottery_config.key().as_ref()], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+    /// The development fee receiver account, or the lottery's `FeeSplit`.
+    /// `mut` because it receives lamports.
+    /// `/// CHECK:` The address is validated against `lottery_config.dev_fee_receiver`.
+    #[account(mut, address = lottery_config.dev_fee_receiver @ LotteryError::IncorrectDevFeeReceiver)]
+    pub dev_fee_receiver: AccountInfo<'info>,
+    /// The signer account, must be the admin.
+    pub admin: Signer<'info>,
+    // Remaining accounts: the `FeeSplit` beneficiaries in table order, when the dev fee receiver is a `FeeSplit`.
+}
+
+/// ## InitializeLotteryStats Accounts
+/// Defines the accounts required for the `initialize_lottery_stats` instruction.
+#[derive(Accounts)]
+pub struct InitializeLotteryStats<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `LotteryStats` account to be created.
+    #[account(init, payer = admin, space = 8 + LotteryStats::INIT_SPACE, seeds = [LOTTERY_STATS_SEED, lottery_config.key().as_ref()], bump)]
+    pub lottery_stats: Account<'info, LotteryStats>,
+    /// The signer account, must be the admin.
+    /// `mut` because it pays for the new account.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## FundTreasury Accounts
+/// Defines the accounts required for the `fund_treasury` instruction.
+#[derive(Accounts)]
+pub struct FundTreasury<'info> {
+    /// The `Treasury` account.
+    /// `mut` because it receives the lamports.
+    #[account(mut, seeds = [TREASURY_SEED, treasury.lottery_config.as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// The signer paying into the treasury.
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    /// The Solana System Program, required for the CPI SOL transfer.
+    pub system_program: Program<'info, System>,
+}
+
+/// ## WithdrawTreasury Accounts
+/// Defines the accounts required for the `withdraw_treasury` instruction.
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    /// The main `LotteryConfig` account (read-only, for the admin key).
+    #[account(seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `Treasury` account.
+    /// `mut` because lamports are transferred out of it.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// The signer account, must be the admin.
+    /// `mut` because it receives the lamports.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+/// ## StartScheduledRound Accounts
+/// Defines the accounts required for the `start_scheduled_round` instruction.
+#[derive(Accounts)]
+#[instruction(next_round_id_arg: u64)]
+pub struct StartScheduledRound<'info> {
+    /// The main `LotteryConfig` account.
+    /// `mut` because `current_lottery_round_id` is updated and the rent is paid from its reserve.
+    #[account(mut, seeds = [LOTTERY_CONFIG_SEED, &lottery_config.lottery_id.to_le_bytes()], bump = lottery_config.bump)]
+    pub lottery_config: Account<'info, LotteryConfig>,
+    /// The `LotteryRound` PDA of the current round ID.
+    /// `/// CHECK:` An `UncheckedAccount` because the round may already have been closed; the PDA is validated here,
+    /// and ownership and layout inside the instruction when the account still exists.
+    #[account(seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], bump)]
+    pub previous_round: UncheckedAccount<'info>,
+    /// The `LotteryRound` PDA of the next round ID, created by this instruction.
+    /// `/// CHECK:` An `UncheckedAccount` because it is allocated manually so that its rent can come from the
+    /// reserve; the PDA is validated here.
     #[account(
-        mut, 
-        seeds = [b"lottery_round".as_ref(), &lottery_config.current_lottery_round_id.to_le_bytes().as_ref()], 
-        bump = lottery_round.bump,
-        constraint = lottery_round.round_id == lottery_config.current_lottery_round_id @ LotteryError::IncorrectLotteryRound,
-        constraint = lottery_round.winners_drawn @ LotteryError::WinnersNotYetDrawn,
-        constraint = !lottery_round.prizes_distributed @ LotteryError::PrizesAlreadyDistributed
+        mut,
+        seeds = [LOTTERY_ROUND_SEED, lottery_config.key().as_ref(), &next_round_id_arg.to_le_bytes().as_ref()], // Checked against the config in the instruction.
+        bump
     )]
-    pub lottery_round: Account<'info, LotteryRound>,
-    /// The development fee receiver account.
-    /// `mut` because it receives lamports.
-    /// `/// CHECK:` The address is validated against `lottery_config.dev_fee_receiver`.
-    #[account(mut, address = lottery_config.dev_fee_receiver @ LotteryError::IncorrectDevFeeReceiver)]
-    pub dev_fee_receiver: AccountInfo<'info>,
-    // Remaining accounts (for winners) are passed dynamically via `ctx.remaining_accounts`.
-    // Their mutability and correctness are checked within the instruction logic.
-    // It's crucial that the client provides these accounts in the correct order and ensures they are mutable.
+    pub lottery_round: UncheckedAccount<'info>,
+    /// The new round's prize vault, a System Program account holding the pot apart from the round data.
+    /// `mut` because its rent is paid from the reserve.
+    #[account(mut, seeds = [PRIZE_VAULT_SEED, lottery_round.key().as_ref()], bump)]
+    pub prize_vault: SystemAccount<'info>,
+    /// The `Treasury` account.
+    /// `mut` because the round's guaranteed main prize is committed in it.
+    #[account(mut, seeds = [TREASURY_SEED, lottery_config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// The Solana System Program, required to allocate and assign the new round account.
+    pub system_program: Program<'info, System>,
 }
 
-
 /// ## LotteryConfig Account
-/// Stores the global configuration for the lottery program.
-/// This is a PDA seeded with `b"lottery_config"`.
+/// Stores the configuration of one lottery.
+/// This is a PDA seeded with `b"lottery_config"` and the `lottery_id`.
 #[account]
 #[derive(InitSpace)] // Automatically calculates space needed for the account based on its fields.
 pub struct LotteryConfig {
@@ -565,11 +3790,212 @@ pub struct LotteryConfig {
     pub current_lottery_round_id: u64,
     /// The bump seed used for PDA derivation of this account.
     pub bump: u8,
+    /// An optional auditor who, in addition to the admin, may flag a round during its dispute window.
+    pub auditor_key: Option<Pubkey>,
+    /// Length of the challenge period between a draw and prize distribution, in seconds (0 = disabled).
+    pub dispute_window_seconds: i64,
+    /// Flat part of the bounty paid to keepers running `conduct_draw` and `distribute_prizes`, in lamports.
+    pub crank_bounty_lamports: u64,
+    /// Percentage part of the keeper bounty, in basis points of the pot.
+    pub crank_bounty_bps: u16,
+    /// Maximum keeper bounty paid per crank, in lamports.
+    pub crank_bounty_cap_lamports: u64,
+    /// Time from a scheduled round's start to its draw, in seconds.
+    pub round_duration_seconds: i64,
+    /// Time between the starts of consecutive scheduled rounds, in seconds (0 = no automatic schedule).
+    pub round_cadence_seconds: i64,
+    /// Unix timestamp when the most recent round was started.
+    pub last_round_start_timestamp: i64,
+    /// Length of the draw calendar period, in seconds (0 = draws are not aligned to a calendar).
+    pub draw_period_seconds: i64,
+    /// Draw time within each calendar period, in seconds since the period start (periods start at the Unix epoch).
+    pub draw_period_offset_seconds: i64,
+    /// Time between the end of ticket sales and the draw, in seconds (0 = sales run until the draw).
+    pub sales_cutoff_seconds: i64,
+    /// How far `extend_round` moves the draw of an under-subscribed round, in seconds (0 = extensions disabled).
+    pub extension_interval_seconds: i64,
+    /// How often a round may be extended before `extend_round` cancels it.
+    pub max_round_extensions: u8,
+    /// Participants after which a round is sold out (0 = only when the round account is full).
+    pub max_participants_per_round: u32,
+    /// Guaranteed main prize of rounds started from now on, in lamports (0 = no guarantee).
+    pub guaranteed_main_prize_lamports: u64,
+    /// The identifier of this lottery among all lotteries run by the program.
+    pub lottery_id: u64,
+    /// Basis points of every distributed pot paid to the platform, fixed when the lottery is created.
+    pub platform_fee_bps: u16,
+    /// Basis points of every distributed pot paid to the dev fee receiver.
+    pub dev_fee_bps: u16,
+    /// Basis points of every distributed pot paid to the main winner. The 4 other winners share the rest equally.
+    pub main_winner_bps: u16,
+    /// Lowest entry fee `update_config` accepts, in lamports.
+    pub min_entry_fee_lamports: u64,
+    /// Highest entry fee `update_config` accepts, in lamports.
+    pub max_entry_fee_lamports: u64,
+    /// URI of the lottery's branding metadata (empty = none).
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,
+    /// Whether this lottery took over the singleton config of program versions before lotteries had IDs
+    /// (see `migrate_config`). Only this lottery can adopt legacy rounds with `migrate_round`.
+    pub is_legacy_lottery: bool,
+}
+
+/// ## PlatformConfig Account
+/// Sets the bounds of lotteries created through `create_lottery` and collects their platform fee.
+/// This is a PDA seeded with `b"platform_config"`; there is one per program.
+#[account]
+#[derive(InitSpace)]
+pub struct PlatformConfig {
+    /// The public key that may update the platform and withdraw its fees.
+    pub authority: Pubkey,
+    /// Platform fee recorded on lotteries created from now on, in basis points of the pot.
+    pub platform_fee_bps: u16,
+    /// Lowest entry fee a created lottery may charge, in lamports.
+    pub min_entry_fee_lamports: u64,
+    /// Highest entry fee a created lottery may charge, in lamports.
+    pub max_entry_fee_lamports: u64,
+    /// Highest dev fee a creator may choose, in basis points of the pot.
+    pub max_dev_fee_bps: u16,
+    /// Lowest main winner share a creator may choose, in basis points of the pot.
+    pub min_main_winner_bps: u16,
+    /// Number of lotteries created through `create_lottery`.
+    pub lottery_count: u64,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+}
+
+/// Upper bound on the platform fee (10% of the pot).
+pub const MAX_PLATFORM_FEE_BPS: u16 = 1_000;
+/// Maximum length of a lottery's metadata URI, in bytes.
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+/// ## Treasury Account
+/// Holds the lamports that back guaranteed main prizes.
+/// This is a PDA seeded with `b"treasury"` and the `LotteryConfig` it belongs to.
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    /// Lamports promised to rounds that have started but have not been paid out or cancelled yet.
+    pub committed_lamports: u64,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+    /// The `LotteryConfig` of the lottery this treasury backs.
+    pub lottery_config: Pubkey,
+}
+
+/// ## FeeVault Account
+/// Holds a lottery's accrued dev fees until the administrator withdraws them with `withdraw_fees`.
+/// This is a PDA seeded with `b"fee_vault"` and the `LotteryConfig` it belongs to.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeVault {
+    /// The `LotteryConfig` of the lottery whose dev fees accrue here.
+    pub lottery_config: Pubkey,
+    /// All dev fees ever credited by `distribute_prizes`, in lamports.
+    pub total_accrued_lamports: u64,
+    /// All fees ever paid out by `withdraw_fees`, in lamports. The vault holds the difference above its rent.
+    pub total_withdrawn_lamports: u64,
+    /// The most `withdraw_fees` may pay out per period, in lamports (0 = unlimited).
+    pub withdrawal_limit_lamports: u64,
+    /// The length of a withdrawal period, in seconds (0 = unlimited).
+    pub withdrawal_period_seconds: i64,
+    /// Unix timestamp when the current withdrawal period started.
+    pub period_start_timestamp: i64,
+    /// Lamports withdrawn in the current period.
+    pub period_withdrawn_lamports: u64,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+}
+
+/// ## LotteryStats Account
+/// Keeps a lottery's lifetime statistics for clients to display. Only the program writes it.
+/// This is a PDA seeded with `b"lottery_stats"` and the `LotteryConfig` it belongs to.
+#[account]
+#[derive(InitSpace)]
+pub struct LotteryStats {
+    /// The `LotteryConfig` of the lottery these statistics describe.
+    pub lottery_config: Pubkey,
+    /// Rounds drawn by `conduct_draw`. Redraws of a disputed round are not counted again.
+    pub total_rounds_drawn: u64,
+    /// Tickets sold by `enter_lottery`, including those of rounds that were later cancelled and refunded.
+    pub total_tickets_sold: u64,
+    /// Prizes paid to winners by `distribute_prizes`, including treasury top-ups, in lamports.
+    pub total_paid_to_winners_lamports: u64,
+    /// Dev fees credited to the `FeeVault` by `distribute_prizes`, in lamports.
+    pub total_dev_fees_lamports: u64,
+    /// The largest main prize paid so far, including any treasury top-up, in lamports.
+    pub largest_jackpot_lamports: u64,
+    /// The round that paid `largest_jackpot_lamports` (0 = no prize paid yet).
+    pub largest_jackpot_round_id: u64,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+}
+
+/// ## PlayerProfile Account
+/// Keeps one wallet's history across all lotteries, and its preferences, so that clients can show it without scanning
+/// every round. Created by the wallet's first `enter_lottery`.
+/// This is a PDA seeded with `b"player_profile"` and the player's wallet.
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerProfile {
+    /// The wallet this profile belongs to.
+    pub player: Pubkey,
+    /// Rounds the player entered, in any lottery.
+    pub rounds_entered: u64,
+    /// Tickets the player bought. Each entry buys one ticket.
+    pub tickets_bought: u64,
+    /// Prizes paid to the player by `distribute_prizes`, including treasury top-ups, in lamports.
+    pub lifetime_winnings_lamports: u64,
+    /// The `LotteryConfig` of the lottery the player entered last.
+    pub last_lottery_config: Pubkey,
+    /// The round of `last_lottery_config` the player entered last.
+    pub last_round_id: u64,
+    /// The account `distribute_prizes` pays the player's prizes to (`None` = the player's wallet).
+    pub preferred_payout_address: Option<Pubkey>,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+}
+
+impl PlayerProfile {
+    /// The account the player's prizes are paid to.
+    pub fn payout_address(&self) -> Pubkey {
+        self.preferred_payout_address.unwrap_or(self.player)
+    }
+}
+
+/// ## FeeSplit Account
+/// Divides a lottery's withdrawn dev fees between several beneficiaries when it is the dev fee receiver.
+/// This is a PDA seeded with `b"fee_split"` and the `LotteryConfig` it belongs to.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeSplit {
+    /// The `LotteryConfig` of the lottery whose dev fee is split.
+    pub lottery_config: Pubkey,
+    /// The beneficiaries, in payout order. The first one also receives the rounding remainder.
+    #[max_len(MAX_FEE_BENEFICIARIES)]
+    pub beneficiaries: Vec<FeeBeneficiary>,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+}
+
+/// ## FeeBeneficiary
+/// One entry of a `FeeSplit` table.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct FeeBeneficiary {
+    pub address: Pubkey,
+    /// Share of the dev fee, in basis points.
+    pub weight_bps: u16,
 }
 
+/// The most beneficiaries a `FeeSplit` can hold.
+pub const MAX_FEE_BENEFICIARIES: usize = 8;
+
+/// Winner accounts at the start of the remaining accounts of `distribute_prizes`: the main winner and 4 other winners.
+pub const WINNER_ACCOUNT_COUNT: usize = 1 + 4;
+
 /// ## LotteryRound Account
 /// Stores the state for a specific lottery round.
-/// This is a PDA seeded with `b"lottery_round"` and the `round_id`.
+/// This is a PDA seeded with `b"lottery_round"`, the `LotteryConfig` it belongs to and the `round_id`.
 #[account]
 #[derive(InitSpace)]
 pub struct LotteryRound {
@@ -582,14 +4008,10 @@ pub struct LotteryRound {
     /// The total amount of lamports collected from ticket sales for this round.
     pub total_pot_lamports: u64,
     /// A vector storing the public keys of all participants who entered this round.
-    #[max_len(2000)] // Example: Max 2000 participants. Adjust based on expected scale and transaction size limits.
+    #[max_len(MAX_PARTICIPANTS)]
     pub participants: Vec<Pubkey>,
-    /// Flag indicating if the lottery round is currently active (i.e., accepting entries).
-    pub is_active: bool,
-    /// Flag indicating if the winners for this round have been drawn.
-    pub winners_drawn: bool,
-    /// Flag indicating if the prizes for this round have been distributed.
-    pub prizes_distributed: bool,
+    /// The lifecycle status of the round. Only changed through `transition_round_status`.
+    pub status: RoundStatus,
     /// The public key of the main winner of this round (receives 50% of the pot).
     pub main_winner: Option<Pubkey>,
     /// A vector storing the public keys of the other winners (e.g., 4 winners receiving 10% each).
@@ -599,6 +4021,677 @@ pub struct LotteryRound {
     pub randomness_seed: Option<[u8; 32]>,
     /// The bump seed used for PDA derivation of this account.
     pub bump: u8,
+    /// Unix timestamp until which the drawn result can be flagged. Prizes cannot be distributed before it.
+    pub dispute_deadline: i64,
+    /// Flag indicating that the `Drawn` round was flagged during its dispute window and awaits resolution.
+    pub is_disputed: bool,
+    /// The amount refunded to each participant of a cancelled round, in lamports.
+    pub refund_per_participant_lamports: u64,
+    /// Number of participants (in entry order) already refunded for a cancelled round.
+    pub refunds_processed: u32,
+    /// The keeper who ran `conduct_draw` for this round.
+    pub draw_keeper: Option<Pubkey>,
+    /// The bounty paid to the draw keeper, in lamports.
+    pub draw_bounty_lamports: u64,
+    /// The keeper who ran `distribute_prizes` for this round.
+    pub distribution_keeper: Option<Pubkey>,
+    /// The bounty paid to the distribution keeper, in lamports.
+    pub distribution_bounty_lamports: u64,
+    /// The account that paid this round's rent. It receives the rent back when the round is closed.
+    pub rent_payer: Pubkey,
+    /// Merkle root over `participants` in entry order, set when sales close (see `participants_merkle_root`).
+    pub participants_root: [u8; 32],
+    /// Unix timestamp after which no more tickets are sold. At or before `draw_timestamp`.
+    pub sales_close_timestamp: i64,
+    /// The slot in which `close_round_sales` ran (0 while sales are open).
+    pub sales_closed_slot: u64,
+    /// Every time `extend_round` moved this round's draw, oldest first.
+    #[max_len(MAX_ROUND_EXTENSIONS)]
+    pub extensions: Vec<RoundExtension>,
+    /// Whether the round reached its participant capacity. A sold-out round can be closed and drawn immediately.
+    pub is_sold_out: bool,
+    /// Lamports added to the pot with `contribute_to_pot`, per contributor in order of their first contribution.
+    /// Included in `total_pot_lamports` until crank bounties are paid out of it (see `LotteryRound::contribution_refund`).
+    #[max_len(MAX_POT_CONTRIBUTORS)]
+    pub contributions: Vec<PotContribution>,
+    /// The minimum main prize promised for this round, in lamports. The treasury pays any shortfall of the main
+    /// winner's share at distribution (0 = no guarantee).
+    pub guaranteed_main_prize_lamports: u64,
+    /// The `LotteryConfig` of the lottery this round belongs to.
+    pub lottery_config: Pubkey,
+    /// The price of a ticket in this round, copied from the lottery when the round started.
+    pub entry_fee_lamports: u64,
+    /// Basis points of the pot paid to the platform, copied from the lottery when the round started.
+    pub platform_fee_bps: u16,
+    /// Basis points of the pot paid to the dev fee receiver, copied from the lottery when the round started.
+    pub dev_fee_bps: u16,
+    /// Basis points of the pot paid to the main winner, copied from the lottery when the round started. The 4 other
+    /// winners share the rest equally.
+    pub main_winner_bps: u16,
+    /// The bump seed of the round's prize vault PDA (seeds = ["prize_vault", lottery_round]), which holds the pot.
+    pub prize_vault_bump: u8,
+    /// The slot in which the round was last flagged with `flag_round` (0 if it never was).
+    pub flagged_slot: u64,
+    /// The slot of the slot hash `redraw_round` drew the current winners from (0 if the round was never redrawn).
+    pub redraw_slot: u64,
+    /// Length of the challenge period after the round's draw, copied from the lottery when the round started.
+    pub dispute_window_seconds: i64,
+    /// Flat part of the crank bounty, copied from the lottery when the round started.
+    pub crank_bounty_lamports: u64,
+    /// Percentage part of the crank bounty in basis points of the pot, copied from the lottery when the round started.
+    pub crank_bounty_bps: u16,
+    /// Maximum crank bounty paid per crank, copied from the lottery when the round started.
+    pub crank_bounty_cap_lamports: u64,
+}
+
+impl LotteryRound {
+    /// The payout split recorded on this round.
+    pub fn split(&self) -> payout::SplitBps {
+        payout::SplitBps {
+            platform_fee_bps: self.platform_fee_bps,
+            dev_fee_bps: self.dev_fee_bps,
+            main_winner_bps: self.main_winner_bps,
+        }
+    }
+
+    /// Returns how many refunds a cancelled round pays: one per participant and one per contributor.
+    pub fn refund_count(&self) -> usize {
+        self.participants.len() + self.contributions.len()
+    }
+
+    /// Returns the crank bounties paid out of the pot so far.
+    pub fn bounty_lamports(&self) -> Option<u64> {
+        self.draw_bounty_lamports.checked_add(self.distribution_bounty_lamports)
+    }
+
+    /// Returns what a contribution of `amount_lamports` is refunded when the round is cancelled. The crank bounties
+    /// already paid were taken from the whole pot, so contributors bear them in proportion to what they put in.
+    pub fn contribution_refund(&self, amount_lamports: u64) -> Option<u64> {
+        let pot_before_bounties = self.total_pot_lamports.checked_add(self.bounty_lamports()?)?;
+        payout::pro_rata(amount_lamports, self.total_pot_lamports, pot_before_bounties).ok()
+    }
+
+    /// Returns the part of the pot refunded to participants: what is left once contributors get their share back.
+    /// Never less than zero, so that a round whose bounties exceeded its ticket sales can still be refunded.
+    pub fn ticket_lamports(&self) -> Option<u64> {
+        let contributed_lamports = self
+            .contributions
+            .iter()
+            .try_fold(0u64, |total, c| total.checked_add(self.contribution_refund(c.amount_lamports)?))?;
+        Some(self.total_pot_lamports.saturating_sub(contributed_lamports))
+    }
+
+    /// Returns the recipient, amount and whether it is a contribution for the `index`-th refund of a cancelled
+    /// round: participants in entry order first, then contributors. The first participant also receives the rounding
+    /// remainder of the ticket sales.
+    pub fn refund_recipient(&self, index: usize) -> Option<(Pubkey, u64, bool)> {
+        match self.participants.get(index) {
+            Some(participant) => {
+                let refund = match index {
+                    0 => self.refund_per_participant_lamports.checked_add(self.refund_remainder_lamports()?)?,
+                    _ => self.refund_per_participant_lamports,
+                };
+                Some((*participant, refund, false))
+            }
+            None => self
+                .contributions
+                .get(index - self.participants.len())
+                .and_then(|c| Some((c.contributor, self.contribution_refund(c.amount_lamports)?, true))),
+        }
+    }
+
+    /// Returns the rounding remainder of splitting the ticket sales of a cancelled round between its participants.
+    fn refund_remainder_lamports(&self) -> Option<u64> {
+        let refunded_equally = self.refund_per_participant_lamports.checked_mul(self.participants.len() as u64)?;
+        self.ticket_lamports()?.checked_sub(refunded_equally)
+    }
+
+    /// Returns the lamports the round's prize vault holds for its pot, on top of its rent-exempt minimum: the whole pot
+    /// until it is paid out, nothing once distributed, and what is left to refund once cancelled.
+    pub fn escrowed_lamports(&self) -> Option<u64> {
+        match self.status {
+            RoundStatus::Distributed | RoundStatus::Closed => Some(0),
+            RoundStatus::Cancelled => {
+                let processed = self.refunds_processed as usize;
+                let refunded_participants = processed.min(self.participants.len());
+                let mut refunded = self.refund_per_participant_lamports.checked_mul(refunded_participants as u64)?;
+                if refunded_participants > 0 {
+                    refunded = refunded.checked_add(self.refund_remainder_lamports()?)?;
+                }
+                for contribution in self.contributions.iter().take(processed - refunded_participants) {
+                    refunded = refunded.checked_add(self.contribution_refund(contribution.amount_lamports)?)?;
+                }
+                self.total_pot_lamports.checked_sub(refunded)
+            }
+            _ => Some(self.total_pot_lamports),
+        }
+    }
+}
+
+/// The most distinct contributors a round can record.
+pub const MAX_POT_CONTRIBUTORS: usize = 16;
+/// The longest memo accepted by `contribute_to_pot`, in bytes.
+pub const MAX_CONTRIBUTION_MEMO_LEN: usize = 128;
+
+/// ## PotContribution
+/// The lamports one account added to a round's pot with `contribute_to_pot`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct PotContribution {
+    pub contributor: Pubkey,
+    pub amount_lamports: u64,
+}
+
+/// The most participants a round account can hold. Adjust based on expected scale and transaction size limits.
+pub const MAX_PARTICIPANTS: usize = 2000;
+
+/// The most extensions a round can record; `set_round_extensions` rejects a higher `max_round_extensions`.
+pub const MAX_ROUND_EXTENSIONS: usize = 8;
+
+/// ## RoundExtension
+/// One draw-time extension of an under-subscribed round, recorded by `extend_round`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct RoundExtension {
+    /// Unix timestamp when the round was extended.
+    pub extended_at: i64,
+    /// The draw time that had been reached.
+    pub previous_draw_timestamp: i64,
+    /// The new draw time.
+    pub new_draw_timestamp: i64,
+    /// Participants at the time of the extension.
+    pub participant_count: u32,
+}
+
+/// ## RoundStatus Enum
+/// The lifecycle of a `LotteryRound`:
+///
+/// `Open` -> `SalesClosed` -> `Drawn` -> `Distributed` -> `Closed`
+///
+/// `extend_round` may move an under-subscribed round from `SalesClosed` back to `Open`.
+///
+/// Any round that has not been paid out can instead move to `Cancelled`, from where it is refunded and then `Closed`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum RoundStatus {
+    /// The round accepts entries until its ticket sales end at `sales_close_timestamp`, or until it sells out.
+    Open,
+    /// Entries are closed and the round awaits its draw.
+    SalesClosed,
+    /// Winners have been drawn; prizes can be distributed once the dispute window has elapsed.
+    Drawn,
+    /// Prizes have been paid out.
+    Distributed,
+    /// The round was cancelled and its pot is refunded to participants.
+    Cancelled,
+    /// The round is finished and no further instruction may modify it.
+    Closed,
+}
+
+impl RoundStatus {
+    /// Returns whether a round in this status may move to `next`.
+    pub fn can_transition_to(self, next: RoundStatus) -> bool {
+        matches!(
+            (self, next),
+            (RoundStatus::Open, RoundStatus::SalesClosed)
+                | (RoundStatus::SalesClosed, RoundStatus::Open)
+                | (RoundStatus::SalesClosed, RoundStatus::Drawn)
+                | (RoundStatus::Drawn, RoundStatus::Distributed)
+                | (RoundStatus::Open | RoundStatus::SalesClosed | RoundStatus::Drawn, RoundStatus::Cancelled)
+                | (RoundStatus::Distributed | RoundStatus::Cancelled, RoundStatus::Closed)
+        )
+    }
+}
+
+/// ## LegacyLotteryConfig
+/// The singleton `LotteryConfig` layout written by program versions before lotteries had IDs
+/// (PDA: seeds = ["lottery_config"]). Only read by `migrate_config`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyLotteryConfig {
+    pub admin_key: Pubkey,
+    pub dev_fee_receiver: Pubkey,
+    pub entry_fee_lamports: u64,
+    pub is_paused: bool,
+    pub current_lottery_round_id: u64,
+    pub bump: u8,
+}
+
+/// ## LegacyLotteryRound
+/// The `LotteryRound` layout written by program versions before `RoundStatus` was introduced, where the lifecycle
+/// was tracked by the `is_active`, `winners_drawn` and `prizes_distributed` flags. These rounds belong to the
+/// singleton legacy config (PDA: seeds = ["lottery_round", round_id]). Only read by `migrate_round`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyLotteryRound {
+    pub round_id: u64,
+    pub start_timestamp: i64,
+    pub draw_timestamp: i64,
+    pub total_pot_lamports: u64,
+    #[max_len(2000)]
+    pub participants: Vec<Pubkey>,
+    pub is_active: bool,
+    pub winners_drawn: bool,
+    pub prizes_distributed: bool,
+    pub main_winner: Option<Pubkey>,
+    #[max_len(4)]
+    pub other_winners: Vec<Pubkey>,
+    pub randomness_seed: Option<[u8; 32]>,
+    pub bump: u8,
+}
+
+impl LegacyLotteryRound {
+    /// Maps the legacy flags to a `RoundStatus`. Flag combinations the old program could not produce are rejected.
+    pub fn status(&self) -> Result<RoundStatus> {
+        match (self.is_active, self.winners_drawn, self.prizes_distributed) {
+            (true, false, false) => Ok(RoundStatus::Open),
+            (false, true, false) => Ok(RoundStatus::Drawn),
+            (false, true, true) => Ok(RoundStatus::Distributed),
+            _ => err!(LotteryError::InconsistentLegacyRound),
+        }
+    }
+}
+
+/// ## RoundResult Account
+/// The compact record of a closed round, kept after its `LotteryRound` account has been closed.
+/// This is a PDA seeded with `b"round_result"`, the `LotteryConfig` of the round and the `round_id`.
+#[account]
+#[derive(InitSpace)]
+pub struct RoundResult {
+    /// The unique identifier of the closed round.
+    pub round_id: u64,
+    /// How the round ended: `Distributed` or `Cancelled`.
+    pub outcome: RoundStatus,
+    /// The round's pot as recorded on the `LotteryRound` when it was closed (after crank bounties), in lamports.
+    pub total_pot_lamports: u64,
+    /// Number of participants in the round.
+    pub participant_count: u32,
+    /// Keccak-256 of the participants' public keys concatenated in entry order (see `participants_hash`).
+    pub participants_hash: [u8; 32],
+    /// Merkle root over the participants in entry order, against which inclusion proofs are checked.
+    pub participants_root: [u8; 32],
+    /// The main winner, if the round was drawn.
+    pub main_winner: Option<Pubkey>,
+    /// The other winners, in selection order.
+    #[max_len(4)]
+    pub other_winners: Vec<Pubkey>,
+    /// The randomness seed used for the draw, if the round was drawn.
+    pub randomness_seed: Option<[u8; 32]>,
+    /// Unix timestamp when the round was closed.
+    pub closed_timestamp: i64,
+    /// The bump seed used for PDA derivation of this account.
+    pub bump: u8,
+    /// The `LotteryConfig` of the lottery the round belonged to.
+    pub lottery_config: Pubkey,
+}
+
+// ## Events
+// Typed events emitted with `emit!` on every state transition. They are part of the IDL so clients can decode them
+// from transaction logs instead of parsing `msg!` strings.
+
+/// Emitted by `initialize_config`.
+#[event]
+pub struct ConfigInitialized {
+    pub lottery_id: u64,
+    pub lottery_config: Pubkey,
+    pub admin: Pubkey,
+    pub dev_fee_receiver: Pubkey,
+    pub entry_fee_lamports: u64,
+}
+
+/// Emitted by `initialize_platform` and `update_platform` with the new values.
+#[event]
+pub struct PlatformConfigUpdated {
+    pub authority: Pubkey,
+    pub platform_fee_bps: u16,
+    pub min_entry_fee_lamports: u64,
+    pub max_entry_fee_lamports: u64,
+    pub max_dev_fee_bps: u16,
+    pub min_main_winner_bps: u16,
+}
+
+/// Emitted by `initialize_fee_split` and `set_fee_beneficiaries` with the new table.
+#[event]
+pub struct FeeBeneficiariesUpdated {
+    pub lottery_config: Pubkey,
+    pub beneficiaries: Vec<FeeBeneficiary>,
+}
+
+/// Emitted by `set_fee_withdrawal_limit`.
+#[event]
+pub struct FeeWithdrawalLimitUpdated {
+    pub lottery_config: Pubkey,
+    pub withdrawal_limit_lamports: u64,
+    pub withdrawal_period_seconds: i64,
+}
+
+/// Emitted by `withdraw_fees` for every recipient paid.
+#[event]
+pub struct FeesWithdrawn {
+    pub lottery_config: Pubkey,
+    pub recipient: Pubkey,
+    pub amount_lamports: u64,
+}
+
+/// Emitted by `withdraw_platform_fees`.
+#[event]
+pub struct PlatformFeesWithdrawn {
+    pub authority: Pubkey,
+    pub amount_lamports: u64,
+    pub remaining_lamports: u64,
+}
+
+/// Emitted by `create_lottery`.
+#[event]
+pub struct LotteryCreated {
+    pub lottery_id: u64,
+    pub lottery_config: Pubkey,
+    pub creator: Pubkey,
+    pub dev_fee_receiver: Pubkey,
+    pub entry_fee_lamports: u64,
+    pub platform_fee_bps: u16,
+    pub dev_fee_bps: u16,
+    pub main_winner_bps: u16,
+    pub metadata_uri: String,
+}
+
+/// Emitted by `set_lottery_metadata`.
+#[event]
+pub struct LotteryMetadataUpdated {
+    pub lottery_config: Pubkey,
+    pub metadata_uri: String,
+}
+
+/// Emitted by `update_config` with the values before and after the update.
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub old_dev_fee_receiver: Pubkey,
+    pub new_dev_fee_receiver: Pubkey,
+    pub old_entry_fee_lamports: u64,
+    pub new_entry_fee_lamports: u64,
+    pub old_is_paused: bool,
+    pub new_is_paused: bool,
+}
+
+/// Emitted by `set_crank_bounty` with the values before and after the update.
+#[event]
+pub struct CrankBountyUpdated {
+    pub old_bounty_lamports: u64,
+    pub new_bounty_lamports: u64,
+    pub old_bounty_bps: u16,
+    pub new_bounty_bps: u16,
+    pub old_bounty_cap_lamports: u64,
+    pub new_bounty_cap_lamports: u64,
+}
+
+/// Emitted by `set_dispute_config` with the values before and after the update.
+#[event]
+pub struct DisputeConfigUpdated {
+    pub old_auditor_key: Option<Pubkey>,
+    pub new_auditor_key: Option<Pubkey>,
+    pub old_dispute_window_seconds: i64,
+    pub new_dispute_window_seconds: i64,
+}
+
+/// Emitted by `set_round_schedule` with the values before and after the update.
+#[event]
+pub struct RoundScheduleUpdated {
+    pub old_round_duration_seconds: i64,
+    pub new_round_duration_seconds: i64,
+    pub old_round_cadence_seconds: i64,
+    pub new_round_cadence_seconds: i64,
+}
+
+/// Emitted by `set_draw_calendar` with the values before and after the update.
+#[event]
+pub struct DrawCalendarUpdated {
+    pub old_draw_period_seconds: i64,
+    pub new_draw_period_seconds: i64,
+    pub old_draw_period_offset_seconds: i64,
+    pub new_draw_period_offset_seconds: i64,
+}
+
+/// Emitted by `set_sales_cutoff` with the values before and after the update.
+#[event]
+pub struct SalesCutoffUpdated {
+    pub old_sales_cutoff_seconds: i64,
+    pub new_sales_cutoff_seconds: i64,
+}
+
+/// Emitted by `set_round_extensions` with the values before and after the update.
+#[event]
+pub struct RoundExtensionRuleUpdated {
+    pub old_extension_interval_seconds: i64,
+    pub new_extension_interval_seconds: i64,
+    pub old_max_round_extensions: u8,
+    pub new_max_round_extensions: u8,
+}
+
+/// Emitted by `set_max_participants` with the values before and after the update.
+#[event]
+pub struct MaxParticipantsUpdated {
+    pub old_max_participants_per_round: u32,
+    pub new_max_participants_per_round: u32,
+}
+
+/// Emitted by `set_guaranteed_main_prize` with the values before and after the update.
+#[event]
+pub struct GuaranteedMainPrizeUpdated {
+    pub old_guaranteed_main_prize_lamports: u64,
+    pub new_guaranteed_main_prize_lamports: u64,
+}
+
+/// Emitted by `fund_treasury` (positive amount) and `withdraw_treasury` (negative amount).
+#[event]
+pub struct TreasuryChanged {
+    pub amount_lamports: i64,
+    /// The treasury lamports not committed to any round after the change.
+    pub available_lamports: u64,
+    pub committed_lamports: u64,
+}
+
+/// Emitted by `fund_rent_reserve` (positive amount) and `withdraw_rent_reserve` (negative amount).
+#[event]
+pub struct RentReserveChanged {
+    pub amount_lamports: i64,
+    /// The reserve after the change.
+    pub reserve_lamports: u64,
+}
+
+/// Emitted by `start_new_lottery_round` and `start_scheduled_round`.
+#[event]
+pub struct RoundStarted {
+    pub lottery_config: Pubkey,
+    pub round_id: u64,
+    pub start_timestamp: i64,
+    pub draw_timestamp: i64,
+    pub entry_fee_lamports: u64,
+}
+
+/// Emitted by `enter_lottery`.
+#[event]
+pub struct TicketPurchased {
+    pub round_id: u64,
+    pub buyer: Pubkey,
+    /// Number of tickets bought by this entry.
+    pub count: u32,
+    pub price_lamports: u64,
+    /// The round's pot after this purchase.
+    pub pot_after_lamports: u64,
+    /// Whether this purchase sold the round out.
+    pub sold_out: bool,
+}
+
+/// Emitted by `contribute_to_pot`.
+#[event]
+pub struct PotContributed {
+    pub round_id: u64,
+    /// The sponsor who paid the contribution.
+    pub contributor: Pubkey,
+    pub amount_lamports: u64,
+    pub memo: Option<String>,
+    /// The round's pot after this contribution.
+    pub pot_after_lamports: u64,
+}
+
+/// Emitted by `enter_lottery` when a round reaches its participant capacity.
+#[event]
+pub struct RoundSoldOut {
+    pub round_id: u64,
+    pub participant_count: u32,
+    pub total_pot_lamports: u64,
+}
+
+/// Emitted by `close_sales` with the commitment to the final participant list.
+#[event]
+pub struct SalesClosed {
+    pub round_id: u64,
+    pub participant_count: u32,
+    pub participants_root: [u8; 32],
+    /// The slot in which sales closed. The draw's slot hash must not be older.
+    pub slot: u64,
+}
+
+/// Emitted by `extend_round` when it moves the draw of an under-subscribed round.
+#[event]
+pub struct RoundExtended {
+    pub round_id: u64,
+    /// 1 for the round's first extension.
+    pub extension_number: u8,
+    pub previous_draw_timestamp: i64,
+    pub new_draw_timestamp: i64,
+    pub sales_close_timestamp: i64,
+    pub participant_count: u32,
+}
+
+/// Emitted by `conduct_draw` and, with `is_redraw` set, by `redraw_round`.
+#[event]
+pub struct DrawConducted {
+    pub round_id: u64,
+    pub randomness_seed: [u8; 32],
+    pub participant_count: u32,
+    pub main_winner: Pubkey,
+    pub other_winners: Vec<Pubkey>,
+    /// Prizes cannot be distributed before this timestamp.
+    pub dispute_deadline: i64,
+    pub is_redraw: bool,
+}
+
+/// The reason a lamport amount was paid out of a round.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrizeKind {
+    DevFee,
+    MainWinner,
+    OtherWinner,
+    CrankBounty,
+    /// Top-up of the main prize from the treasury to the round's guarantee.
+    GuaranteeTopUp,
+    /// Platform fee paid to the `PlatformConfig` account.
+    PlatformFee,
+}
+
+/// Emitted by `set_payout_address`.
+#[event]
+pub struct PayoutAddressUpdated {
+    pub player: Pubkey,
+    /// `None` when prizes are paid to the player's wallet.
+    pub payout_address: Option<Pubkey>,
+}
+
+/// Emitted for every payout made by `distribute_prizes` and for every crank bounty.
+#[event]
+pub struct PrizePaid {
+    pub round_id: u64,
+    pub recipient: Pubkey,
+    pub amount_lamports: u64,
+    pub kind: PrizeKind,
+}
+
+/// Emitted by `transition_round_status` whenever a round changes status.
+#[event]
+pub struct RoundStatusChanged {
+    pub round_id: u64,
+    pub previous: RoundStatus,
+    pub next: RoundStatus,
+}
+
+/// Emitted by `migrate_config` once the legacy singleton config has moved to a `LotteryConfig` keyed by lottery ID.
+#[event]
+pub struct ConfigMigrated {
+    pub lottery_id: u64,
+    pub lottery_config: Pubkey,
+    pub admin: Pubkey,
+    pub current_lottery_round_id: u64,
+}
+
+/// Emitted by `migrate_round` once a legacy round has been moved into the current layout.
+#[event]
+pub struct RoundMigrated {
+    pub round_id: u64,
+    pub status: RoundStatus,
+}
+
+/// Emitted by `close_round` once the `RoundResult` has been written and the round's rent is reclaimed.
+#[event]
+pub struct RoundResultRecorded {
+    pub round_id: u64,
+    pub outcome: RoundStatus,
+    pub round_result: Pubkey,
+    pub rent_payer: Pubkey,
+    pub reclaimed_lamports: u64,
+}
+
+/// Emitted by `flag_round`.
+#[event]
+pub struct RoundFlagged {
+    pub round_id: u64,
+    pub flagged_by: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted by `cancel_round`.
+#[event]
+pub struct RoundCancelled {
+    pub round_id: u64,
+    pub participant_count: u32,
+    pub refund_per_participant_lamports: u64,
+}
+
+/// Emitted by `refund_participants` for every refunded participant.
+#[event]
+pub struct RefundPaid {
+    pub round_id: u64,
+    /// The refunded participant or, if `is_contribution` is set, contributor.
+    pub participant: Pubkey,
+    pub amount_lamports: u64,
+    /// Whether this refunds a pot contribution rather than a ticket.
+    pub is_contribution: bool,
+}
+
+/// Emitted by `reconcile_round`.
+#[event]
+pub struct RoundReconciled {
+    pub round_id: u64,
+    pub balance_lamports: u64,
+    pub rent_exempt_minimum_lamports: u64,
+    /// The part of the pot the round still owes (see `LotteryRound::escrowed_lamports`).
+    pub escrowed_lamports: u64,
+    /// Lamports above what the round owes, moved to the treasury.
+    pub surplus_lamports: u64,
+    /// Lamports missing from what the round owes.
+    pub shortfall_lamports: u64,
+}
+
+/// How a round reached the end of its lifecycle.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundCloseReason {
+    PrizesDistributed,
+    Refunded,
+}
+
+/// Emitted when a round's funds have been fully paid out, either as prizes or as refunds.
+#[event]
+pub struct RoundClosed {
+    pub round_id: u64,
+    pub total_pot_lamports: u64,
+    pub participant_count: u32,
+    pub reason: RoundCloseReason,
 }
 
 /// ## LotteryError Enum
@@ -651,5 +4744,172 @@ pub enum LotteryError {
     ZeroPotAmount,
     #[msg("Winners have not yet been drawn for this round.")]
     WinnersNotYetDrawn,
+    #[msg("Dispute window must not be negative.")]
+    InvalidDisputeWindow,
+    #[msg("Unauthorized: Signer is neither the admin nor the auditor.")]
+    UnauthorizedDisputeAuthority,
+    #[msg("The dispute window for this round has closed.")]
+    DisputeWindowClosed,
+    #[msg("The dispute window for this round is still open. Prizes cannot be distributed yet.")]
+    DisputeWindowOpen,
+    #[msg("This round is disputed and must be resolved first.")]
+    RoundDisputed,
+    #[msg("This round is not disputed.")]
+    RoundNotDisputed,
+    #[msg("This round has been cancelled.")]
+    RoundCancelled,
+    #[msg("This round has not been cancelled.")]
+    RoundNotCancelled,
+    #[msg("The redraw produced the same randomness seed. Retry in a later slot.")]
+    StaleRandomness,
+    #[msg("All participants of this round have already been refunded.")]
+    RefundsComplete,
+    #[msg("Incorrect number of participant accounts provided for the refund.")]
+    IncorrectNumberOfParticipantAccounts,
+    #[msg("Provided participant account does not match the next participant to refund.")]
+    IncorrectParticipantAccount,
+    #[msg("Crank bounty basis points, flat amount or cap exceed the allowed maximum.")]
+    CrankBountyTooHigh,
+    #[msg("The round cannot move from its current status to the requested one.")]
+    InvalidRoundStatusTransition,
+    #[msg("This round has already been migrated to the current account layout.")]
+    RoundAlreadyMigrated,
+    #[msg("The round account does not match any known LotteryRound layout.")]
+    UnrecognizedRoundLayout,
+    #[msg("The legacy round has an inconsistent combination of status flags.")]
+    InconsistentLegacyRound,
+    #[msg("Only rounds whose prizes were distributed or that were cancelled can be closed.")]
+    RoundNotFinished,
+    #[msg("Not all participants of this cancelled round have been refunded yet.")]
+    RefundsIncomplete,
+    #[msg("Provided rent payer does not match the account that paid for the round.")]
+    IncorrectRentPayer,
+    #[msg("Participant index is out of range.")]
+    ParticipantIndexOutOfRange,
+    #[msg("Round schedule is invalid: duration must be positive and cadence at least the duration.")]
+    InvalidRoundSchedule,
+    #[msg("No round schedule is configured.")]
+    RoundScheduleNotSet,
+    #[msg("The previous round has not been paid out, cancelled or closed yet.")]
+    PreviousRoundNotFinished,
+    #[msg("The next scheduled round is not due yet.")]
+    RoundNotDue,
+    #[msg("The rent reserve does not hold enough lamports.")]
+    InsufficientRentReserve,
+    #[msg("Draw calendar is invalid: period must be positive and the offset within the period.")]
+    InvalidDrawCalendar,
+    #[msg("Ticket sales for this round have ended.")]
+    SalesPeriodEnded,
+    #[msg("Sales close time for this round has not been reached yet.")]
+    SalesCloseTimeNotReached,
+    #[msg("Sales for this round must be closed with close_round_sales before the draw.")]
+    SalesNotClosed,
+    #[msg("Sales cutoff is invalid: it must not be negative or end sales before the round starts.")]
+    InvalidSalesCutoff,
+    #[msg("The most recent slot hash predates the close of sales. Retry in a later slot.")]
+    RandomnessPredatesSalesClose,
+    #[msg("Round extension rule is invalid: interval must be positive and the maximum between 1 and MAX_ROUND_EXTENSIONS.")]
+    InvalidRoundExtensionRule,
+    #[msg("Round extensions are not enabled.")]
+    RoundExtensionsDisabled,
+    #[msg("The round has enough participants for a draw and cannot be extended.")]
+    RoundHasEnoughParticipants,
+    #[msg("Max participants must be 0 or between the number of winners and MAX_PARTICIPANTS.")]
+    InvalidMaxParticipants,
+    #[msg("This round is sold out.")]
+    RoundSoldOut,
+    #[msg("Contribution amount must be greater than zero.")]
+    InvalidContributionAmount,
+    #[msg("Contribution memo is longer than MAX_CONTRIBUTION_MEMO_LEN bytes.")]
+    ContributionMemoTooLong,
+    #[msg("This round already has the maximum number of contributors.")]
+    TooManyContributors,
+    #[msg("The treasury does not hold enough uncommitted lamports.")]
+    InsufficientTreasury,
+    #[msg("Unauthorized: Only the platform authority can perform this action.")]
+    UnauthorizedPlatformAuthority,
+    #[msg("Platform bounds are invalid: fee above MAX_PLATFORM_FEE_BPS, empty entry fee range, or no valid split possible.")]
+    InvalidPlatformBounds,
+    #[msg("Entry fee is outside the bounds allowed for this lottery.")]
+    EntryFeeOutOfBounds,
+    #[msg("Payout split is outside the platform's bounds or adds up to more than the whole pot.")]
+    InvalidPayoutSplit,
+    #[msg("Metadata URI is longer than MAX_METADATA_URI_LEN bytes.")]
+    MetadataUriTooLong,
+    #[msg("The platform has not collected enough fees.")]
+    InsufficientPlatformFees,
+    #[msg("Fee beneficiaries are invalid: 1 to MAX_FEE_BENEFICIARIES distinct addresses with non-zero weights adding up to 10,000 bps.")]
+    InvalidFeeBeneficiaries,
+    #[msg("Fee beneficiary accounts do not match the fee split table.")]
+    IncorrectFeeBeneficiaryAccounts,
+    #[msg("The fee vault does not hold enough accrued fees.")]
+    InsufficientFees,
+    #[msg("Fee withdrawal period must not be negative.")]
+    InvalidFeeWithdrawalLimit,
+    #[msg("The withdrawal exceeds what is left of this period's fee withdrawal limit.")]
+    FeeWithdrawalLimitExceeded,
+    #[msg("The dev fee receiver may not be the program or the system program.")]
+    InvalidDevFeeReceiver,
+    #[msg("The fee recipient account must be writable.")]
+    FeeRecipientNotWritable,
+    #[msg("The fee recipient account must not be executable.")]
+    FeeRecipientExecutable,
+    #[msg("The fee recipient account would not be rent-exempt after the payment.")]
+    FeeRecipientNotRentExempt,
+    #[msg("The round's prize vault holds less than its rent-exempt minimum plus the pot it owes.")]
+    RoundInsolvent,
+    #[msg("Provided player profile account is not the player's writable PlayerProfile PDA.")]
+    IncorrectPlayerProfile,
+    #[msg("The payout address may not be the program, the system program, the player profile, an executable account or a sysvar.")]
+    InvalidPayoutAddress,
+    #[msg("The config account does not match the legacy LotteryConfig layout.")]
+    UnrecognizedConfigLayout,
+    #[msg("Only the lottery created by migrate_config can adopt legacy rounds.")]
+    NotLegacyLottery,
+    #[msg("The legacy round account holds less than the pot it owes.")]
+    LegacyRoundInsolvent,
+    #[msg("Provided payout account does not match the payout address.")]
+    IncorrectPayoutAccount,
+    #[msg("This round has already been redrawn once. Cancel it instead.")]
+    RoundAlreadyRedrawn,
+    #[msg("The most recent slot hash predates the flag. Retry in a later slot.")]
+    RandomnessPredatesFlag,
+    #[msg("The fee recipient account must be owned by the System Program.")]
+    FeeRecipientNotSystemOwned,
+}
+
+impl From<CoreError> for LotteryError {
+    fn from(e: CoreError) -> Self {
+        match e {
+            CoreError::NotEnoughParticipants => LotteryError::NotEnoughParticipants,
+            CoreError::WinnerSelectionFailed => LotteryError::WinnerSelectionFailed,
+            CoreError::NumericOverflow => LotteryError::NumericOverflow,
+            CoreError::DistributionExceedsPot => LotteryError::DistributionExceedsPot,
+            CoreError::IndexOutOfRange => LotteryError::ParticipantIndexOutOfRange,
+            CoreError::InvalidCalendar => LotteryError::InvalidDrawCalendar,
+            CoreError::InvalidSplit => LotteryError::InvalidPayoutSplit,
+        }
+    }
+}
+
+/// Keccak-256 over the concatenation of `data`, using the native syscall on-chain.
+/// Passed to the `solana_lottery_core` draw functions.
+pub fn keccak_hashv(data: &[&[u8]]) -> [u8; 32] {
+    keccak::hashv(data).to_bytes()
+}
+
+/// Keccak-256 of the participants' public keys concatenated in entry order, as stored in `RoundResult`.
+pub fn participants_hash(participants: &[Pubkey]) -> [u8; 32] {
+    let slices: Vec<&[u8]> = participants.iter().map(|participant| participant.as_ref()).collect();
+    keccak_hashv(&slices)
+}
+
+/// Merkle root over the participants in entry order, as stored in `participants_root`.
+pub fn participants_merkle_root(participants: &[Pubkey]) -> std::result::Result<[u8; 32], LotteryError> {
+    let mut builder = merkle::MerkleBuilder::new(keccak_hashv);
+    for participant in participants {
+        builder.push_participant(participant.as_ref())?;
+    }
+    Ok(builder.root())
 }
 
@@ -31,13 +31,19 @@ describe("Initialize Lottery Config on Devnet", () => {
   // Entry Fee in Lamports (e.g., 0.1 SOL)
   const entryFeeLamports = new BN(0.1 * LAMPORTS_PER_SOL);
 
+  // ID of the lottery to create; several lotteries can run from the same program
+  const lotteryId = new BN(0);
+
   // PDA for LotteryConfig
   const [lotteryConfigPDA, lotteryConfigBump] = PublicKey.findProgramAddressSync(
-    [Buffer.from("lottery_config")],
+    [Buffer.from("lottery_config"), lotteryId.toBuffer("le", 8)],
     program.programId
   );
   console.log(`LotteryConfig PDA: ${lotteryConfigPDA.toBase58()}`);
 
+  // PDA for the global PlatformConfig; only its authority may initialize lottery configs directly
+  const [platformConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("platform_config")], program.programId);
+
   // Helper function to airdrop SOL if needed (e.g., if using a new keypair for admin)
   async function ensureSol(publicKey: PublicKey, amountSol: number = 1) {
     const balance = await provider.connection.getBalance(publicKey);
@@ -78,10 +84,22 @@ describe("Initialize Lottery Config on Devnet", () => {
     console.log(`  Lottery Config PDA: ${lotteryConfigPDA.toBase58()}`);
 
     try {
+      // On a fresh deployment the admin first becomes the platform authority
+      // (2.5% platform fee on created lotteries, entry fees of 0.01-5 SOL, dev fee up to 20%, main prize at least 30%).
+      if (!(await provider.connection.getAccountInfo(platformConfigPDA))) {
+        console.log(`Initializing PlatformConfig at ${platformConfigPDA.toBase58()}...`);
+        await program.methods
+          .initializePlatform(250, new BN(0.01 * LAMPORTS_PER_SOL), new BN(5 * LAMPORTS_PER_SOL), 2000, 3000)
+          .accounts({ platformConfig: platformConfigPDA, authority: adminKeypair.publicKey, systemProgram: SystemProgram.programId })
+          .signers([adminKeypair])
+          .rpc();
+      }
+
       const txSignature = await program.methods
-        .initializeConfig(developmentFeeReceiverPubKey, entryFeeLamports)
+        .initializeConfig(lotteryId, developmentFeeReceiverPubKey, entryFeeLamports)
         .accounts({
           lotteryConfig: lotteryConfigPDA,
+          platformConfig: platformConfigPDA, // The admin must be the platform authority
           admin: adminKeypair.publicKey, // The admin authority
           systemProgram: SystemProgram.programId,
         })
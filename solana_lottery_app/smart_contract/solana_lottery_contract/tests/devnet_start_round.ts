@@ -18,13 +18,22 @@ describe("Start New Lottery Round on Devnet", () => {
   const adminKeypair = (provider.wallet as any).payer; // Assuming provider.wallet is a NodeWallet with a payer
   console.log(`Admin public key: ${adminKeypair.publicKey.toBase58()}`);
 
+  // ID of the lottery to start a round in
+  const lotteryId = new BN(0);
+
   // PDA for LotteryConfig
   const [lotteryConfigPDA, lotteryConfigBump] = PublicKey.findProgramAddressSync(
-    [Buffer.from("lottery_config")],
+    [Buffer.from("lottery_config"), lotteryId.toBuffer("le", 8)],
     program.programId
   );
   console.log(`LotteryConfig PDA: ${lotteryConfigPDA.toBase58()}`);
 
+  // PDA for the Treasury backing guaranteed main prizes
+  const [treasuryPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury"), lotteryConfigPDA.toBuffer()],
+    program.programId
+  );
+
   // Define the ID for the new lottery round (should be 1 if first round after init)
   // This will be determined by fetching the current_lottery_round_id from config and incrementing it.
   // For this script, we'll assume we are starting round 1.
@@ -32,11 +41,23 @@ describe("Start New Lottery Round on Devnet", () => {
 
   // PDA for the new LotteryRound
   const [lotteryRoundPDA, lotteryRoundBump] = PublicKey.findProgramAddressSync(
-    [Buffer.from("lottery_round"), newLotteryRoundId.toBuffer("le", 8)],
+    [Buffer.from("lottery_round"), lotteryConfigPDA.toBuffer(), newLotteryRoundId.toBuffer("le", 8)],
     program.programId
   );
   console.log(`LotteryRound PDA for round ${newLotteryRoundId.toString()}: ${lotteryRoundPDA.toBase58()}`);
 
+  // PDA of the previous round (round 0 never exists), which must be paid out, cancelled or closed first
+  const [previousRoundPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from("lottery_round"), lotteryConfigPDA.toBuffer(), newLotteryRoundId.subn(1).toBuffer("le", 8)],
+    program.programId
+  );
+
+  // PDA of the round's prize vault, which holds its pot apart from the round data
+  const [prizeVaultPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from("prize_vault"), lotteryRoundPDA.toBuffer()],
+    program.programId
+  );
+
   // Define the end time for the lottery round (e.g., 7 days from now)
   const sevenDaysInSeconds = 7 * 24 * 60 * 60;
   const drawTimestampOffsetSeconds = new BN(sevenDaysInSeconds);
@@ -66,7 +87,10 @@ describe("Start New Lottery Round on Devnet", () => {
         .startNewLotteryRound(drawTimestampOffsetSeconds)
         .accounts({
           lotteryConfig: lotteryConfigPDA,
+          treasury: treasuryPDA,
+          previousRound: previousRoundPDA,
           lotteryRound: lotteryRoundPDA,
+          prizeVault: prizeVaultPDA,
           admin: adminKeypair.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -81,7 +105,7 @@ describe("Start New Lottery Round on Devnet", () => {
       const roundAccount = await program.account.lotteryRound.fetch(lotteryRoundPDA);
       console.log("Fetched LotteryRound Data:");
       console.log(`  Round ID: ${roundAccount.roundId.toString()}`);
-      console.log(`  Is Active: ${roundAccount.isActive}`);
+      console.log(`  Status: ${Object.keys(roundAccount.status)[0]}`);
       console.log(`  Draw Timestamp: ${new Date(roundAccount.drawTimestamp.toNumber() * 1000).toISOString()}`);
       console.log(`  Total Pot (Lamports): ${roundAccount.totalPotLamports.toString()}`);
       console.log(`  Participant Count: ${roundAccount.participants.length}`);
@@ -95,8 +119,8 @@ describe("Start New Lottery Round on Devnet", () => {
       if (!roundAccount.roundId.eq(newLotteryRoundId)) {
         throw new Error("Round ID mismatch in LotteryRound account!");
       }
-      if (!roundAccount.isActive) {
-        throw new Error("LotteryRound should be active!");
+      if (!("open" in roundAccount.status)) {
+        throw new Error("LotteryRound should be open!");
       }
       if (!configAccountAfter.currentLotteryRoundId.eq(newLotteryRoundId)) {
         throw new Error("CurrentLotteryRoundId mismatch in LotteryConfig account!");
@@ -41,27 +41,44 @@ async function main() {
   // Use PublicKey from @solana/web3.js
   const developmentFeeReceiver = new PublicKey("Ge3nRFoScsPY9RvyLEFNHJbxDbgvFk3nuaD78zbXahEn"); 
   const entryFeeLamports = new anchor.BN(100000000); // 0.1 SOL
+  const lotteryId = new anchor.BN(0); // The ID of the lottery to create.
 
   // Use PublicKey from @solana/web3.js for findProgramAddressSync
   const [lotteryConfigPDA, _lotteryConfigBump] = 
     PublicKey.findProgramAddressSync(
-      [Buffer.from("lottery_config")], // Seed for the PDA as per IDL
+      [Buffer.from("lottery_config"), lotteryId.toBuffer("le", 8)], // Seeds for the PDA: b"lottery_config" and the lottery ID
       program.programId
     );
 
+  // The global PlatformConfig; only its authority may initialize lottery configs directly.
+  const [platformConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("platform_config")], program.programId);
+
   console.log("Attempting to initialize LotteryConfig with PDA:", lotteryConfigPDA.toBase58());
   console.log("Admin being used (newly generated for this script):", adminKeypair.publicKey.toBase58());
   console.log("Development Fee Receiver:", developmentFeeReceiver.toBase58());
   console.log("Entry Fee (Lamports):", entryFeeLamports.toString());
 
   try {
+    // On a fresh deployment the new admin first becomes the platform authority. Otherwise initializeConfig below
+    // fails unless this admin already is the platform authority.
+    if (!(await provider.connection.getAccountInfo(platformConfigPDA))) {
+      await program.methods
+        .initializePlatform(250, new anchor.BN(0.01 * LAMPORTS_PER_SOL), new anchor.BN(5 * LAMPORTS_PER_SOL), 2000, 3000)
+        .accounts({ platformConfig: platformConfigPDA, authority: adminKeypair.publicKey, systemProgram: SystemProgram.programId })
+        .signers([adminKeypair])
+        .rpc();
+      console.log("PlatformConfig initialized with PDA:", platformConfigPDA.toBase58());
+    }
+
     const tx = await program.methods
       .initializeConfig(
+        lotteryId,
         developmentFeeReceiver, // web3.js PublicKey
         entryFeeLamports
       )
       .accounts({
         lotteryConfig: lotteryConfigPDA,        // web3.js PublicKey
+        platformConfig: platformConfigPDA,      // web3.js PublicKey
         admin: adminKeypair.publicKey,          // web3.js PublicKey
         systemProgram: SystemProgram.programId, // web3.js PublicKey (from import)
       })
@@ -2,7 +2,8 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, web3 } from "@coral-xyz/anchor";
 import { SolanaLotteryContract } from "../target/types/solana_lottery_contract"; // Anchor generated types for the contract
 import { assert, expect } from "chai"; // Assertion library for tests
-import { Keypair, SystemProgram, LAMPORTS_PER_SOL, PublicKey, SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
+import { Keypair, SystemProgram, LAMPORTS_PER_SOL, PublicKey, SYSVAR_CLOCK_PUBKEY, SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
+import * as fs from "fs";
 
 // Test suite for the Solana Lottery Contract
 describe("solana_lottery_contract", () => {
@@ -26,12 +27,22 @@ describe("solana_lottery_contract", () => {
   const participant6 = Keypair.generate(); // Additional participant for specific tests.
 
   // --- PDAs and Bumps ---
+  // The ID of the lottery most tests run against. Other lotteries can run side by side in the same program.
+  const lotteryId = new anchor.BN(0);
   // Derive the Program Derived Address (PDA) for the LotteryConfig account.
   // The seeds must match those defined in the smart contract.
   const [lotteryConfigPDA, lotteryConfigBump] = PublicKey.findProgramAddressSync(
-    [Buffer.from("lottery_config")], // Seed: b"lottery_config"
+    [Buffer.from("lottery_config"), lotteryId.toBuffer("le", 8)], // Seeds: b"lottery_config" and the lottery ID
     program.programId
   );
+  // PDA for the treasury that backs guaranteed main prizes.
+  const [treasuryPDA] = PublicKey.findProgramAddressSync([Buffer.from("treasury"), lotteryConfigPDA.toBuffer()], program.programId);
+  // PDA of the global platform config that bounds created lotteries and collects the platform fee.
+  const [platformConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("platform_config")], program.programId);
+  // PDA of the vault in which the dev fees accrue until they are withdrawn.
+  const [feeVaultPDA] = PublicKey.findProgramAddressSync([Buffer.from("fee_vault"), lotteryConfigPDA.toBuffer()], program.programId);
+  // PDA of the lottery's lifetime statistics.
+  const [lotteryStatsPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_stats"), lotteryConfigPDA.toBuffer()], program.programId);
 
   // Variables to store the current lottery round ID and its PDA details.
   // These will be updated as new rounds are started in the tests.
@@ -42,15 +53,55 @@ describe("solana_lottery_contract", () => {
   /**
    * Helper function to derive the PDA for a LotteryRound account given its ID.
    * @param {anchor.BN} roundId - The ID of the lottery round.
+   * @param {PublicKey} [configPDA=lotteryConfigPDA] - The LotteryConfig PDA of the lottery the round belongs to.
    * @returns {Promise<[PublicKey, number]>} A promise that resolves to the PDA and its bump seed.
    */
-  async function findLotteryRoundPDA(roundId: anchor.BN): Promise<[PublicKey, number]> {
+  async function findLotteryRoundPDA(roundId: anchor.BN, configPDA: PublicKey = lotteryConfigPDA): Promise<[PublicKey, number]> {
     return PublicKey.findProgramAddressSync(
-      [Buffer.from("lottery_round"), roundId.toBuffer("le", 8)], // Seeds: b"lottery_round" and roundId (as 8-byte little-endian buffer)
+      [Buffer.from("lottery_round"), configPDA.toBuffer(), roundId.toBuffer("le", 8)], // Seeds: b"lottery_round", the config PDA and roundId (as 8-byte little-endian buffer)
       program.programId
     );
   }
 
+  /**
+   * Helper function to derive the prize vault PDA that holds the pot of a LotteryRound.
+   * @param {PublicKey} roundPDA - The LotteryRound PDA the vault belongs to.
+   * @returns {PublicKey} The prize vault PDA.
+   */
+  function findPrizeVaultPDA(roundPDA: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync([Buffer.from("prize_vault"), roundPDA.toBuffer()], program.programId)[0]; // Seeds: b"prize_vault" and the round PDA
+  }
+
+  /**
+   * Helper function to derive the PDA of the round before a LotteryRound, which must be finished before it can start.
+   * @param {anchor.BN} roundId - The ID of the lottery round to start.
+   * @param {PublicKey} [configPDA=lotteryConfigPDA] - The LotteryConfig PDA of the lottery the round belongs to.
+   * @returns {PublicKey} The LotteryRound PDA of the previous round ID.
+   */
+  function findPreviousRoundPDA(roundId: anchor.BN, configPDA: PublicKey = lotteryConfigPDA): PublicKey {
+    return PublicKey.findProgramAddressSync([Buffer.from("lottery_round"), configPDA.toBuffer(), roundId.subn(1).toBuffer("le", 8)], program.programId)[0];
+  }
+
+  /**
+   * Helper function to derive the PlayerProfile PDA of a wallet.
+   * @param {PublicKey} player - The wallet the profile belongs to.
+   * @returns {PublicKey} The player profile PDA.
+   */
+  function findPlayerProfilePDA(player: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync([Buffer.from("player_profile"), player.toBuffer()], program.programId)[0]; // Seeds: b"player_profile" and the wallet
+  }
+
+  /**
+   * Helper function to build the `remainingAccounts` of `distributePrizes`: the winners' payout accounts in the order
+   * Main Winner, Other Winner 1, ..., Other Winner 4, followed by their PlayerProfile PDAs and then their wallets in the
+   * same order. A winner is paid at their wallet if their payout address cannot receive the prize.
+   * @param {PublicKey[]} winners - The winners, main winner first.
+   * @param {PublicKey[]} [payoutAddresses=winners] - Where each winner is paid, if not at their wallet.
+   */
+  function prizeRecipientAccounts(winners: PublicKey[], payoutAddresses: PublicKey[] = winners) {
+    return [...payoutAddresses, ...winners.map(findPlayerProfilePDA), ...winners].map(pubkey => ({ pubkey, isSigner: false, isWritable: true }));
+  }
+
   /**
    * Helper function to airdrop SOL to a specified public key.
    * This is necessary to fund accounts for transaction fees and rent.
@@ -70,6 +121,40 @@ describe("solana_lottery_contract", () => {
     console.log(`Airdrop successful. Signature: ${signature}`);
   }
 
+  /**
+   * Helper function to decode the events a confirmed transaction emitted, from the "Program data:" lines of its logs.
+   * @param {string} signature - The signature returned by `rpc()`.
+   * @returns {Promise<{ name: string; data: any }[]>} The decoded events, in the order they were emitted.
+   */
+  async function transactionEvents(signature: string) {
+    const tx = await provider.connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    return tx!.meta!.logMessages!
+      .filter(log => log.startsWith("Program data: "))
+      .map(log => program.coder.events.decode(log.slice("Program data: ".length)))
+      .filter(event => event !== null);
+  }
+
+  /**
+   * Helper function to cancel an under-subscribed round of the main lottery (fewer than 5 participants) by running it
+   * out of extensions, so that the next round can start. Waits for the round's draw time to pass first.
+   * @param {PublicKey} roundPDA - The LotteryRound PDA of the round to cancel.
+   */
+  async function cancelUnderSubscribedRound(roundPDA: PublicKey) {
+    const waitForDrawTime = async () => {
+      const { drawTimestamp } = await program.account.lotteryRound.fetch(roundPDA);
+      const secondsLeft = Math.max(drawTimestamp.toNumber() - Math.floor(Date.now() / 1000), 0);
+      await new Promise(resolve => setTimeout(resolve, (secondsLeft + 1) * 1000));
+    };
+    const extendRound = () => program.methods.extendRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: roundPDA }).rpc();
+    await program.methods.setRoundExtensions(new anchor.BN(1), 1).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await waitForDrawTime();
+    await extendRound(); // Reopens the round for one more second.
+    await waitForDrawTime();
+    await extendRound(); // Out of extensions, so the round is cancelled.
+    await program.methods.setRoundExtensions(new anchor.BN(0), 0).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    assert.deepEqual((await program.account.lotteryRound.fetch(roundPDA)).status, { cancelled: {} }, "Round should be cancelled");
+  }
+
   // --- Before Hook ---
   // This block runs once before any tests in the suite.
   // It airdrops SOL to all necessary test accounts.
@@ -92,11 +177,19 @@ describe("solana_lottery_contract", () => {
     console.log("Test: Initialize Lottery Config");
     const entryFeeLamports = new anchor.BN(0.1 * LAMPORTS_PER_SOL); // Set entry fee to 0.1 SOL.
 
+    // The admin also runs the platform: 2.5% platform fee, entry fees of 0.01-5 SOL, dev fee up to 20%, main prize at least 30%.
+    await program.methods
+      .initializePlatform(250, new anchor.BN(0.01 * LAMPORTS_PER_SOL), new anchor.BN(5 * LAMPORTS_PER_SOL), 2000, 3000)
+      .accounts({ platformConfig: platformConfigPDA, authority: admin.publicKey, systemProgram: SystemProgram.programId })
+      .signers([admin])
+      .rpc();
+
     // Call the `initializeConfig` instruction.
     const tx = await program.methods
-      .initializeConfig(devFeeReceiver.publicKey, entryFeeLamports)
+      .initializeConfig(lotteryId, devFeeReceiver.publicKey, entryFeeLamports)
       .accounts({
         lotteryConfig: lotteryConfigPDA,
+        platformConfig: platformConfigPDA,
         admin: admin.publicKey,
         systemProgram: SystemProgram.programId, // System program is required for account creation.
       })
@@ -115,6 +208,33 @@ describe("solana_lottery_contract", () => {
     assert.ok(configAccount.currentLotteryRoundId.eqn(0), "Initial round ID should be 0");
     assert.strictEqual(configAccount.bump, lotteryConfigBump, "Stored bump does not match derived bump");
     console.log("LotteryConfig initialized successfully.");
+
+    // Create the treasury PDA; rounds reference it even when no guarantee is configured.
+    await program.methods
+      .initializeTreasury()
+      .accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId })
+      .signers([admin])
+      .rpc();
+    const treasuryAccount = await program.account.treasury.fetch(treasuryPDA);
+    assert.ok(treasuryAccount.committedLamports.eqn(0), "A new treasury should have no commitments");
+
+    // Create the fee vault in which distributed dev fees accrue.
+    await program.methods
+      .initializeFeeVault()
+      .accounts({ lotteryConfig: lotteryConfigPDA, feeVault: feeVaultPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId })
+      .signers([admin])
+      .rpc();
+    const feeVaultAccount = await program.account.feeVault.fetch(feeVaultPDA);
+    assert.ok(feeVaultAccount.totalAccruedLamports.eqn(0), "A new fee vault should hold no fees");
+
+    // Create the statistics account that ticket sales, draws and distributions update.
+    await program.methods
+      .initializeLotteryStats()
+      .accounts({ lotteryConfig: lotteryConfigPDA, lotteryStats: lotteryStatsPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId })
+      .signers([admin])
+      .rpc();
+    const lotteryStatsAccount = await program.account.lotteryStats.fetch(lotteryStatsPDA);
+    assert.ok(lotteryStatsAccount.totalTicketsSold.eqn(0), "New lottery stats should count no tickets");
   });
 
   it("Updates config!", async () => {
@@ -154,16 +274,19 @@ describe("solana_lottery_contract", () => {
     console.log("Test: Start New Lottery Round");
     currentLotteryRoundId = new anchor.BN(1); // Expecting the first round to have ID 1.
     [lotteryRoundPDA, lotteryRoundBump] = await findLotteryRoundPDA(currentLotteryRoundId);
-    const drawTimestampOffsetSeconds = new anchor.BN(3600); // Set draw time to 1 hour from now.
+    const drawTimestampOffsetSeconds = new anchor.BN(10); // Set draw time to 10 seconds from now, so that round 1 can be paid out before round 2.
 
     // Call `startNewLotteryRound`.
     // Note: The `next_round_id_arg` is now part of the method signature in the Rust code.
     // It must match the `currentLotteryRoundId` being initiated.
-    await program.methods
+    const startSignature = await program.methods
       .startNewLotteryRound(drawTimestampOffsetSeconds, currentLotteryRoundId) 
       .accounts({
         lotteryConfig: lotteryConfigPDA,
+        treasury: treasuryPDA,
+        previousRound: findPreviousRoundPDA(currentLotteryRoundId),
         lotteryRound: lotteryRoundPDA,
+        prizeVault: findPrizeVaultPDA(lotteryRoundPDA),
         admin: admin.publicKey,
         systemProgram: SystemProgram.programId,
       })
@@ -177,11 +300,18 @@ describe("solana_lottery_contract", () => {
     // Verify the state of the new round and the updated config.
     assert.ok(roundAccount.roundId.eq(currentLotteryRoundId), "Round ID mismatch");
     assert.ok(configAccount.currentLotteryRoundId.eq(currentLotteryRoundId), "Config current round ID mismatch");
-    assert.isTrue(roundAccount.isActive, "Round should be active");
-    assert.isFalse(roundAccount.winnersDrawn, "Winners should not be drawn yet");
-    assert.isFalse(roundAccount.prizesDistributed, "Prizes should not be distributed yet");
+    assert.deepEqual(roundAccount.status, { open: {} }, "Round should be open");
     assert.ok(roundAccount.totalPotLamports.eqn(0), "Initial pot should be 0");
     assert.lengthOf(roundAccount.participants, 0, "Initial participants list should be empty");
+
+    // `RoundStarted` should describe the round that was created.
+    const [roundStarted] = (await transactionEvents(startSignature)).filter(event => event.name === "roundStarted");
+    assert.ok(roundStarted, "RoundStarted event was not emitted");
+    assert.ok(roundStarted.data.lotteryConfig.equals(lotteryConfigPDA), "RoundStarted lottery config mismatch");
+    assert.ok(roundStarted.data.roundId.eq(currentLotteryRoundId), "RoundStarted round ID mismatch");
+    assert.ok(roundStarted.data.startTimestamp.eq(roundAccount.startTimestamp), "RoundStarted start timestamp mismatch");
+    assert.ok(roundStarted.data.drawTimestamp.eq(roundAccount.drawTimestamp), "RoundStarted draw timestamp mismatch");
+    assert.ok(roundStarted.data.entryFeeLamports.eq(configAccount.entryFeeLamports), "RoundStarted entry fee mismatch");
     console.log("New lottery round state verified.");
   });
 
@@ -198,17 +328,31 @@ describe("solana_lottery_contract", () => {
 
     for (const p of participantsToEnter) {
       console.log(`Participant ${p.publicKey.toBase58()} entering...`);
-      await program.methods
+      const enterSignature = await program.methods
         .enterLottery()
         .accounts({
           lotteryConfig: lotteryConfigPDA,
           lotteryRound: lotteryRoundPDA, // Use the PDA for the current round (Round 1)
+          prizeVault: findPrizeVaultPDA(lotteryRoundPDA),
+          lotteryStats: lotteryStatsPDA,
+          lotteryStats: lotteryStatsPDA,
           participant: p.publicKey,
+          playerProfile: findPlayerProfilePDA(p.publicKey),
           systemProgram: SystemProgram.programId,
         })
         .signers([p])
         .rpc();
       expectedPot = expectedPot.add(entryFee);
+
+      // `TicketPurchased` should name the buyer and carry the pot after the entry.
+      const [ticketPurchased] = (await transactionEvents(enterSignature)).filter(event => event.name === "ticketPurchased");
+      assert.ok(ticketPurchased, "TicketPurchased event was not emitted");
+      assert.ok(ticketPurchased.data.roundId.eq(currentLotteryRoundId), "TicketPurchased round ID mismatch");
+      assert.ok(ticketPurchased.data.buyer.equals(p.publicKey), "TicketPurchased buyer mismatch");
+      assert.equal(ticketPurchased.data.count, 1, "TicketPurchased count mismatch");
+      assert.ok(ticketPurchased.data.priceLamports.eq(entryFee), "TicketPurchased price mismatch");
+      assert.ok(ticketPurchased.data.potAfterLamports.eq(expectedPot), "TicketPurchased pot after mismatch");
+      assert.isFalse(ticketPurchased.data.soldOut, "TicketPurchased should not report a sold-out round");
     }
     console.log("All 5 participants entered Round 1.");
 
@@ -228,7 +372,10 @@ describe("solana_lottery_contract", () => {
         .accounts({
           lotteryConfig: lotteryConfigPDA,
           lotteryRound: lotteryRoundPDA,
+          prizeVault: findPrizeVaultPDA(lotteryRoundPDA),
+          lotteryStats: lotteryStatsPDA,
           participant: participant1.publicKey, // participant1 tries to enter again
+          playerProfile: findPlayerProfilePDA(participant1.publicKey),
           systemProgram: SystemProgram.programId,
         })
         .signers([participant1])
@@ -255,17 +402,40 @@ describe("solana_lottery_contract", () => {
     const [shortDrawLotteryRoundPDA, shortDrawLotteryRoundBump] = await findLotteryRoundPDA(currentLotteryRoundId);
     const shortDrawOffsetSeconds = new anchor.BN(2); // Set draw time to 2 seconds from now.
 
-    console.log(`Starting Round ${currentLotteryRoundId} with a ${shortDrawOffsetSeconds}s draw offset.`);
-    await program.methods
+    // Round 1 has not been paid out yet, so round 2 cannot start.
+    const startShortDrawRound = () => program.methods
       .startNewLotteryRound(shortDrawOffsetSeconds, currentLotteryRoundId)
       .accounts({ 
         lotteryConfig: lotteryConfigPDA, 
+        treasury: treasuryPDA,
+        previousRound: findPreviousRoundPDA(currentLotteryRoundId),
         lotteryRound: shortDrawLotteryRoundPDA, 
+        prizeVault: findPrizeVaultPDA(shortDrawLotteryRoundPDA),
         admin: admin.publicKey, 
         systemProgram: SystemProgram.programId 
       })
       .signers([admin])
       .rpc();
+    try {
+      await startShortDrawRound();
+      assert.fail("Should not have been able to start a round before the previous one was paid out");
+    } catch (err) {
+      assert.include(err.toString(), "PreviousRoundNotFinished", "Error message mismatch for PreviousRoundNotFinished");
+    }
+
+    // Draw round 1 and pay it out.
+    const round1 = await program.account.lotteryRound.fetch(lotteryRoundPDA);
+    const secondsUntilRound1Draw = Math.max(round1.drawTimestamp.toNumber() - Math.floor(Date.now() / 1000), 0);
+    await new Promise(resolve => setTimeout(resolve, (secondsUntilRound1Draw + 1) * 1000)); // Wait for round 1's draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: lotteryRoundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: lotteryRoundPDA, prizeVault: findPrizeVaultPDA(lotteryRoundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    const drawnRound1 = await program.account.lotteryRound.fetch(lotteryRoundPDA);
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: lotteryRoundPDA, prizeVault: findPrizeVaultPDA(lotteryRoundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(prizeRecipientAccounts([drawnRound1.mainWinner!, ...drawnRound1.otherWinners])).rpc();
+    assert.deepEqual((await program.account.lotteryRound.fetch(lotteryRoundPDA)).status, { distributed: {} }, "Round 1 should be distributed");
+
+    console.log(`Starting Round ${currentLotteryRoundId} with a ${shortDrawOffsetSeconds}s draw offset.`);
+    await startShortDrawRound();
     
     // Have 5 participants enter this new short round.
     const participantsForShortDraw = [participant1, participant2, participant3, participant4, participant5]; // Re-using keypairs for simplicity
@@ -276,7 +446,10 @@ describe("solana_lottery_contract", () => {
         .accounts({ 
           lotteryConfig: lotteryConfigPDA, 
           lotteryRound: shortDrawLotteryRoundPDA, 
+          prizeVault: findPrizeVaultPDA(shortDrawLotteryRoundPDA),
+          lotteryStats: lotteryStatsPDA,
           participant: p.publicKey, 
+          playerProfile: findPlayerProfilePDA(p.publicKey),
           systemProgram: SystemProgram.programId 
         })
         .signers([p])
@@ -287,24 +460,39 @@ describe("solana_lottery_contract", () => {
     console.log(`Waiting for ${shortDrawOffsetSeconds.toNumber() + 1} seconds for draw time to pass...`);
     await new Promise(resolve => setTimeout(resolve, (shortDrawOffsetSeconds.toNumber() + 1) * 1000)); // Wait for draw time to pass.
 
+    // The draw needs randomness from after the close of sales, so sales must be closed in an earlier transaction.
+    try {
+      await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: shortDrawLotteryRoundPDA, prizeVault: findPrizeVaultPDA(shortDrawLotteryRoundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+      assert.fail("Should not have been able to conduct the draw before sales were closed");
+    } catch (err) {
+      assert.include(err.toString(), "SalesNotClosed", "Error message mismatch for SalesNotClosed");
+    }
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: shortDrawLotteryRoundPDA }).rpc();
+    const roundAfterSalesClose = await program.account.lotteryRound.fetch(shortDrawLotteryRoundPDA);
+    assert.deepEqual(roundAfterSalesClose.status, { salesClosed: {} }, "Sales should be closed");
+    assert.ok(roundAfterSalesClose.salesClosedSlot.gtn(0), "Sales close slot should be recorded");
+
     console.log("Attempting to conduct draw...");
-    await program.methods
+    const drawSignature = await program.methods
       .conductDraw()
       .accounts({
         lotteryConfig: lotteryConfigPDA,
         lotteryRound: shortDrawLotteryRoundPDA,
+        prizeVault: findPrizeVaultPDA(shortDrawLotteryRoundPDA),
+        lotteryStats: lotteryStatsPDA,
         slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, // SlotHashes sysvar is required for randomness.
+        keeper: provider.wallet.publicKey, // The provider wallet runs the draw and receives any crank bounty.
       })
       .rpc();
     console.log("ConductDraw transaction successful.");
 
     const roundAccountAfterDraw = await program.account.lotteryRound.fetch(shortDrawLotteryRoundPDA);
     // Verify state after draw.
-    assert.isTrue(roundAccountAfterDraw.winnersDrawn, "Winners should be marked as drawn");
-    assert.isFalse(roundAccountAfterDraw.isActive, "Round should be inactive after draw");
+    assert.deepEqual(roundAccountAfterDraw.status, { drawn: {} }, "Round should be drawn");
     assert.isNotNull(roundAccountAfterDraw.mainWinner, "Main winner should be set");
     assert.lengthOf(roundAccountAfterDraw.otherWinners, 4, "Should be 4 other winners");
     assert.isNotNull(roundAccountAfterDraw.randomnessSeed, "Randomness seed should be set");
+    assert.isTrue(roundAccountAfterDraw.participantsRoot.some(byte => byte !== 0), "Participants root should be committed when sales close");
 
     // Ensure winners are from the participant list and are unique.
     const allWinners = [roundAccountAfterDraw.mainWinner!, ...roundAccountAfterDraw.otherWinners]; // Use non-null assertion as we checked isNotNull
@@ -314,6 +502,17 @@ describe("solana_lottery_contract", () => {
     });
     const uniqueWinners = new Set(allWinners.map(w => w.toBase58()));
     assert.equal(uniqueWinners.size, 5, "Winners are not unique");
+
+    // `DrawConducted` should carry the seed and the winners recorded on the round.
+    const [drawConducted] = (await transactionEvents(drawSignature)).filter(event => event.name === "drawConducted");
+    assert.ok(drawConducted, "DrawConducted event was not emitted");
+    assert.ok(drawConducted.data.roundId.eq(currentLotteryRoundId), "DrawConducted round ID mismatch");
+    assert.deepEqual(drawConducted.data.randomnessSeed, roundAccountAfterDraw.randomnessSeed, "DrawConducted randomness seed mismatch");
+    assert.equal(drawConducted.data.participantCount, participantsForShortDraw.length, "DrawConducted participant count mismatch");
+    assert.ok(drawConducted.data.mainWinner.equals(roundAccountAfterDraw.mainWinner!), "DrawConducted main winner mismatch");
+    assert.deepEqual(drawConducted.data.otherWinners.map(w => w.toBase58()), roundAccountAfterDraw.otherWinners.map(w => w.toBase58()), "DrawConducted other winners mismatch");
+    assert.ok(drawConducted.data.disputeDeadline.eq(roundAccountAfterDraw.disputeDeadline), "DrawConducted dispute deadline mismatch");
+    assert.isFalse(drawConducted.data.isRedraw, "DrawConducted should not be a redraw");
     console.log("Draw conducted and winners verified.");
   });
 
@@ -326,40 +525,41 @@ describe("solana_lottery_contract", () => {
     const roundAccount = await program.account.lotteryRound.fetch(prizeRoundPDA);
     const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
     
-    assert.isTrue(roundAccount.winnersDrawn, "Winners must be drawn before distributing prizes");
-    assert.isFalse(roundAccount.prizesDistributed, "Prizes should not be distributed yet");
+    assert.deepEqual(roundAccount.status, { drawn: {} }, "Winners must be drawn before distributing prizes");
 
     const mainWinner = roundAccount.mainWinner!;
     const otherWinners = roundAccount.otherWinners;
 
     // Get initial balances to verify distribution amounts.
-    const devFeeReceiverInitialBalance = await provider.connection.getBalance(devFeeReceiver.publicKey);
+    const feeVaultInitialBalance = await provider.connection.getBalance(feeVaultPDA);
     const mainWinnerInitialBalance = await provider.connection.getBalance(mainWinner);
     const otherWinnerInitialBalances = await Promise.all(otherWinners.map(ow => provider.connection.getBalance(ow)));
-    const lotteryRoundInitialBalance = await provider.connection.getBalance(prizeRoundPDA);
-    console.log(`Initial balances: DevFeeReceiver=${devFeeReceiverInitialBalance}, MainWinner=${mainWinnerInitialBalance}, LotteryRoundPDA=${lotteryRoundInitialBalance}`);
+    const prizeVaultInitialBalance = await provider.connection.getBalance(findPrizeVaultPDA(prizeRoundPDA));
+    console.log(`Initial balances: FeeVault=${feeVaultInitialBalance}, MainWinner=${mainWinnerInitialBalance}, PrizeVault=${prizeVaultInitialBalance}`);
 
     // Prepare `remainingAccounts` for the `distributePrizes` instruction.
-    // These must be in the order: Main Winner, Other Winner 1, ..., Other Winner 4.
-    const prizeWinnerAccounts = [
-        { pubkey: mainWinner, isSigner: false, isWritable: true },
-        ...otherWinners.map(ow => ({ pubkey: ow, isSigner: false, isWritable: true }))
-    ];
+    // These must be in the order: Main Winner, Other Winner 1, ..., Other Winner 4, followed by the winners' profiles.
+    const prizeWinnerAccounts = prizeRecipientAccounts([mainWinner, ...otherWinners]);
 
     console.log("Attempting to distribute prizes...");
-    await program.methods
+    const distributeSignature = await program.methods
         .distributePrizes()
         .accounts({
             lotteryConfig: lotteryConfigPDA,
+            treasury: treasuryPDA,
+            platformConfig: platformConfigPDA,
             lotteryRound: prizeRoundPDA,
-            devFeeReceiver: devFeeReceiver.publicKey, 
+            prizeVault: findPrizeVaultPDA(prizeRoundPDA),
+            lotteryStats: lotteryStatsPDA,
+            feeVault: feeVaultPDA, // The dev fee accrues in the fee vault.
+            keeper: provider.wallet.publicKey, // The provider wallet runs the distribution and receives any crank bounty.
         })
-        .remainingAccounts(prizeWinnerAccounts) // Pass winner accounts.
+        .remainingAccounts(prizeWinnerAccounts) // Pass winner and winner profile accounts.
         .rpc();
     console.log("DistributePrizes transaction successful.");
 
     const roundAccountAfterDistro = await program.account.lotteryRound.fetch(prizeRoundPDA);
-    assert.isTrue(roundAccountAfterDistro.prizesDistributed, "Prizes should be marked as distributed");
+    assert.deepEqual(roundAccountAfterDistro.status, { distributed: {} }, "Round should be distributed");
 
     // Calculate expected shares.
     const totalPot = roundAccount.totalPotLamports;
@@ -368,15 +568,17 @@ describe("solana_lottery_contract", () => {
     const otherWinnerShare = totalPot.mul(new anchor.BN(10)).div(new anchor.BN(100));
 
     // Verify final balances.
-    const devFeeReceiverFinalBalance = await provider.connection.getBalance(devFeeReceiver.publicKey);
+    const feeVaultFinalBalance = await provider.connection.getBalance(feeVaultPDA);
     const mainWinnerFinalBalance = await provider.connection.getBalance(mainWinner);
     const otherWinnerFinalBalances = await Promise.all(otherWinners.map(ow => provider.connection.getBalance(ow)));
-    const lotteryRoundFinalBalance = await provider.connection.getBalance(prizeRoundPDA);
-    console.log(`Final balances: DevFeeReceiver=${devFeeReceiverFinalBalance}, MainWinner=${mainWinnerFinalBalance}, LotteryRoundPDA=${lotteryRoundFinalBalance}`);
+    const prizeVaultFinalBalance = await provider.connection.getBalance(findPrizeVaultPDA(prizeRoundPDA));
+    console.log(`Final balances: FeeVault=${feeVaultFinalBalance}, MainWinner=${mainWinnerFinalBalance}, PrizeVault=${prizeVaultFinalBalance}`);
 
 
-    assert.ok(new anchor.BN(devFeeReceiverFinalBalance).eq(new anchor.BN(devFeeReceiverInitialBalance).add(devShare)), 
-        `Dev fee incorrect. Expected: ${new anchor.BN(devFeeReceiverInitialBalance).add(devShare)}, Got: ${devFeeReceiverFinalBalance}`);
+    assert.ok(new anchor.BN(feeVaultFinalBalance).eq(new anchor.BN(feeVaultInitialBalance).add(devShare)), 
+        `Dev fee incorrect. Expected: ${new anchor.BN(feeVaultInitialBalance).add(devShare)}, Got: ${feeVaultFinalBalance}`);
+    const feeVaultAccount = await program.account.feeVault.fetch(feeVaultPDA);
+    assert.ok(feeVaultAccount.totalAccruedLamports.eq(devShare), "The fee vault should record the accrued dev fee");
     assert.ok(new anchor.BN(mainWinnerFinalBalance).eq(new anchor.BN(mainWinnerInitialBalance).add(mainWinnerShare)), 
         `Main winner share incorrect. Expected: ${new anchor.BN(mainWinnerInitialBalance).add(mainWinnerShare)}, Got: ${mainWinnerFinalBalance}`);
     otherWinners.forEach((ow, index) => {
@@ -384,14 +586,36 @@ describe("solana_lottery_contract", () => {
             `Other winner ${index+1} (${ow.toBase58()}) share incorrect. Expected: ${new anchor.BN(otherWinnerInitialBalances[index]).add(otherWinnerShare)}, Got: ${otherWinnerFinalBalances[index]}`);
     });
     
-    // The LotteryRound PDA should have its lamports reduced by the total distributed amount.
-    // It will still hold rent-exempt minimum if it was created with `init`.
-    // A precise check needs to account for rent, but it should be significantly less.
+    // The prize vault should have its lamports reduced by the total distributed amount.
+    // It still holds its rent-exempt minimum, which the admin paid when the round started.
+    // A precise check needs to account for the rounding remainder, but it should be significantly less.
     const totalDistributed = devShare.add(mainWinnerShare).add(otherWinnerShare.mul(new anchor.BN(4)));
-    const expectedPdaBalanceAfter = new anchor.BN(lotteryRoundInitialBalance).sub(totalDistributed);
-    // Allow for small differences due to rent not being perfectly accounted for in this simple check.
+    const expectedVaultBalanceAfter = new anchor.BN(prizeVaultInitialBalance).sub(totalDistributed);
+    // Allow for small differences due to the rounding remainder not being accounted for in this simple check.
     // Using a tolerance, e.g., 0.001 SOL.
-    expect(lotteryRoundFinalBalance).to.be.closeTo(expectedPdaBalanceAfter.toNumber(), LAMPORTS_PER_SOL * 0.001, "LotteryRound PDA balance after distribution is not as expected");
+    expect(prizeVaultFinalBalance).to.be.closeTo(expectedVaultBalanceAfter.toNumber(), LAMPORTS_PER_SOL * 0.001, "Prize vault balance after distribution is not as expected");
+
+    // Each payment should be reported by a `PrizePaid` event, and the payout as a whole by `RoundClosed`.
+    const distributeEvents = await transactionEvents(distributeSignature);
+    const prizesPaid = distributeEvents.filter(event => event.name === "prizePaid").map(event => event.data);
+    const expectedPrizes = [
+      { recipient: feeVaultPDA, amount: devShare, kind: { devFee: {} } },
+      { recipient: mainWinner, amount: mainWinnerShare, kind: { mainWinner: {} } },
+      ...otherWinners.map(ow => ({ recipient: ow, amount: otherWinnerShare, kind: { otherWinner: {} } })),
+    ];
+    assert.lengthOf(prizesPaid, expectedPrizes.length, "PrizePaid event count mismatch");
+    prizesPaid.forEach((prize, index) => {
+      assert.ok(prize.roundId.eq(roundIdForPrize), `PrizePaid ${index} round ID mismatch`);
+      assert.ok(prize.recipient.equals(expectedPrizes[index].recipient), `PrizePaid ${index} recipient mismatch`);
+      assert.ok(prize.amountLamports.eq(expectedPrizes[index].amount), `PrizePaid ${index} amount mismatch`);
+      assert.deepEqual(prize.kind, expectedPrizes[index].kind, `PrizePaid ${index} kind mismatch`);
+    });
+    const [roundClosed] = distributeEvents.filter(event => event.name === "roundClosed");
+    assert.ok(roundClosed, "RoundClosed event was not emitted");
+    assert.ok(roundClosed.data.roundId.eq(roundIdForPrize), "RoundClosed round ID mismatch");
+    assert.ok(roundClosed.data.totalPotLamports.eq(totalPot), "RoundClosed total pot mismatch");
+    assert.equal(roundClosed.data.participantCount, roundAccount.participants.length, "RoundClosed participant count mismatch");
+    assert.deepEqual(roundClosed.data.reason, { prizesDistributed: {} }, "RoundClosed reason mismatch");
     console.log("Prize distribution amounts verified.");
   });
 
@@ -403,16 +627,18 @@ describe("solana_lottery_contract", () => {
     // Test: Attempt to start a new round when the lottery is paused.
     console.log("Sub-test: Start round when paused");
     await program.methods.updateConfig(null, null, true).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc(); // Pause lottery
+    const pausedRoundId = new anchor.BN(3); // New round ID for this test.
+    const [pausedRoundPDA, _] = await findLotteryRoundPDA(pausedRoundId);
+    const startPausedRound = () => program.methods.startNewLotteryRound(new anchor.BN(2), pausedRoundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(pausedRoundId), lotteryRound: pausedRoundPDA, prizeVault: findPrizeVaultPDA(pausedRoundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
     try {
-        const roundId = new anchor.BN(3); // New round ID for this test.
-        const [pausedRoundPDA, _] = await findLotteryRoundPDA(roundId);
-        await program.methods.startNewLotteryRound(new anchor.BN(10), roundId).accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: pausedRoundPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+        await startPausedRound();
         assert.fail("Should not have been able to start a round when lottery is paused");
     } catch (err) {
         console.log("Caught expected error for starting round when paused:", err.message);
         assert.include(err.toString(), "LotteryPaused", "Error message mismatch for LotteryPaused");
     }
     await program.methods.updateConfig(null, null, false).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc(); // Unpause for subsequent tests.
+    await startPausedRound(); // The round starts once the lottery is unpaused.
 
     // Test: Attempt to enter a round that is not active (e.g., after draw has been conducted).
     // Use Round 2, for which the draw was conducted in a previous test.
@@ -420,31 +646,1433 @@ describe("solana_lottery_contract", () => {
     const roundIdForInactiveTest = new anchor.BN(2);
     const [inactiveRoundPDA, __] = await findLotteryRoundPDA(roundIdForInactiveTest);
     try {
-        await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: inactiveRoundPDA, participant: participant6.publicKey, systemProgram: SystemProgram.programId }).signers([participant6]).rpc();
+        await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: inactiveRoundPDA, prizeVault: findPrizeVaultPDA(inactiveRoundPDA), lotteryStats: lotteryStatsPDA, participant: participant6.publicKey, playerProfile: findPlayerProfilePDA(participant6.publicKey), systemProgram: SystemProgram.programId }).signers([participant6]).rpc();
         assert.fail("Should not have been able to enter an inactive round (after draw)");
     } catch (err) {
         console.log("Caught expected error for entering inactive round:", err.message);
         assert.include(err.toString(), "LotteryRoundNotActive", "Error message mismatch for LotteryRoundNotActive");
     }
 
+    // Round 3 drew no entries; cancel it so that the next round can start.
+    await cancelUnderSubscribedRound(pausedRoundPDA);
+
     // Test: Attempt to conduct a draw with not enough participants.
     console.log("Sub-test: Conduct draw with insufficient participants");
     const roundIdForNotEnoughP = new anchor.BN(4); // New round ID for this test.
     const [notEnoughParticipantsRoundPDA, ___] = await findLotteryRoundPDA(roundIdForNotEnoughP);
     // Start a new round.
-    await program.methods.startNewLotteryRound(new anchor.BN(5), roundIdForNotEnoughP).accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: notEnoughParticipantsRoundPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    await program.methods.startNewLotteryRound(new anchor.BN(5), roundIdForNotEnoughP).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundIdForNotEnoughP), lotteryRound: notEnoughParticipantsRoundPDA, prizeVault: findPrizeVaultPDA(notEnoughParticipantsRoundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
     // Only have 1 participant enter (less than the required 5).
-    await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: notEnoughParticipantsRoundPDA, participant: participant1.publicKey, systemProgram: SystemProgram.programId }).signers([participant1]).rpc();
+    await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: notEnoughParticipantsRoundPDA, prizeVault: findPrizeVaultPDA(notEnoughParticipantsRoundPDA), lotteryStats: lotteryStatsPDA, participant: participant1.publicKey, playerProfile: findPlayerProfilePDA(participant1.publicKey), systemProgram: SystemProgram.programId }).signers([participant1]).rpc();
     
     await new Promise(resolve => setTimeout(resolve, 6000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: notEnoughParticipantsRoundPDA }).rpc();
     try {
-        await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: notEnoughParticipantsRoundPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY }).rpc();
+        await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: notEnoughParticipantsRoundPDA, prizeVault: findPrizeVaultPDA(notEnoughParticipantsRoundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
         assert.fail("Should not have been able to conduct draw with insufficient participants");
     } catch (err) {
         console.log("Caught expected error for insufficient participants:", err.message);
         assert.include(err.toString(), "NotEnoughParticipants", "Error message mismatch for NotEnoughParticipants");
     }
+    // Cancel round 4 so that the next round can start. Its participant is not refunded yet.
+    await cancelUnderSubscribedRound(notEnoughParticipantsRoundPDA);
     console.log("Error handling tests completed.");
   });
+
+  it("Blocks payouts for a flagged round and refunds it on cancellation!", async () => {
+    console.log("Test: Dispute Window (Round 5)");
+    const auditor = Keypair.generate();
+    const disputeWindowSeconds = new anchor.BN(30);
+
+    // Enable a 30 second dispute window with a designated auditor.
+    await program.methods
+      .setDisputeConfig(auditor.publicKey, disputeWindowSeconds)
+      .accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    let configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    assert.ok(configAccount.auditorKey.equals(auditor.publicKey), "Auditor key mismatch");
+    assert.ok(configAccount.disputeWindowSeconds.eq(disputeWindowSeconds), "Dispute window mismatch");
+
+    // Start a short round, fill it and draw it.
+    const roundId = new anchor.BN(5);
+    const [disputedRoundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: disputedRoundPDA, prizeVault: findPrizeVaultPDA(disputedRoundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    const roundParticipants = [participant1, participant2, participant3, participant4, participant5];
+    for (const p of roundParticipants) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: disputedRoundPDA, prizeVault: findPrizeVaultPDA(disputedRoundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: disputedRoundPDA }).rpc();
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: disputedRoundPDA, prizeVault: findPrizeVaultPDA(disputedRoundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    let roundAccount = await program.account.lotteryRound.fetch(disputedRoundPDA);
+    const winnerAccounts = prizeRecipientAccounts([roundAccount.mainWinner!, ...roundAccount.otherWinners]);
+
+    // Distribution must wait for the dispute window.
+    console.log("Sub-test: Distribute during the dispute window");
+    try {
+      await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: disputedRoundPDA, prizeVault: findPrizeVaultPDA(disputedRoundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(winnerAccounts).rpc();
+      assert.fail("Should not have been able to distribute prizes during the dispute window");
+    } catch (err) {
+      assert.include(err.toString(), "DisputeWindowOpen", "Error message mismatch for DisputeWindowOpen");
+    }
+
+    // A participant cannot flag the round, the auditor can.
+    console.log("Sub-test: Flag the round");
+    try {
+      await program.methods.flagRound().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: disputedRoundPDA, authority: participant6.publicKey }).signers([participant6]).rpc();
+      assert.fail("Should not have been able to flag the round without authority");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedDisputeAuthority", "Error message mismatch for UnauthorizedDisputeAuthority");
+    }
+    await program.methods.flagRound().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: disputedRoundPDA, authority: auditor.publicKey }).signers([auditor]).rpc();
+    roundAccount = await program.account.lotteryRound.fetch(disputedRoundPDA);
+    assert.isTrue(roundAccount.isDisputed, "Round should be disputed");
+
+    // The admin cancels the round and anyone can crank the refunds.
+    console.log("Sub-test: Cancel and refund");
+    await program.methods.cancelRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: disputedRoundPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    roundAccount = await program.account.lotteryRound.fetch(disputedRoundPDA);
+    assert.deepEqual(roundAccount.status, { cancelled: {} }, "Round should be cancelled");
+    assert.isFalse(roundAccount.isDisputed, "Round should no longer be disputed");
+    const refund = roundAccount.refundPerParticipantLamports;
+    assert.ok(refund.eq(roundAccount.totalPotLamports.divn(roundParticipants.length)), "Refund amount mismatch");
+
+    const balancesBefore = await Promise.all(roundAccount.participants.map(p => provider.connection.getBalance(p)));
+    await program.methods
+      .refundParticipants(roundId)
+      .accounts({ lotteryRound: disputedRoundPDA, prizeVault: findPrizeVaultPDA(disputedRoundPDA) })
+      .remainingAccounts(roundAccount.participants.map(p => ({ pubkey: p, isSigner: false, isWritable: true })))
+      .rpc();
+    const balancesAfter = await Promise.all(roundAccount.participants.map(p => provider.connection.getBalance(p)));
+    balancesAfter.forEach((balance, i) => {
+      assert.ok(new anchor.BN(balance).eq(new anchor.BN(balancesBefore[i]).add(refund)), `Participant ${i} was not refunded`);
+    });
+    roundAccount = await program.account.lotteryRound.fetch(disputedRoundPDA);
+    assert.equal(roundAccount.refundsProcessed, roundParticipants.length, "All participants should be refunded");
+
+    // Disable the dispute window again for any later tests.
+    await program.methods.setDisputeConfig(null, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Dispute flow verified.");
+  });
+
+  it("Pays the crank bounty to community keepers!", async () => {
+    console.log("Test: Crank Bounty (Round 6)");
+    const keeper = Keypair.generate();
+    await airdropSol(keeper.publicKey, 0.1); // The keeper only signs; the provider wallet pays transaction fees.
+
+    // A flat amount or cap above 0.1 SOL could take more than the allowed share of a small pot.
+    for (const [flat, cap] of [[0.1 * LAMPORTS_PER_SOL + 1, 0], [0, 0.1 * LAMPORTS_PER_SOL + 1]]) {
+      try {
+        await program.methods.setCrankBounty(new anchor.BN(flat), 0, new anchor.BN(cap)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+        assert.fail("Should not have been able to set an excessive crank bounty");
+      } catch (err) {
+        assert.include(err.toString(), "CrankBountyTooHigh", "Error message mismatch for CrankBountyTooHigh");
+      }
+    }
+
+    // 0.001 SOL flat plus 1% of the pot, capped at 0.005 SOL per crank (and never more than 5% of the pot).
+    const bountyLamports = new anchor.BN(0.001 * LAMPORTS_PER_SOL);
+    const bountyBps = 100;
+    const bountyCapLamports = new anchor.BN(0.005 * LAMPORTS_PER_SOL);
+    await program.methods
+      .setCrankBounty(bountyLamports, bountyBps, bountyCapLamports)
+      .accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    const roundId = new anchor.BN(6);
+    const [bountyRoundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: bountyRoundPDA, prizeVault: findPrizeVaultPDA(bountyRoundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: bountyRoundPDA, prizeVault: findPrizeVaultPDA(bountyRoundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: bountyRoundPDA }).rpc();
+
+    const potBeforeDraw = (await program.account.lotteryRound.fetch(bountyRoundPDA)).totalPotLamports;
+    const expectedDrawBounty = anchor.BN.min(anchor.BN.min(bountyLamports.add(potBeforeDraw.muln(bountyBps).divn(10_000)), bountyCapLamports), potBeforeDraw.muln(500).divn(10_000));
+    const keeperBalanceBefore = await provider.connection.getBalance(keeper.publicKey);
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: bountyRoundPDA, prizeVault: findPrizeVaultPDA(bountyRoundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: keeper.publicKey }).signers([keeper]).rpc();
+
+    let roundAccount = await program.account.lotteryRound.fetch(bountyRoundPDA);
+    assert.ok(roundAccount.drawKeeper.equals(keeper.publicKey), "Draw keeper mismatch");
+    assert.ok(roundAccount.drawBountyLamports.eq(expectedDrawBounty), "Draw bounty mismatch");
+    assert.ok(roundAccount.totalPotLamports.eq(potBeforeDraw.sub(expectedDrawBounty)), "Pot should be reduced by the draw bounty");
+
+    const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    const winnerAccounts = prizeRecipientAccounts([roundAccount.mainWinner!, ...roundAccount.otherWinners]);
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: bountyRoundPDA, prizeVault: findPrizeVaultPDA(bountyRoundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: keeper.publicKey }).remainingAccounts(winnerAccounts).signers([keeper]).rpc();
+
+    roundAccount = await program.account.lotteryRound.fetch(bountyRoundPDA);
+    assert.ok(roundAccount.distributionKeeper.equals(keeper.publicKey), "Distribution keeper mismatch");
+    const keeperBalanceAfter = await provider.connection.getBalance(keeper.publicKey);
+    const totalBounty = roundAccount.drawBountyLamports.add(roundAccount.distributionBountyLamports);
+    assert.ok(new anchor.BN(keeperBalanceAfter).eq(new anchor.BN(keeperBalanceBefore).add(totalBounty)), "Keeper was not paid both bounties");
+
+    // Disable the bounty again for any later tests.
+    await program.methods.setCrankBounty(new anchor.BN(0), 0, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Crank bounty verified.");
+  });
+
+  it("Emits typed events for state transitions!", async () => {
+    // Listen for `ConfigUpdated` and check it carries both the old and the new values.
+    const configBefore = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    let receivedEvent: any = null;
+    const listener = program.addEventListener("configUpdated", (event) => { receivedEvent = event; });
+    try {
+      await program.methods.updateConfig(null, null, false).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+      for (let i = 0; i < 20 && !receivedEvent; i++) {
+        await new Promise(resolve => setTimeout(resolve, 250)); // Give the log subscription time to deliver the event.
+      }
+    } finally {
+      await program.removeEventListener(listener);
+    }
+
+    assert.ok(receivedEvent, "ConfigUpdated event was not emitted");
+    assert.ok(receivedEvent.admin.equals(admin.publicKey), "Event admin mismatch");
+    assert.ok(receivedEvent.oldDevFeeReceiver.equals(configBefore.devFeeReceiver), "Old dev fee receiver mismatch");
+    assert.ok(receivedEvent.newDevFeeReceiver.equals(configBefore.devFeeReceiver), "New dev fee receiver mismatch");
+    assert.ok(receivedEvent.oldEntryFeeLamports.eq(configBefore.entryFeeLamports), "Old entry fee mismatch");
+    assert.ok(receivedEvent.newEntryFeeLamports.eq(configBefore.entryFeeLamports), "New entry fee mismatch");
+    assert.equal(receivedEvent.oldIsPaused, configBefore.isPaused, "Old paused flag mismatch");
+    assert.equal(receivedEvent.newIsPaused, false, "New paused flag mismatch");
+    console.log("ConfigUpdated event verified.");
+  });
+
+  it("Refuses to migrate legacy rounds into a lottery that did not take over the legacy config!", async () => {
+    const roundId = new anchor.BN(1);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const [legacyRoundPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_round"), roundId.toBuffer("le", 8)], program.programId);
+    const roundBefore = await program.account.lotteryRound.fetch(roundPDA);
+    try {
+      await program.methods
+        .migrateRound(roundId)
+        .accounts({ lotteryConfig: lotteryConfigPDA, legacyLotteryRound: legacyRoundPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should not have been able to migrate into a lottery other than the legacy one");
+    } catch (err) {
+      assert.include(err.toString(), "NotLegacyLottery", "Error message mismatch for NotLegacyLottery");
+    }
+    const roundAfter = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAfter.status, roundBefore.status, "Status should be unchanged");
+    console.log("Migration guard verified.");
+  });
+
+  it("Closes a distributed round into a RoundResult and reclaims its rent!", async () => {
+    // Round 2 was drawn and distributed in earlier tests.
+    const roundId = new anchor.BN(2);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const [roundResultPDA, __] = PublicKey.findProgramAddressSync(
+      [Buffer.from("round_result"), lotteryConfigPDA.toBuffer(), roundId.toBuffer("le", 8)], // Seeds: b"round_result", the config PDA and roundId
+      program.programId
+    );
+    const roundBefore = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(roundBefore.rentPayer.equals(admin.publicKey), "Admin should have paid the round's rent");
+    const roundLamports = await provider.connection.getBalance(roundPDA) + await provider.connection.getBalance(findPrizeVaultPDA(roundPDA));
+    const adminBalanceBefore = await provider.connection.getBalance(admin.publicKey);
+
+    await program.methods.closeRound(roundId).accounts({ lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), roundResult: roundResultPDA, rentPayer: admin.publicKey, payer: provider.wallet.publicKey, systemProgram: SystemProgram.programId }).rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(roundPDA), "LotteryRound account should be closed");
+    assert.isNull(await provider.connection.getAccountInfo(findPrizeVaultPDA(roundPDA)), "Prize vault should be emptied");
+    const adminBalanceAfter = await provider.connection.getBalance(admin.publicKey);
+    assert.equal(adminBalanceAfter - adminBalanceBefore, roundLamports, "Rent payer should receive all of the round's and its vault's lamports");
+
+    const result = await program.account.roundResult.fetch(roundResultPDA);
+    assert.ok(result.roundId.eq(roundId), "Round ID mismatch");
+    assert.deepEqual(result.outcome, { distributed: {} }, "Outcome should be distributed");
+    assert.equal(result.participantCount, roundBefore.participants.length, "Participant count mismatch");
+    assert.ok(result.mainWinner.equals(roundBefore.mainWinner!), "Main winner mismatch");
+    assert.deepEqual(result.otherWinners.map(w => w.toBase58()), roundBefore.otherWinners.map(w => w.toBase58()), "Other winners mismatch");
+    assert.deepEqual(result.randomnessSeed, roundBefore.randomnessSeed, "Randomness seed mismatch");
+    assert.deepEqual(result.participantsRoot, roundBefore.participantsRoot, "Participants root mismatch");
+
+    // A cancelled round cannot be closed before its participants are refunded; round 4 still owes its participant a refund.
+    const [unrefundedRoundPDA, ___] = await findLotteryRoundPDA(new anchor.BN(4));
+    const [unrefundedRoundResultPDA, ____] = PublicKey.findProgramAddressSync([Buffer.from("round_result"), lotteryConfigPDA.toBuffer(), new anchor.BN(4).toBuffer("le", 8)], program.programId);
+    try {
+      await program.methods.closeRound(new anchor.BN(4)).accounts({ lotteryRound: unrefundedRoundPDA, prizeVault: findPrizeVaultPDA(unrefundedRoundPDA), roundResult: unrefundedRoundResultPDA, rentPayer: admin.publicKey, payer: provider.wallet.publicKey, systemProgram: SystemProgram.programId }).rpc();
+      assert.fail("Should not have been able to close a round with outstanding refunds");
+    } catch (err) {
+      assert.include(err.toString(), "RefundsIncomplete", "Error message mismatch for RefundsIncomplete");
+    }
+    console.log("Round closing verified.");
+  });
+
+  it("Starts scheduled rounds permissionlessly with rent from the reserve!", async () => {
+    // Round 6 (drawn and distributed) is the current round; the next scheduled round is round 7.
+    const roundId = new anchor.BN(7);
+    const [previousRoundPDA, _] = await findLotteryRoundPDA(new anchor.BN(6));
+    const [scheduledRoundPDA, __] = await findLotteryRoundPDA(roundId);
+    const startScheduled = () => program.methods.startScheduledRound(roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: previousRoundPDA, lotteryRound: scheduledRoundPDA, prizeVault: findPrizeVaultPDA(scheduledRoundPDA), systemProgram: SystemProgram.programId }).rpc();
+
+    try {
+      await startScheduled();
+      assert.fail("Should not have been able to start a scheduled round without a schedule");
+    } catch (err) {
+      assert.include(err.toString(), "RoundScheduleNotSet", "Error message mismatch for RoundScheduleNotSet");
+    }
+
+    await program.methods.setRoundSchedule(new anchor.BN(60), new anchor.BN(2)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc().then(
+      () => assert.fail("Cadence shorter than the duration should be rejected"),
+      (err) => assert.include(err.toString(), "InvalidRoundSchedule", "Error message mismatch for InvalidRoundSchedule")
+    );
+    await program.methods.setRoundSchedule(new anchor.BN(2), new anchor.BN(2)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.fundRentReserve(new anchor.BN(LAMPORTS_PER_SOL)).accounts({ lotteryConfig: lotteryConfigPDA, funder: provider.wallet.publicKey, systemProgram: SystemProgram.programId }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for the cadence to elapse since round 6 started.
+
+    const configBalanceBefore = await provider.connection.getBalance(lotteryConfigPDA);
+    await startScheduled();
+
+    const roundAccount = await program.account.lotteryRound.fetch(scheduledRoundPDA);
+    assert.ok(roundAccount.roundId.eq(roundId), "Round ID mismatch");
+    assert.deepEqual(roundAccount.status, { open: {} }, "Scheduled round should be open");
+    assert.ok(roundAccount.rentPayer.equals(lotteryConfigPDA), "The reserve should be the scheduled round's rent payer");
+    assert.equal(roundAccount.drawTimestamp.sub(roundAccount.startTimestamp).toNumber(), 2, "Draw should be one duration after the start");
+    const roundLamports = await provider.connection.getBalance(scheduledRoundPDA) + await provider.connection.getBalance(findPrizeVaultPDA(scheduledRoundPDA));
+    const configBalanceAfter = await provider.connection.getBalance(lotteryConfigPDA);
+    assert.equal(configBalanceBefore - configBalanceAfter, roundLamports, "The rent of the round and its vault should be paid from the reserve");
+    const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    assert.ok(configAccount.currentLotteryRoundId.eq(roundId), "Current round ID should advance");
+
+    // Round 7 has not been paid out, so round 8 cannot start yet.
+    const [nextRoundPDA, ___] = await findLotteryRoundPDA(new anchor.BN(8));
+    try {
+      await program.methods.startScheduledRound(new anchor.BN(8)).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: scheduledRoundPDA, lotteryRound: nextRoundPDA, prizeVault: findPrizeVaultPDA(nextRoundPDA), systemProgram: SystemProgram.programId }).rpc();
+      assert.fail("Should not have been able to start a round before the previous one was paid out");
+    } catch (err) {
+      assert.include(err.toString(), "PreviousRoundNotFinished", "Error message mismatch for PreviousRoundNotFinished");
+    }
+
+    // Round 7 drew no entries; cancel it so that the next round can start.
+    await cancelUnderSubscribedRound(scheduledRoundPDA);
+
+    // Disable the schedule again for any later tests.
+    await program.methods.setRoundSchedule(new anchor.BN(0), new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Scheduled round rollover verified.");
+  });
+
+  it("Aligns draw times to the configured draw calendar!", async () => {
+    // Round 7 was cancelled; the admin starts round 8 with draws aligned to the 5th second of every 10 seconds.
+    const roundId = new anchor.BN(8);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const period = 10;
+    const offset = 5;
+
+    await program.methods.setDrawCalendar(new anchor.BN(period), new anchor.BN(period)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc().then(
+      () => assert.fail("An offset outside the period should be rejected"),
+      (err) => assert.include(err.toString(), "InvalidDrawCalendar", "Error message mismatch for InvalidDrawCalendar")
+    );
+    await program.methods.setDrawCalendar(new anchor.BN(period), new anchor.BN(offset)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+
+    const minDuration = 5;
+    await program.methods
+      .startNewLotteryRound(new anchor.BN(minDuration), roundId)
+      .accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId })
+      .signers([admin])
+      .rpc();
+
+    const roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    const start = roundAccount.startTimestamp.toNumber();
+    const draw = roundAccount.drawTimestamp.toNumber();
+    assert.equal(draw % period, offset, "Draw should fall on a calendar slot");
+    assert.isAtLeast(draw - start, minDuration, "Draw should not be earlier than the requested duration");
+    assert.isBelow(draw - start, minDuration + period, "Draw should be the first slot after the requested duration");
+
+    // Disable the calendar again for any later tests, then cancel the round, which drew no entries.
+    await program.methods.setDrawCalendar(new anchor.BN(0), new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await cancelUnderSubscribedRound(roundPDA);
+    console.log("Draw calendar alignment verified.");
+  });
+
+  it("Stops ticket sales at the sales cutoff before the draw!", async () => {
+    // Round 8 was cancelled; the admin starts round 9 with sales ending 3 seconds before its draw.
+    const roundId = new anchor.BN(9);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const cutoff = 3;
+
+    await program.methods.setSalesCutoff(new anchor.BN(-1)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc().then(
+      () => assert.fail("A negative sales cutoff should be rejected"),
+      (err) => assert.include(err.toString(), "InvalidSalesCutoff", "Error message mismatch for InvalidSalesCutoff")
+    );
+    await program.methods.setSalesCutoff(new anchor.BN(cutoff)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.startNewLotteryRound(new anchor.BN(5), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+
+    let roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.equal(roundAccount.drawTimestamp.sub(roundAccount.salesCloseTimestamp).toNumber(), cutoff, "Sales should close one cutoff before the draw");
+
+    try {
+      await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+      assert.fail("Should not have been able to close sales before the sales close time");
+    } catch (err) {
+      assert.include(err.toString(), "SalesCloseTimeNotReached", "Error message mismatch for SalesCloseTimeNotReached");
+    }
+    await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: participant1.publicKey, playerProfile: findPlayerProfilePDA(participant1.publicKey), systemProgram: SystemProgram.programId }).signers([participant1]).rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for sales to close; the draw is still ahead.
+    try {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: participant2.publicKey, playerProfile: findPlayerProfilePDA(participant2.publicKey), systemProgram: SystemProgram.programId }).signers([participant2]).rpc();
+      assert.fail("Should not have been able to enter after sales closed");
+    } catch (err) {
+      assert.include(err.toString(), "SalesPeriodEnded", "Error message mismatch for SalesPeriodEnded");
+    }
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAccount.status, { salesClosed: {} }, "Sales should be closed");
+    assert.equal(roundAccount.participants.length, 1, "Only the entry before the cutoff should count");
+
+    // Sell until the draw again for any later tests.
+    await program.methods.setSalesCutoff(new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Sales cutoff verified.");
+  });
+
+  it("Extends under-subscribed rounds and cancels them once out of extensions!", async () => {
+    // Round 9 (one participant, sales closed) is the current round and cannot be drawn.
+    const roundId = new anchor.BN(9);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const extendRound = () => program.methods.extendRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: roundPDA }).rpc();
+
+    await program.methods.setRoundExtensions(new anchor.BN(2), 9).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc().then(
+      () => assert.fail("More extensions than a round can record should be rejected"),
+      (err) => assert.include(err.toString(), "InvalidRoundExtensionRule", "Error message mismatch for InvalidRoundExtensionRule")
+    );
+    await program.methods.setRoundExtensions(new anchor.BN(2), 1).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for the draw time to pass.
+
+    const roundBefore = await program.account.lotteryRound.fetch(roundPDA);
+    await extendRound();
+    let roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAccount.status, { open: {} }, "Extension should reopen sales");
+    assert.equal(roundAccount.extensions.length, 1, "Extension should be recorded");
+    assert.ok(roundAccount.extensions[0].previousDrawTimestamp.eq(roundBefore.drawTimestamp), "Previous draw time mismatch");
+    assert.ok(roundAccount.extensions[0].newDrawTimestamp.eq(roundAccount.drawTimestamp), "New draw time mismatch");
+    assert.equal(roundAccount.extensions[0].participantCount, 1, "Participant count mismatch");
+    assert.ok(roundAccount.drawTimestamp.gt(roundBefore.drawTimestamp), "Draw should move into the future");
+
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for the extended draw time to pass.
+    await extendRound();
+    roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAccount.status, { cancelled: {} }, "Round should be cancelled once out of extensions");
+    assert.equal(roundAccount.extensions.length, 1, "No further extension should be recorded");
+    assert.ok(roundAccount.refundPerParticipantLamports.eq(roundAccount.totalPotLamports), "The single participant should get the pot back");
+
+    // Disable extensions again for any later tests.
+    await program.methods.setRoundExtensions(new anchor.BN(0), 0).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Round extensions verified.");
+  });
+
+  it("Closes sold-out rounds early and draws them without waiting!", async () => {
+    // Round 9 was cancelled; the admin starts round 10 with a draw far in the future and a cap of 5 participants.
+    const roundId = new anchor.BN(10);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+
+    await program.methods.setMaxParticipants(3).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc().then(
+      () => assert.fail("A cap below the number of winners should be rejected"),
+      (err) => assert.include(err.toString(), "InvalidMaxParticipants", "Error message mismatch for InvalidMaxParticipants")
+    );
+    await program.methods.setMaxParticipants(5).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.startNewLotteryRound(new anchor.BN(3600), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+
+    let soldOutEvent: any = null;
+    const listener = program.addEventListener("roundSoldOut", (event) => { soldOutEvent = event; });
+    try {
+      for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+        await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+      }
+      for (let i = 0; i < 20 && !soldOutEvent; i++) {
+        await new Promise(resolve => setTimeout(resolve, 250)); // Give the log subscription time to deliver the event.
+      }
+    } finally {
+      await program.removeEventListener(listener);
+    }
+    let roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.isTrue(roundAccount.isSoldOut, "Round should be sold out at the cap");
+    assert.isNotNull(soldOutEvent, "RoundSoldOut should be emitted");
+    assert.ok(soldOutEvent.roundId.eq(roundId), "Sold-out round ID mismatch");
+
+    try {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: participant6.publicKey, playerProfile: findPlayerProfilePDA(participant6.publicKey), systemProgram: SystemProgram.programId }).signers([participant6]).rpc();
+      assert.fail("Should not have been able to enter a sold-out round");
+    } catch (err) {
+      assert.include(err.toString(), "RoundSoldOut", "Error message mismatch for RoundSoldOut");
+    }
+
+    // Neither the sales close time nor the draw time has been reached.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAccount.status, { drawn: {} }, "Sold-out round should be drawn before its draw time");
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(prizeRecipientAccounts([roundAccount.mainWinner!, ...roundAccount.otherWinners])).rpc();
+
+    // Only sell out at the account capacity again for any later tests.
+    await program.methods.setMaxParticipants(0).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Sold-out early close verified.");
+  });
+
+  it("Adds sponsor contributions to the pot and refunds them to the sponsor!", async () => {
+    // Round 10 was paid out; the admin starts round 11, which a sponsor tops up but only one participant enters.
+    const roundId = new anchor.BN(11);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const sponsor = Keypair.generate();
+    await airdropSol(sponsor.publicKey, 1);
+    const contribution = new anchor.BN(LAMPORTS_PER_SOL / 2);
+
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    try {
+      await program.methods.contributeToPot(contribution, "x".repeat(129)).accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), contributor: sponsor.publicKey, systemProgram: SystemProgram.programId }).signers([sponsor]).rpc();
+      assert.fail("Should not have accepted an overlong memo");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionMemoTooLong", "Error message mismatch for ContributionMemoTooLong");
+    }
+    await program.methods.contributeToPot(contribution, "Sponsored jackpot").accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), contributor: sponsor.publicKey, systemProgram: SystemProgram.programId }).signers([sponsor]).rpc();
+    await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: participant1.publicKey, playerProfile: findPlayerProfilePDA(participant1.publicKey), systemProgram: SystemProgram.programId }).signers([participant1]).rpc();
+
+    let roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    const config = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    assert.equal(roundAccount.contributions.length, 1, "Contribution should be recorded");
+    assert.ok(roundAccount.contributions[0].contributor.equals(sponsor.publicKey), "Contributor mismatch");
+    assert.ok(roundAccount.contributions[0].amountLamports.eq(contribution), "Contribution amount mismatch");
+    assert.ok(roundAccount.totalPotLamports.eq(contribution.add(config.entryFeeLamports)), "Contribution should be part of the pot");
+
+    // Cancel the under-subscribed round by running out of extensions.
+    await program.methods.setRoundExtensions(new anchor.BN(1), 1).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    const extendRound = () => program.methods.extendRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for the draw time to pass.
+    await extendRound();
+    await new Promise(resolve => setTimeout(resolve, 2000)); // Wait for the extended draw time to pass.
+    await extendRound();
+    roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAccount.status, { cancelled: {} }, "Round should be cancelled");
+    assert.ok(roundAccount.refundPerParticipantLamports.eq(config.entryFeeLamports), "Participants should only share the ticket sales");
+
+    // Participants are refunded first, then contributors.
+    const participantBalanceBefore = await provider.connection.getBalance(participant1.publicKey);
+    const sponsorBalanceBefore = await provider.connection.getBalance(sponsor.publicKey);
+    await program.methods
+      .refundParticipants(roundId)
+      .accounts({ lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA) })
+      .remainingAccounts([participant1.publicKey, sponsor.publicKey].map(pubkey => ({ pubkey, isSigner: false, isWritable: true })))
+      .rpc();
+    assert.equal(await provider.connection.getBalance(participant1.publicKey) - participantBalanceBefore, config.entryFeeLamports.toNumber(), "Participant refund mismatch");
+    assert.equal(await provider.connection.getBalance(sponsor.publicKey) - sponsorBalanceBefore, contribution.toNumber(), "Sponsor should get the contribution back");
+    roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.equal(roundAccount.refundsProcessed, 2, "All refunds should be processed");
+
+    // Disable extensions again for any later tests.
+    await program.methods.setRoundExtensions(new anchor.BN(0), 0).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Pot contributions verified.");
+  });
+
+  it("Tops up the main prize to the guarantee from the treasury!", async () => {
+    // Round 11 was cancelled; round 12 guarantees a main prize larger than its own pot can pay.
+    const roundId = new anchor.BN(12);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const guarantee = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    await program.methods.setGuaranteedMainPrize(guarantee).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+
+    // An empty treasury cannot back the guarantee.
+    try {
+      await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+      assert.fail("Should not have started a round the treasury cannot back");
+    } catch (err) {
+      assert.include(err.toString(), "InsufficientTreasury", "Error message mismatch for InsufficientTreasury");
+    }
+
+    const sponsor = Keypair.generate();
+    await airdropSol(sponsor.publicKey, 3);
+    await program.methods.fundTreasury(guarantee).accounts({ treasury: treasuryPDA, funder: sponsor.publicKey, systemProgram: SystemProgram.programId }).signers([sponsor]).rpc();
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    let treasuryAccount = await program.account.treasury.fetch(treasuryPDA);
+    assert.ok(treasuryAccount.committedLamports.eq(guarantee), "The guarantee should be committed while the round runs");
+    try {
+      await program.methods.withdrawTreasury(new anchor.BN(1)).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, admin: admin.publicKey }).signers([admin]).rpc();
+      assert.fail("Should not have withdrawn committed treasury funds");
+    } catch (err) {
+      assert.include(err.toString(), "InsufficientTreasury", "Error message mismatch for InsufficientTreasury");
+    }
+
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    const roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    const mainWinner = roundAccount.mainWinner!;
+    const winnerAccounts = prizeRecipientAccounts([mainWinner, ...roundAccount.otherWinners]);
+    const mainWinnerBalanceBefore = await provider.connection.getBalance(mainWinner);
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(winnerAccounts).rpc();
+
+    const mainWinnerBalanceAfter = await provider.connection.getBalance(mainWinner);
+    assert.equal(mainWinnerBalanceAfter - mainWinnerBalanceBefore, guarantee.toNumber(), "Main winner should receive exactly the guarantee");
+    treasuryAccount = await program.account.treasury.fetch(treasuryPDA);
+    assert.ok(treasuryAccount.committedLamports.eqn(0), "The guarantee should be released after distribution");
+
+    // Remove the guarantee again for any later tests.
+    await program.methods.setGuaranteedMainPrize(new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Guaranteed main prize verified.");
+  });
+
+  it("Runs independent lotteries side by side!", async () => {
+    // Lottery 1 (e.g. a VIP lottery) has its own admin, entry fee, treasury and round numbering.
+    const vipLotteryId = new anchor.BN(1);
+    const vipAdmin = Keypair.generate();
+    await airdropSol(vipAdmin.publicKey, 2);
+    const vipEntryFee = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+    const [vipConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_config"), vipLotteryId.toBuffer("le", 8)], program.programId);
+    const [vipTreasuryPDA] = PublicKey.findProgramAddressSync([Buffer.from("treasury"), vipConfigPDA.toBuffer()], program.programId);
+    const [vipLotteryStatsPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_stats"), vipConfigPDA.toBuffer()], program.programId);
+    const mainConfigBefore = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+
+    await program.methods.createLottery(vipLotteryId, devFeeReceiver.publicKey, vipEntryFee, 1000, 5000, "").accounts({ lotteryConfig: vipConfigPDA, platformConfig: platformConfigPDA, creator: vipAdmin.publicKey, systemProgram: SystemProgram.programId }).signers([vipAdmin]).rpc();
+    await program.methods.initializeTreasury().accounts({ lotteryConfig: vipConfigPDA, treasury: vipTreasuryPDA, admin: vipAdmin.publicKey, systemProgram: SystemProgram.programId }).signers([vipAdmin]).rpc();
+    await program.methods.initializeLotteryStats().accounts({ lotteryConfig: vipConfigPDA, lotteryStats: vipLotteryStatsPDA, admin: vipAdmin.publicKey, systemProgram: SystemProgram.programId }).signers([vipAdmin]).rpc();
+    const vipConfig = await program.account.lotteryConfig.fetch(vipConfigPDA);
+    assert.ok(vipConfig.lotteryId.eq(vipLotteryId), "Lottery ID mismatch");
+    assert.ok(vipConfig.adminKey.equals(vipAdmin.publicKey), "Each lottery should have its own admin");
+
+    // Round 1 of the VIP lottery does not collide with round 1 of the main lottery.
+    const roundId = new anchor.BN(1);
+    const [vipRoundPDA, _] = await findLotteryRoundPDA(roundId, vipConfigPDA);
+    const [mainRoundPDA, __] = await findLotteryRoundPDA(roundId);
+    assert.notOk(vipRoundPDA.equals(mainRoundPDA), "Rounds of different lotteries should have different PDAs");
+    try {
+      await program.methods.startNewLotteryRound(new anchor.BN(3600), roundId).accounts({ lotteryConfig: vipConfigPDA, treasury: vipTreasuryPDA, previousRound: findPreviousRoundPDA(roundId, vipConfigPDA), lotteryRound: vipRoundPDA, prizeVault: findPrizeVaultPDA(vipRoundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+      assert.fail("The main lottery's admin should not manage the VIP lottery");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedAdmin", "Error message mismatch for UnauthorizedAdmin");
+    }
+    await program.methods.startNewLotteryRound(new anchor.BN(3600), roundId).accounts({ lotteryConfig: vipConfigPDA, treasury: vipTreasuryPDA, previousRound: findPreviousRoundPDA(roundId, vipConfigPDA), lotteryRound: vipRoundPDA, prizeVault: findPrizeVaultPDA(vipRoundPDA), admin: vipAdmin.publicKey, systemProgram: SystemProgram.programId }).signers([vipAdmin]).rpc();
+    await program.methods.enterLottery().accounts({ lotteryConfig: vipConfigPDA, lotteryRound: vipRoundPDA, prizeVault: findPrizeVaultPDA(vipRoundPDA), lotteryStats: vipLotteryStatsPDA, participant: participant1.publicKey, playerProfile: findPlayerProfilePDA(participant1.publicKey), systemProgram: SystemProgram.programId }).signers([participant1]).rpc();
+
+    const vipRound = await program.account.lotteryRound.fetch(vipRoundPDA);
+    assert.ok(vipRound.lotteryConfig.equals(vipConfigPDA), "Round should record its lottery");
+    assert.ok(vipRound.totalPotLamports.eq(vipEntryFee), "VIP round should charge the VIP entry fee");
+    const mainConfigAfter = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    assert.ok(mainConfigAfter.currentLotteryRoundId.eq(mainConfigBefore.currentLotteryRoundId), "The main lottery should be unaffected");
+    console.log("Independent lotteries verified.");
+  });
+
+  it("Lets anyone create a lottery within the platform bounds and pays the platform fee!", async () => {
+    const creator = Keypair.generate();
+    const creatorFeeReceiver = Keypair.generate();
+    await airdropSol(creator.publicKey, 2);
+    const partnerLotteryId = new anchor.BN(2);
+    const [partnerConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_config"), partnerLotteryId.toBuffer("le", 8)], program.programId);
+    const [partnerTreasuryPDA] = PublicKey.findProgramAddressSync([Buffer.from("treasury"), partnerConfigPDA.toBuffer()], program.programId);
+    const [partnerFeeVaultPDA] = PublicKey.findProgramAddressSync([Buffer.from("fee_vault"), partnerConfigPDA.toBuffer()], program.programId);
+    const [partnerLotteryStatsPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_stats"), partnerConfigPDA.toBuffer()], program.programId);
+    const entryFee = new anchor.BN(0.2 * LAMPORTS_PER_SOL);
+    const createLottery = (fee: anchor.BN, devFeeBps: number, mainWinnerBps: number, uri: string) =>
+      program.methods.createLottery(partnerLotteryId, creatorFeeReceiver.publicKey, fee, devFeeBps, mainWinnerBps, uri).accounts({ lotteryConfig: partnerConfigPDA, platformConfig: platformConfigPDA, creator: creator.publicKey, systemProgram: SystemProgram.programId }).signers([creator]).rpc();
+
+    // Creators must stay within the platform bounds.
+    for (const [fee, devFeeBps, mainWinnerBps, uri, expected] of [
+      [new anchor.BN(10 * LAMPORTS_PER_SOL), 500, 6000, "", "EntryFeeOutOfBounds"],
+      [entryFee, 3000, 6000, "", "InvalidPayoutSplit"],
+      [entryFee, 500, 2000, "", "InvalidPayoutSplit"],
+      [entryFee, 500, 9800, "", "InvalidPayoutSplit"],
+      [entryFee, 500, 6000, "x".repeat(201), "MetadataUriTooLong"],
+    ] as [anchor.BN, number, number, string, string][]) {
+      try {
+        await createLottery(fee, devFeeBps, mainWinnerBps, uri);
+        assert.fail(`Should not have created a lottery violating the bounds (${expected})`);
+      } catch (err) {
+        assert.include(err.toString(), expected, `Error message mismatch for ${expected}`);
+      }
+    }
+
+    const platformBefore = await program.account.platformConfig.fetch(platformConfigPDA);
+    await createLottery(entryFee, 500, 6000, "https://example.com/partner-lottery.json");
+    const partnerConfig = await program.account.lotteryConfig.fetch(partnerConfigPDA);
+    assert.ok(partnerConfig.adminKey.equals(creator.publicKey), "The creator should administer the lottery");
+    assert.equal(partnerConfig.platformFeeBps, 250, "The platform fee should be recorded on the lottery");
+    assert.equal(partnerConfig.devFeeBps, 500, "Dev fee mismatch");
+    assert.equal(partnerConfig.mainWinnerBps, 6000, "Main winner share mismatch");
+    assert.equal(partnerConfig.metadataUri, "https://example.com/partner-lottery.json", "Metadata URI mismatch");
+    const platformAfter = await program.account.platformConfig.fetch(platformConfigPDA);
+    assert.ok(platformAfter.lotteryCount.eq(platformBefore.lotteryCount.addn(1)), "The platform should count the new lottery");
+
+    // The entry fee stays within the platform bounds after creation, and the metadata can be replaced.
+    try {
+      await program.methods.updateConfig(null, new anchor.BN(1000), null).accounts({ lotteryConfig: partnerConfigPDA, admin: creator.publicKey }).signers([creator]).rpc();
+      assert.fail("Should not have lowered the entry fee below the platform bounds");
+    } catch (err) {
+      assert.include(err.toString(), "EntryFeeOutOfBounds", "Error message mismatch for EntryFeeOutOfBounds");
+    }
+    await program.methods.setLotteryMetadata("ipfs://partner-lottery").accounts({ lotteryConfig: partnerConfigPDA, admin: creator.publicKey }).signers([creator]).rpc();
+    assert.equal((await program.account.lotteryConfig.fetch(partnerConfigPDA)).metadataUri, "ipfs://partner-lottery", "Metadata URI should be updated");
+
+    // Run a round and check that the pot is split as chosen by the creator, with the platform fee going to the platform.
+    const roundId = new anchor.BN(1);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId, partnerConfigPDA);
+    await program.methods.initializeTreasury().accounts({ lotteryConfig: partnerConfigPDA, treasury: partnerTreasuryPDA, admin: creator.publicKey, systemProgram: SystemProgram.programId }).signers([creator]).rpc();
+    await program.methods.initializeFeeVault().accounts({ lotteryConfig: partnerConfigPDA, feeVault: partnerFeeVaultPDA, admin: creator.publicKey, systemProgram: SystemProgram.programId }).signers([creator]).rpc();
+    await program.methods.initializeLotteryStats().accounts({ lotteryConfig: partnerConfigPDA, lotteryStats: partnerLotteryStatsPDA, admin: creator.publicKey, systemProgram: SystemProgram.programId }).signers([creator]).rpc();
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: partnerConfigPDA, treasury: partnerTreasuryPDA, previousRound: findPreviousRoundPDA(roundId, partnerConfigPDA), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: creator.publicKey, systemProgram: SystemProgram.programId }).signers([creator]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: partnerConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: partnerLotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: partnerConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: partnerConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: partnerLotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    const roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    const pot = roundAccount.totalPotLamports.toNumber();
+    const winnerAccounts = prizeRecipientAccounts([roundAccount.mainWinner!, ...roundAccount.otherWinners]);
+    const platformBalanceBefore = await provider.connection.getBalance(platformConfigPDA);
+    const mainWinnerBalanceBefore = await provider.connection.getBalance(roundAccount.mainWinner!);
+    await program.methods.distributePrizes().accounts({ lotteryConfig: partnerConfigPDA, treasury: partnerTreasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: partnerLotteryStatsPDA, feeVault: partnerFeeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(winnerAccounts).rpc();
+
+    const platformFee = pot * 250 / 10000;
+    assert.equal(await provider.connection.getBalance(platformConfigPDA) - platformBalanceBefore, platformFee, "The platform should receive its fee");
+    const devFee = pot * 500 / 10000;
+    assert.ok((await program.account.feeVault.fetch(partnerFeeVaultPDA)).totalAccruedLamports.eqn(devFee), "The chosen dev fee should accrue in the fee vault");
+    await program.methods.withdrawFees(new anchor.BN(devFee)).accounts({ lotteryConfig: partnerConfigPDA, feeVault: partnerFeeVaultPDA, devFeeReceiver: creatorFeeReceiver.publicKey, admin: creator.publicKey }).signers([creator]).rpc();
+    assert.equal(await provider.connection.getBalance(creatorFeeReceiver.publicKey), devFee, "The creator should withdraw the chosen dev fee");
+    assert.equal(await provider.connection.getBalance(roundAccount.mainWinner!) - mainWinnerBalanceBefore, pot * 6000 / 10000, "The main winner should receive the chosen share");
+
+    // Only the platform authority can withdraw the collected fees.
+    try {
+      await program.methods.withdrawPlatformFees(new anchor.BN(platformFee)).accounts({ platformConfig: platformConfigPDA, authority: creator.publicKey }).signers([creator]).rpc();
+      assert.fail("Should not have let the creator withdraw platform fees");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedPlatformAuthority", "Error message mismatch for UnauthorizedPlatformAuthority");
+    }
+    await program.methods.withdrawPlatformFees(new anchor.BN(platformFee)).accounts({ platformConfig: platformConfigPDA, authority: admin.publicKey }).signers([admin]).rpc();
+    assert.equal(await provider.connection.getBalance(platformConfigPDA), platformBalanceBefore, "The platform fees should be withdrawn");
+    console.log("Lottery factory and platform fee verified.");
+  });
+
+  it("Keeps the rules a round started with when the config changes mid-round!", async () => {
+    // Round 12 was distributed; round 13 records the entry fee, prize split, crank bounty and dispute window in effect when it starts.
+    const roundId = new anchor.BN(13);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    const configAtStart = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    const roundAtStart = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(roundAtStart.entryFeeLamports.eq(configAtStart.entryFeeLamports), "The round should record the entry fee");
+    assert.equal(roundAtStart.devFeeBps, configAtStart.devFeeBps, "The round should record the split");
+    assert.equal(roundAtStart.mainWinnerBps, configAtStart.mainWinnerBps, "The round should record the split");
+    assert.ok(roundAtStart.crankBountyCapLamports.eq(configAtStart.crankBountyCapLamports), "The round should record the crank bounty");
+    assert.ok(roundAtStart.disputeWindowSeconds.eq(configAtStart.disputeWindowSeconds), "The round should record the dispute window");
+
+    // The admin changes the entry fee and the dev fee receiver while the round is running.
+    const laterDevFeeReceiver = Keypair.generate();
+    await program.methods.updateConfig(laterDevFeeReceiver.publicKey, configAtStart.entryFeeLamports.muln(2), null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    // ... and introduces a crank bounty and a dispute window, which only apply from the next round on.
+    await program.methods.setCrankBounty(new anchor.BN(0.001 * LAMPORTS_PER_SOL), 100, new anchor.BN(0.005 * LAMPORTS_PER_SOL)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.setDisputeConfig(null, new anchor.BN(3600)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    const roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(roundAccount.totalPotLamports.eq(configAtStart.entryFeeLamports.muln(5)), "Participants should pay the entry fee the round started with");
+
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(drawnRound.drawBountyLamports.eqn(0), "The draw should pay the bounty the round started with");
+    const winnerAccounts = prizeRecipientAccounts([drawnRound.mainWinner!, ...drawnRound.otherWinners]);
+    const accruedBefore = (await program.account.feeVault.fetch(feeVaultPDA)).totalAccruedLamports;
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(winnerAccounts).rpc();
+
+    // The dev fee still follows the split the round started with.
+    const expectedDevFee = drawnRound.totalPotLamports.muln(configAtStart.devFeeBps).divn(10000);
+    const accruedAfter = (await program.account.feeVault.fetch(feeVaultPDA)).totalAccruedLamports;
+    assert.ok(accruedAfter.sub(accruedBefore).eq(expectedDevFee), "Dev fee should follow the round's split");
+    // The prizes were paid right after the draw, without the new dispute window, and without a distribution bounty.
+    assert.ok((await program.account.lotteryRound.fetch(roundPDA)).distributionBountyLamports.eqn(0), "The distribution should pay the bounty the round started with");
+
+    // Restore the config for any later tests.
+    await program.methods.setCrankBounty(new anchor.BN(0), 0, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.setDisputeConfig(null, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.updateConfig(configAtStart.devFeeReceiver, configAtStart.entryFeeLamports, null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Per-round parameter snapshot verified.");
+  });
+
+  it("Splits the dev fee between weighted beneficiaries!", async () => {
+    const [feeSplitPDA] = PublicKey.findProgramAddressSync([Buffer.from("fee_split"), lotteryConfigPDA.toBuffer()], program.programId);
+    const beneficiaries = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const table = beneficiaries.map((b, i) => ({ address: b.publicKey, weightBps: [5000, 3000, 2000][i] }));
+    const configBefore = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+
+    // Weights must add up to the whole dev fee.
+    try {
+      await program.methods.initializeFeeSplit(table.map(b => ({ ...b, weightBps: 3000 }))).accounts({ lotteryConfig: lotteryConfigPDA, feeSplit: feeSplitPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+      assert.fail("Should not have accepted weights that do not add up to 10,000 bps");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidFeeBeneficiaries", "Error message mismatch for InvalidFeeBeneficiaries");
+    }
+    await program.methods.initializeFeeSplit(table).accounts({ lotteryConfig: lotteryConfigPDA, feeSplit: feeSplitPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    assert.ok((await program.account.lotteryConfig.fetch(lotteryConfigPDA)).devFeeReceiver.equals(feeSplitPDA), "The fee split should become the dev fee receiver");
+
+    // The dev fee of round 14 is withdrawn through the fee split.
+    const roundId = new anchor.BN(14);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    const winnerAccounts = prizeRecipientAccounts([drawnRound.mainWinner!, ...drawnRound.otherWinners]);
+    const beneficiaryAccounts = beneficiaries.map(b => ({ pubkey: b.publicKey, isSigner: false, isWritable: true }));
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(winnerAccounts).rpc();
+    const distributedRound = await program.account.lotteryRound.fetch(roundPDA);
+    const devShare = drawnRound.totalPotLamports.sub(distributedRound.distributionBountyLamports).muln(distributedRound.devFeeBps).divn(10000);
+    const withdraw = (feeAccounts: { pubkey: PublicKey; isSigner: boolean; isWritable: boolean }[]) =>
+      program.methods.withdrawFees(devShare).accounts({ lotteryConfig: lotteryConfigPDA, feeVault: feeVaultPDA, devFeeReceiver: feeSplitPDA, admin: admin.publicKey }).remainingAccounts(feeAccounts).signers([admin]).rpc();
+
+    // Beneficiaries must be passed in table order.
+    try {
+      await withdraw([...beneficiaryAccounts].reverse());
+      assert.fail("Should not have paid beneficiaries passed out of order");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectFeeBeneficiaryAccounts", "Error message mismatch for IncorrectFeeBeneficiaryAccounts");
+    }
+    await withdraw(beneficiaryAccounts);
+    const paid = await Promise.all(beneficiaries.map(b => provider.connection.getBalance(b.publicKey)));
+    const second = devShare.muln(3000).divn(10000).toNumber();
+    const third = devShare.muln(2000).divn(10000).toNumber();
+    assert.equal(paid[1], second, "Second beneficiary should receive 30% of the dev fee");
+    assert.equal(paid[2], third, "Third beneficiary should receive 20% of the dev fee");
+    assert.equal(paid[0], devShare.toNumber() - second - third, "First beneficiary should receive 50% plus any rounding remainder");
+
+    // Point the dev fee back at the single receiver for any later tests.
+    await program.methods.updateConfig(configBefore.devFeeReceiver, null, null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Dev fee split verified.");
+  });
+
+  it("Withdraws accrued dev fees within the withdrawal limit!", async () => {
+    const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    const withdraw = (amount: anchor.BN, signer: Keypair = admin) =>
+      program.methods.withdrawFees(amount).accounts({ lotteryConfig: lotteryConfigPDA, feeVault: feeVaultPDA, devFeeReceiver: configAccount.devFeeReceiver, admin: signer.publicKey }).signers([signer]).rpc();
+    const vaultBefore = await program.account.feeVault.fetch(feeVaultPDA);
+    const available = vaultBefore.totalAccruedLamports.sub(vaultBefore.totalWithdrawnLamports);
+    assert.ok(available.gtn(2), "Earlier rounds should have accrued dev fees");
+
+    // Only the admin can withdraw, and never more than has accrued.
+    try {
+      await withdraw(new anchor.BN(1), participant1);
+      assert.fail("Should not have let a non-admin withdraw fees");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedAdmin", "Error message mismatch for UnauthorizedAdmin");
+    }
+    try {
+      await withdraw(available.addn(1));
+      assert.fail("Should not have withdrawn more than has accrued");
+    } catch (err) {
+      assert.include(err.toString(), "InsufficientFees", "Error message mismatch for InsufficientFees");
+    }
+
+    // With a limit of half the available fees per hour, only that much can be withdrawn now.
+    const limit = available.divn(2);
+    await program.methods.setFeeWithdrawalLimit(limit, new anchor.BN(3600)).accounts({ lotteryConfig: lotteryConfigPDA, feeVault: feeVaultPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    const receiverBalanceBefore = await provider.connection.getBalance(configAccount.devFeeReceiver);
+    await withdraw(limit);
+    assert.equal(await provider.connection.getBalance(configAccount.devFeeReceiver) - receiverBalanceBefore, limit.toNumber(), "The dev fee receiver should receive the withdrawn fees");
+    try {
+      await withdraw(new anchor.BN(1));
+      assert.fail("Should not have withdrawn past the period's limit");
+    } catch (err) {
+      assert.include(err.toString(), "FeeWithdrawalLimitExceeded", "Error message mismatch for FeeWithdrawalLimitExceeded");
+    }
+    const vaultAfter = await program.account.feeVault.fetch(feeVaultPDA);
+    assert.ok(vaultAfter.totalWithdrawnLamports.eq(vaultBefore.totalWithdrawnLamports.add(limit)), "The vault should record the withdrawal");
+    assert.ok(vaultAfter.periodWithdrawnLamports.eq(limit), "The vault should record the period's withdrawals");
+
+    // Removing the limit allows the rest to be withdrawn.
+    await program.methods.setFeeWithdrawalLimit(new anchor.BN(0), new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, feeVault: feeVaultPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await withdraw(available.sub(limit));
+    const vaultEmptied = await program.account.feeVault.fetch(feeVaultPDA);
+    assert.ok(vaultEmptied.totalWithdrawnLamports.eq(vaultEmptied.totalAccruedLamports), "All accrued fees should be withdrawn");
+    console.log("Fee vault withdrawals verified.");
+  });
+
+  it("Rejects dev fee receivers that cannot safely receive fees!", async () => {
+    const configBefore = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    const [currentRoundPDA, _] = await findLotteryRoundPDA(configBefore.currentLotteryRoundId);
+    const setReceiver = (receiver: PublicKey) =>
+      program.methods.updateConfig(receiver, null, null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+
+    // The program and the system program are rejected when the receiver is set.
+    for (const receiver of [program.programId, SystemProgram.programId]) {
+      try {
+        await setReceiver(receiver);
+        assert.fail(`Should not have accepted ${receiver.toBase58()} as the dev fee receiver`);
+      } catch (err) {
+        assert.include(err.toString(), "InvalidDevFeeReceiver", "Error message mismatch for InvalidDevFeeReceiver");
+      }
+    }
+    const [feeSplitPDA] = PublicKey.findProgramAddressSync([Buffer.from("fee_split"), lotteryConfigPDA.toBuffer()], program.programId);
+    try {
+      await program.methods.setFeeBeneficiaries([{ address: program.programId, weightBps: 10000 }]).accounts({ lotteryConfig: lotteryConfigPDA, feeSplit: feeSplitPDA, admin: admin.publicKey }).signers([admin]).rpc();
+      assert.fail("Should not have accepted the program as a fee beneficiary");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidFeeBeneficiaries", "Error message mismatch for InvalidFeeBeneficiaries");
+    }
+    assert.ok((await program.account.lotteryConfig.fetch(lotteryConfigPDA)).devFeeReceiver.equals(configBefore.devFeeReceiver), "The dev fee receiver should be unchanged");
+
+    // The lottery's own accounts and other programs' accounts are rejected when fees are paid, before anything is paid.
+    const vaultBefore = await program.account.feeVault.fetch(feeVaultPDA);
+    const withdrawTo = (receiver: PublicKey) =>
+      program.methods.withdrawFees(new anchor.BN(1)).accounts({ lotteryConfig: lotteryConfigPDA, feeVault: feeVaultPDA, devFeeReceiver: receiver, admin: admin.publicKey }).signers([admin]).rpc();
+    for (const receiver of [lotteryConfigPDA, treasuryPDA, feeVaultPDA, currentRoundPDA]) {
+      await setReceiver(receiver);
+      try {
+        await withdrawTo(receiver);
+        assert.fail(`Should not have paid fees to ${receiver.toBase58()}`);
+      } catch (err) {
+        assert.include(err.toString(), "IncorrectDevFeeReceiver", "Error message mismatch for IncorrectDevFeeReceiver");
+      }
+    }
+    const foreignAccount = Keypair.generate();
+    await provider.sendAndConfirm(new web3.Transaction().add(SystemProgram.createAccount({ fromPubkey: admin.publicKey, newAccountPubkey: foreignAccount.publicKey, lamports: await provider.connection.getMinimumBalanceForRentExemption(0), space: 0, programId: Keypair.generate().publicKey })), [admin, foreignAccount]);
+    await setReceiver(foreignAccount.publicKey);
+    try {
+      await withdrawTo(foreignAccount.publicKey);
+      assert.fail("Should not have paid fees to an account owned by another program");
+    } catch (err) {
+      assert.include(err.toString(), "FeeRecipientNotSystemOwned", "Error message mismatch for FeeRecipientNotSystemOwned");
+    }
+
+    // A receiver that would not be rent-exempt after the payment is detected before anything is paid.
+    const emptyReceiver = Keypair.generate();
+    await setReceiver(emptyReceiver.publicKey);
+    try {
+      await withdrawTo(emptyReceiver.publicKey);
+      assert.fail("Should not have paid a receiver left below the rent-exempt minimum");
+    } catch (err) {
+      assert.include(err.toString(), "FeeRecipientNotRentExempt", "Error message mismatch for FeeRecipientNotRentExempt");
+    }
+    const vaultAfter = await program.account.feeVault.fetch(feeVaultPDA);
+    assert.ok(vaultAfter.totalWithdrawnLamports.eq(vaultBefore.totalWithdrawnLamports), "No fees should have been withdrawn");
+
+    // Point the dev fee back at the original receiver for any later tests.
+    await setReceiver(configBefore.devFeeReceiver);
+    console.log("Dev fee receiver validation verified.");
+  });
+
+  it("Pays out the whole pot and reconciles round balances!", async () => {
+    // Round 15 gets an odd sponsor contribution so that the split leaves a rounding remainder.
+    const roundId = new anchor.BN(15);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await program.methods.contributeToPot(new anchor.BN(7), null).accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), contributor: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+
+    // The prize vault holds exactly its rent-exempt minimum plus the pot.
+    const prizeVaultPDA = findPrizeVaultPDA(roundPDA);
+    const rentExemptMinimum = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const openRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.equal(await provider.connection.getBalance(prizeVaultPDA), rentExemptMinimum + openRound.totalPotLamports.toNumber(), "The vault should hold its rent plus the pot");
+
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    const winnerAccounts = prizeRecipientAccounts([drawnRound.mainWinner!, ...drawnRound.otherWinners]);
+    const mainWinnerBalanceBefore = await provider.connection.getBalance(drawnRound.mainWinner!);
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(winnerAccounts).rpc();
+
+    // Nothing is left behind: the main winner receives the rounding remainder.
+    const distributedRound = await program.account.lotteryRound.fetch(roundPDA);
+    const pot = distributedRound.totalPotLamports;
+    const share = (bps: number) => pot.muln(bps).divn(10000);
+    const otherWinnerBps = 10000 - distributedRound.platformFeeBps - distributedRound.devFeeBps - distributedRound.mainWinnerBps;
+    const mainWinnerShare = pot.sub(share(distributedRound.platformFeeBps)).sub(share(distributedRound.devFeeBps)).sub(share(otherWinnerBps).muln(4));
+    assert.ok(mainWinnerShare.gt(share(distributedRound.mainWinnerBps)), "The split should leave a remainder for the main winner");
+    const topUp = anchor.BN.max(distributedRound.guaranteedMainPrizeLamports.sub(mainWinnerShare), new anchor.BN(0));
+    assert.equal(await provider.connection.getBalance(drawnRound.mainWinner!) - mainWinnerBalanceBefore, mainWinnerShare.add(topUp).toNumber(), "The main winner should receive the remainder");
+    assert.equal(await provider.connection.getBalance(prizeVaultPDA), rentExemptMinimum, "Only the rent should be left in the vault");
+
+    // Lamports sent straight to the vault are a surplus that reconciliation moves to the treasury.
+    const surplus = 5000;
+    await provider.sendAndConfirm(new web3.Transaction().add(SystemProgram.transfer({ fromPubkey: admin.publicKey, toPubkey: prizeVaultPDA, lamports: surplus })), [admin]);
+    const treasuryBalanceBefore = await provider.connection.getBalance(treasuryPDA);
+    await program.methods.reconcileRound(roundId).accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), treasury: treasuryPDA }).rpc();
+    assert.equal(await provider.connection.getBalance(prizeVaultPDA), rentExemptMinimum, "The surplus should leave the vault");
+    assert.equal(await provider.connection.getBalance(treasuryPDA) - treasuryBalanceBefore, surplus, "The treasury should receive the surplus");
+    console.log("Exact pot accounting verified.");
+  });
+
+  it("Keeps each round's pot in its own prize vault!", async () => {
+    // Round 16 is cancelled after one entry, so its pot is refunded from the vault before the round is closed.
+    const roundId = new anchor.BN(16);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const prizeVaultPDA = findPrizeVaultPDA(roundPDA);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: prizeVaultPDA, admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+
+    // The vault is a plain System Program account without data, funded with its rent by the admin.
+    const vaultRent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultInfo = await provider.connection.getAccountInfo(prizeVaultPDA);
+    assert.ok(vaultInfo!.owner.equals(SystemProgram.programId), "The vault should be owned by the System Program");
+    assert.equal(vaultInfo!.data.length, 0, "The vault should hold no data");
+    assert.equal(vaultInfo!.lamports, vaultRent, "The vault should start with only its rent");
+
+    // Tickets are paid into the vault; the round data account keeps only its own rent.
+    const roundLamports = await provider.connection.getBalance(roundPDA);
+    await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: prizeVaultPDA, lotteryStats: lotteryStatsPDA, participant: participant1.publicKey, playerProfile: findPlayerProfilePDA(participant1.publicKey), systemProgram: SystemProgram.programId }).signers([participant1]).rpc();
+    const round = await program.account.lotteryRound.fetch(roundPDA);
+    assert.equal(await provider.connection.getBalance(roundPDA), roundLamports, "The round account should not receive the entry fee");
+    assert.equal(await provider.connection.getBalance(prizeVaultPDA), vaultRent + round.entryFeeLamports.toNumber(), "The vault should hold the entry fee");
+
+    // Cancel the under-subscribed round by running out of extensions, then refund the participant out of the vault.
+    await program.methods.setRoundExtensions(new anchor.BN(1), 1).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    const extendRound = () => program.methods.extendRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for the draw time to pass.
+    await extendRound();
+    await new Promise(resolve => setTimeout(resolve, 2000)); // Wait for the extended draw time to pass.
+    await extendRound();
+    const participantBalanceBefore = await provider.connection.getBalance(participant1.publicKey);
+    await program.methods
+      .refundParticipants(roundId)
+      .accounts({ lotteryRound: roundPDA, prizeVault: prizeVaultPDA })
+      .remainingAccounts([{ pubkey: participant1.publicKey, isSigner: false, isWritable: true }])
+      .rpc();
+    assert.equal(await provider.connection.getBalance(participant1.publicKey) - participantBalanceBefore, round.entryFeeLamports.toNumber(), "The refund should come out of the vault");
+    assert.equal(await provider.connection.getBalance(roundPDA), roundLamports, "The round account should not pay the refund");
+    assert.equal(await provider.connection.getBalance(prizeVaultPDA), vaultRent, "Only the rent should be left in the vault");
+
+    // Closing the round returns the rent of both accounts to the admin.
+    const [roundResultPDA] = PublicKey.findProgramAddressSync([Buffer.from("round_result"), lotteryConfigPDA.toBuffer(), roundId.toBuffer("le", 8)], program.programId);
+    const adminBalanceBefore = await provider.connection.getBalance(admin.publicKey);
+    await program.methods.closeRound(roundId).accounts({ lotteryRound: roundPDA, prizeVault: prizeVaultPDA, roundResult: roundResultPDA, rentPayer: admin.publicKey, payer: provider.wallet.publicKey, systemProgram: SystemProgram.programId }).rpc();
+    assert.isNull(await provider.connection.getAccountInfo(roundPDA), "LotteryRound account should be closed");
+    assert.isNull(await provider.connection.getAccountInfo(prizeVaultPDA), "Prize vault should be emptied");
+    assert.equal(await provider.connection.getBalance(admin.publicKey) - adminBalanceBefore, roundLamports + vaultRent, "The admin should get back the rent of the round and its vault");
+
+    // Disable extensions again for any later tests.
+    await program.methods.setRoundExtensions(new anchor.BN(0), 0).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Prize vault separation verified.");
+  });
+
+  it("Keeps lifetime lottery statistics!", async () => {
+    const roundId = new anchor.BN(17);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    const statsBefore = await program.account.lotteryStats.fetch(lotteryStatsPDA);
+    assert.ok(statsBefore.lotteryConfig.equals(lotteryConfigPDA), "The stats should record their lottery");
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+
+    // Every ticket sold is counted.
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    const statsAfterSales = await program.account.lotteryStats.fetch(lotteryStatsPDA);
+    assert.ok(statsAfterSales.totalTicketsSold.eq(statsBefore.totalTicketsSold.addn(5)), "The 5 tickets should be counted");
+
+    // The draw is counted.
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    const statsAfterDraw = await program.account.lotteryStats.fetch(lotteryStatsPDA);
+    assert.ok(statsAfterDraw.totalRoundsDrawn.eq(statsBefore.totalRoundsDrawn.addn(1)), "The drawn round should be counted");
+
+    // The distribution records what the winners and the fee vault received.
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    const winners = [drawnRound.mainWinner!, ...drawnRound.otherWinners];
+    const winnerBalancesBefore = await Promise.all(winners.map(w => provider.connection.getBalance(w)));
+    const accruedBefore = (await program.account.feeVault.fetch(feeVaultPDA)).totalAccruedLamports;
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(prizeRecipientAccounts(winners)).rpc();
+    const winnerGains = (await Promise.all(winners.map(w => provider.connection.getBalance(w)))).map((balance, i) => balance - winnerBalancesBefore[i]);
+    const accruedAfter = (await program.account.feeVault.fetch(feeVaultPDA)).totalAccruedLamports;
+
+    const stats = await program.account.lotteryStats.fetch(lotteryStatsPDA);
+    assert.equal(stats.totalPaidToWinnersLamports.sub(statsBefore.totalPaidToWinnersLamports).toNumber(), winnerGains.reduce((a, b) => a + b, 0), "Paid prizes mismatch");
+    assert.ok(stats.totalDevFeesLamports.sub(statsBefore.totalDevFeesLamports).eq(accruedAfter.sub(accruedBefore)), "Dev fees should match the fee vault");
+    if (winnerGains[0] > statsBefore.largestJackpotLamports.toNumber()) {
+      assert.equal(stats.largestJackpotLamports.toNumber(), winnerGains[0], "The main prize should be the new largest jackpot");
+      assert.ok(stats.largestJackpotRoundId.eq(roundId), "The jackpot should be attributed to this round");
+    } else {
+      assert.ok(stats.largestJackpotLamports.eq(statsBefore.largestJackpotLamports), "The largest jackpot should be unchanged");
+    }
+    console.log("Lottery statistics verified.");
+  });
+
+  it("Keeps player profiles and pays prizes to the preferred payout address!", async () => {
+    const roundId = new anchor.BN(18);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    const enter = (p: Keypair) => program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    const setPayoutAddress = (p: Keypair, address: PublicKey | null) => program.methods.setPayoutAddress(address).accounts({ playerProfile: findPlayerProfilePDA(p.publicKey), player: p.publicKey, payoutAccount: address }).signers([p]).rpc();
+
+    // A new player has no profile and cannot set preferences until their first entry creates it.
+    const newPlayer = Keypair.generate();
+    const coldWallet = Keypair.generate();
+    await airdropSol(newPlayer.publicKey, 2);
+    await airdropSol(coldWallet.publicKey, 1);
+    assert.isNull(await provider.connection.getAccountInfo(findPlayerProfilePDA(newPlayer.publicKey)), "A new player should have no profile");
+    try {
+      await setPayoutAddress(newPlayer, coldWallet.publicKey);
+      assert.fail("Should not have set a payout address without a profile");
+    } catch (err) {
+      assert.include(err.toString(), "AccountNotInitialized", "Error message mismatch for AccountNotInitialized");
+    }
+
+    // The first entry creates the profile, paid for by the player.
+    const balanceBefore = await provider.connection.getBalance(newPlayer.publicKey);
+    await enter(newPlayer);
+    const round = await program.account.lotteryRound.fetch(roundPDA);
+    const profileRent = await provider.connection.getBalance(findPlayerProfilePDA(newPlayer.publicKey));
+    assert.equal(balanceBefore - await provider.connection.getBalance(newPlayer.publicKey), round.entryFeeLamports.toNumber() + profileRent, "The player should pay the entry fee and the profile rent");
+    const newProfile = await program.account.playerProfile.fetch(findPlayerProfilePDA(newPlayer.publicKey));
+    assert.ok(newProfile.player.equals(newPlayer.publicKey), "The profile should belong to the player");
+    assert.ok(newProfile.roundsEntered.eqn(1) && newProfile.ticketsBought.eqn(1), "The first entry should be recorded");
+    assert.ok(newProfile.lastLotteryConfig.equals(lotteryConfigPDA) && newProfile.lastRoundId.eq(roundId), "The last round played should be recorded");
+    assert.isNull(newProfile.preferredPayoutAddress, "Prizes should go to the wallet by default");
+
+    // Returning players keep their history.
+    const entrants = [newPlayer, participant1, participant2, participant3, participant4];
+    const historyBefore = await program.account.playerProfile.fetch(findPlayerProfilePDA(participant1.publicKey));
+    for (const p of entrants.slice(1)) {
+      await enter(p);
+    }
+    const history = await program.account.playerProfile.fetch(findPlayerProfilePDA(participant1.publicKey));
+    assert.ok(history.roundsEntered.eq(historyBefore.roundsEntered.addn(1)), "The entry should be added to the player's history");
+    assert.ok(history.lastRoundId.eq(roundId), "The last round played should be updated");
+
+    // Every entrant sends their prizes to the cold wallet. The program itself is not a valid payout address.
+    try {
+      await setPayoutAddress(newPlayer, program.programId);
+      assert.fail("Should not have accepted the program as payout address");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidPayoutAddress", "Error message mismatch for InvalidPayoutAddress");
+    }
+    for (const p of entrants) {
+      await setPayoutAddress(p, coldWallet.publicKey);
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    const winners = [drawnRound.mainWinner!, ...drawnRound.otherWinners];
+    const distribute = (payoutAddresses: PublicKey[]) => program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(prizeRecipientAccounts(winners, payoutAddresses)).rpc();
+
+    // Paying a winner's wallet instead of their payout address is rejected.
+    try {
+      await distribute(winners);
+      assert.fail("Should not have paid the main winner's wallet instead of the payout address");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectMainWinnerAccount", "Error message mismatch for IncorrectMainWinnerAccount");
+    }
+
+    const walletBalancesBefore = await Promise.all(winners.map(w => provider.connection.getBalance(w)));
+    const profilesBefore = await Promise.all(winners.map(w => program.account.playerProfile.fetch(findPlayerProfilePDA(w))));
+    const coldWalletBefore = await provider.connection.getBalance(coldWallet.publicKey);
+    await distribute(winners.map(() => coldWallet.publicKey));
+    const profilesAfter = await Promise.all(winners.map(w => program.account.playerProfile.fetch(findPlayerProfilePDA(w))));
+    const winnings = profilesAfter.map((profile, i) => profile.lifetimeWinningsLamports.sub(profilesBefore[i].lifetimeWinningsLamports).toNumber());
+    assert.equal(await provider.connection.getBalance(coldWallet.publicKey) - coldWalletBefore, winnings.reduce((a, b) => a + b, 0), "The cold wallet should receive every prize");
+    assert.deepEqual(await Promise.all(winners.map(w => provider.connection.getBalance(w))), walletBalancesBefore, "The winners' wallets should not be paid");
+    assert.ok(winnings.every(w => w > 0), "Every winner's lifetime winnings should grow");
+
+    // Clear the payout addresses again for any later tests.
+    for (const p of entrants) {
+      await setPayoutAddress(p, null);
+    }
+    assert.isNull((await program.account.playerProfile.fetch(findPlayerProfilePDA(newPlayer.publicKey))).preferredPayoutAddress, "The payout address should be cleared");
+    console.log("Player profiles verified.");
+  });
+
+  it("Migrates the legacy config and a legacy round from their original addresses!", async function () {
+    // The legacy accounts are loaded from tests/fixtures by the local test validator (see Anchor.toml).
+    const [legacyConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_config")], program.programId);
+    if ((await provider.connection.getAccountInfo(legacyConfigPDA)) === null) {
+      console.log("Legacy fixtures not loaded on this cluster; skipping the migration test.");
+      this.skip();
+    }
+    const legacyAdmin = Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync("tests/fixtures/legacy_admin.json", "utf8"))));
+    await airdropSol(legacyAdmin.publicKey, 2);
+
+    // The legacy lottery continues as lottery 3, at round 7 where the fixture config left off.
+    const legacyLotteryId = new anchor.BN(3);
+    const [migratedConfigPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_config"), legacyLotteryId.toBuffer("le", 8)], program.programId);
+    await program.methods
+      .migrateConfig(legacyLotteryId)
+      .accounts({ legacyLotteryConfig: legacyConfigPDA, lotteryConfig: migratedConfigPDA, admin: legacyAdmin.publicKey, systemProgram: SystemProgram.programId })
+      .signers([legacyAdmin])
+      .rpc();
+    const migratedConfig = await program.account.lotteryConfig.fetch(migratedConfigPDA);
+    assert.ok(migratedConfig.adminKey.equals(legacyAdmin.publicKey), "Admin should carry over");
+    assert.ok(migratedConfig.entryFeeLamports.eq(new anchor.BN(0.1 * LAMPORTS_PER_SOL)), "Entry fee should carry over");
+    assert.ok(migratedConfig.currentLotteryRoundId.eq(new anchor.BN(7)), "Round counter should carry over");
+    assert.isTrue(migratedConfig.isLegacyLottery, "Migrated config should be the legacy lottery");
+    assert.isNull(await provider.connection.getAccountInfo(legacyConfigPDA), "Legacy config should be closed");
+
+    // Round 7 was drawn but not distributed by the legacy program; its 0.5 SOL pot sits in the round account.
+    const roundId = new anchor.BN(7);
+    const [legacyRoundPDA] = PublicKey.findProgramAddressSync([Buffer.from("lottery_round"), roundId.toBuffer("le", 8)], program.programId);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId, migratedConfigPDA);
+    const prizeVaultPDA = findPrizeVaultPDA(roundPDA);
+    const migrateAccounts = { lotteryConfig: migratedConfigPDA, legacyLotteryRound: legacyRoundPDA, lotteryRound: roundPDA, prizeVault: prizeVaultPDA, admin: legacyAdmin.publicKey, systemProgram: SystemProgram.programId };
+    await program.methods.migrateRound(roundId).accounts(migrateAccounts).signers([legacyAdmin]).rpc();
+
+    const round = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(round.status, { drawn: {} }, "Legacy flags should map to Drawn");
+    assert.ok(round.lotteryConfig.equals(migratedConfigPDA), "Round should belong to the migrated lottery");
+    assert.equal(round.participants.length, 5, "Participants should carry over");
+    assert.ok(round.mainWinner.equals(round.participants[2]), "Main winner should carry over");
+    assert.equal(round.otherWinners.length, 4, "Other winners should carry over");
+    assert.ok(round.totalPotLamports.eq(new anchor.BN(0.5 * LAMPORTS_PER_SOL)), "Pot should carry over");
+    const vaultRent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(await provider.connection.getBalance(prizeVaultPDA), vaultRent + 0.5 * LAMPORTS_PER_SOL, "The prize vault should hold the whole pot");
+    assert.isNull(await provider.connection.getAccountInfo(legacyRoundPDA), "Legacy round should be closed");
+
+    try {
+      await program.methods.migrateRound(roundId).accounts(migrateAccounts).signers([legacyAdmin]).rpc();
+      assert.fail("Should not have been able to migrate the round twice");
+    } catch (err) {
+      assert.include(err.toString(), "RoundAlreadyMigrated", "Error message mismatch for RoundAlreadyMigrated");
+    }
+    console.log("Legacy config and round migration verified.");
+  });
+
+  it("Does not start a scheduled round over a drawn round that is not paid out!", async () => {
+    // Round 19 is drawn but its prizes are not distributed yet.
+    const roundId = new anchor.BN(19);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    await program.methods.setRoundSchedule(new anchor.BN(2), new anchor.BN(2)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.fundRentReserve(new anchor.BN(LAMPORTS_PER_SOL)).accounts({ lotteryConfig: lotteryConfigPDA, funder: provider.wallet.publicKey, systemProgram: SystemProgram.programId }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for the cadence to elapse since round 19 started.
+
+    const nextRoundId = new anchor.BN(20);
+    const [nextRoundPDA, __] = await findLotteryRoundPDA(nextRoundId);
+    const startScheduled = async (id: anchor.BN) => {
+      const [newRoundPDA] = await findLotteryRoundPDA(id);
+      await program.methods.startScheduledRound(id).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: roundPDA, lotteryRound: newRoundPDA, prizeVault: findPrizeVaultPDA(newRoundPDA), systemProgram: SystemProgram.programId }).rpc();
+    };
+    try {
+      await startScheduled(nextRoundId);
+      assert.fail("Should not have been able to start a round over a drawn round");
+    } catch (err) {
+      assert.include(err.toString(), "PreviousRoundNotFinished", "Error message mismatch for PreviousRoundNotFinished");
+    }
+    const roundAccount = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(roundAccount.status, { drawn: {} }, "Round 19 should still be drawn");
+    const configAccount = await program.account.lotteryConfig.fetch(lotteryConfigPDA);
+    assert.ok(configAccount.currentLotteryRoundId.eq(roundId), "Round 19 should still be the current round");
+
+    // Once round 19 is paid out, round 20 can start; the round ID argument must be the next one.
+    const winners = [roundAccount.mainWinner!, ...roundAccount.otherWinners];
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(prizeRecipientAccounts(winners)).rpc();
+    try {
+      await startScheduled(new anchor.BN(21));
+      assert.fail("Should not have been able to start a round with the wrong round ID");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectNextRoundIdArg", "Error message mismatch for IncorrectNextRoundIdArg");
+    }
+    await startScheduled(nextRoundId);
+    assert.deepEqual((await program.account.lotteryRound.fetch(nextRoundPDA)).status, { open: {} }, "Round 20 should be open");
+    await cancelUnderSubscribedRound(nextRoundPDA); // Round 20 draws no entries.
+
+    // Disable the schedule again for any later tests.
+    await program.methods.setRoundSchedule(new anchor.BN(0), new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Scheduled start over a drawn round rejected.");
+  });
+
+  it("Pays prizes that a payout address cannot receive to the winner's wallet!", async () => {
+    const setPayoutAddress = (p: Keypair, address: PublicKey | null) => program.methods.setPayoutAddress(address).accounts({ playerProfile: findPlayerProfilePDA(p.publicKey), player: p.publicKey, payoutAccount: address }).signers([p]).rpc();
+
+    // Sysvars and executable accounts can never receive prizes, so they are rejected up front.
+    const bpfLoaderUpgradeable = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+    for (const address of [SYSVAR_CLOCK_PUBKEY, bpfLoaderUpgradeable]) {
+      try {
+        await setPayoutAddress(participant1, address);
+        assert.fail(`Should not have accepted ${address.toBase58()} as payout address`);
+      } catch (err) {
+        assert.include(err.toString(), "InvalidPayoutAddress", "Error message mismatch for InvalidPayoutAddress");
+      }
+    }
+    try {
+      await program.methods.setPayoutAddress(Keypair.generate().publicKey).accounts({ playerProfile: findPlayerProfilePDA(participant1.publicKey), player: participant1.publicKey, payoutAccount: participant2.publicKey }).signers([participant1]).rpc();
+      assert.fail("Should not have accepted a payout account other than the payout address");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectPayoutAccount", "Error message mismatch for IncorrectPayoutAccount");
+    }
+
+    // With 0.001 SOL tickets the other winners' prizes are below the rent-exempt minimum of a new account, so payout
+    // addresses that do not exist yet cannot receive them. The main prize is large enough.
+    await program.methods.updateConfig(null, new anchor.BN(1_000_000), null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    const roundId = new anchor.BN(21);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    const entrants = [participant1, participant2, participant3, participant4, participant5];
+    const newAddresses = entrants.map(() => Keypair.generate().publicKey);
+    for (const [i, p] of entrants.entries()) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+      await setPayoutAddress(p, newAddresses[i]);
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    const winners = [drawnRound.mainWinner!, ...drawnRound.otherWinners];
+    const payoutAddresses = winners.map(w => newAddresses[entrants.findIndex(p => p.publicKey.equals(w))]);
+    const walletBalancesBefore = await Promise.all(winners.map(w => provider.connection.getBalance(w)));
+    const profilesBefore = await Promise.all(winners.map(w => program.account.playerProfile.fetch(findPlayerProfilePDA(w))));
+    await program.methods.distributePrizes().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, platformConfig: platformConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, feeVault: feeVaultPDA, keeper: provider.wallet.publicKey }).remainingAccounts(prizeRecipientAccounts(winners, payoutAddresses)).rpc();
+
+    assert.deepEqual((await program.account.lotteryRound.fetch(roundPDA)).status, { distributed: {} }, "Round should be distributed");
+    const profilesAfter = await Promise.all(winners.map(w => program.account.playerProfile.fetch(findPlayerProfilePDA(w))));
+    const winnings = profilesAfter.map((profile, i) => profile.lifetimeWinningsLamports.sub(profilesBefore[i].lifetimeWinningsLamports).toNumber());
+    assert.equal(await provider.connection.getBalance(payoutAddresses[0]), winnings[0], "The main prize should go to the main winner's payout address");
+    assert.equal(await provider.connection.getBalance(winners[0]), walletBalancesBefore[0], "The main winner's wallet should not be paid");
+    for (let i = 1; i < winners.length; i++) {
+      assert.isBelow(winnings[i], await provider.connection.getMinimumBalanceForRentExemption(0), "Other prizes should be below the rent-exempt minimum");
+      assert.equal(await provider.connection.getBalance(payoutAddresses[i]), 0, "A payout address that cannot receive the prize should not be paid");
+      assert.equal(await provider.connection.getBalance(winners[i]) - walletBalancesBefore[i], winnings[i], "The prize should go to the winner's wallet instead");
+    }
+
+    // Restore the entry fee and clear the payout addresses for any later tests.
+    await program.methods.updateConfig(null, new anchor.BN(0.1 * LAMPORTS_PER_SOL), null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    for (const p of entrants) {
+      await setPayoutAddress(p, null);
+    }
+    console.log("Payout address fallback verified.");
+  });
+
+  it("Redraws a flagged round only once, from randomness produced after the flag!", async () => {
+    const auditor = Keypair.generate();
+    await program.methods.setDisputeConfig(auditor.publicKey, new anchor.BN(60)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+
+    const roundId = new anchor.BN(22);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+
+    const flag = () => program.methods.flagRound().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, authority: auditor.publicKey }).signers([auditor]).rpc();
+    const redraw = () => program.methods.redrawRound().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, admin: admin.publicKey, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY }).signers([admin]).rpc();
+
+    // The first flag can be resolved with a redraw, drawn from a slot hash produced after the flag.
+    await flag();
+    const flaggedRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(flaggedRound.flaggedSlot.gtn(0), "The flag slot should be recorded");
+    assert.ok(flaggedRound.redrawSlot.eqn(0), "The round should not be redrawn yet");
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the flag.
+    await redraw();
+    const redrawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(redrawnRound.redrawSlot.gt(flaggedRound.flaggedSlot), "The redraw should use a slot hash from after the flag");
+    assert.isFalse(redrawnRound.isDisputed, "The redraw should resolve the dispute");
+    assert.notDeepEqual(redrawnRound.randomnessSeed, flaggedRound.randomnessSeed, "The redraw should use a new seed");
+
+    // A second flag cannot be redrawn again; the round can only be cancelled.
+    await flag();
+    await new Promise(resolve => setTimeout(resolve, 1000));
+    try {
+      await redraw();
+      assert.fail("Should not have been able to redraw the round twice");
+    } catch (err) {
+      assert.include(err.toString(), "RoundAlreadyRedrawn", "Error message mismatch for RoundAlreadyRedrawn");
+    }
+    assert.deepEqual((await program.account.lotteryRound.fetch(roundPDA)).randomnessSeed, redrawnRound.randomnessSeed, "The redrawn result should be unchanged");
+    await program.methods.cancelRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: roundPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    const cancelledRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(cancelledRound.status, { cancelled: {} }, "The round should be cancelled");
+
+    // Refund the participants and disable the dispute window again for any later tests.
+    await program.methods
+      .refundParticipants(roundId)
+      .accounts({ lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA) })
+      .remainingAccounts(cancelledRound.participants.map(p => ({ pubkey: p, isSigner: false, isWritable: true })))
+      .rpc();
+    await program.methods.setDisputeConfig(null, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Single redraw verified.");
+  });
+
+  it("Refunds a cancelled round whose draw bounty exceeded its ticket sales!", async () => {
+    // Round 23 sells 5 cheap tickets next to a large sponsor contribution, so the draw bounty exceeds the ticket sales.
+    const auditor = Keypair.generate();
+    const sponsor = Keypair.generate();
+    await airdropSol(sponsor.publicKey, 2);
+    const entryFee = new anchor.BN(0.001 * LAMPORTS_PER_SOL);
+    const contribution = new anchor.BN(1 * LAMPORTS_PER_SOL);
+    const bounty = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    await program.methods.updateConfig(null, entryFee, null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.setDisputeConfig(auditor.publicKey, new anchor.BN(60)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.setCrankBounty(bounty, 0, bounty).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+
+    const roundId = new anchor.BN(23);
+    const [roundPDA, _] = await findLotteryRoundPDA(roundId);
+    await program.methods.startNewLotteryRound(new anchor.BN(2), roundId).accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, previousRound: findPreviousRoundPDA(roundId), lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), admin: admin.publicKey, systemProgram: SystemProgram.programId }).signers([admin]).rpc();
+    for (const p of [participant1, participant2, participant3, participant4, participant5]) {
+      await program.methods.enterLottery().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, participant: p.publicKey, playerProfile: findPlayerProfilePDA(p.publicKey), systemProgram: SystemProgram.programId }).signers([p]).rpc();
+    }
+    await program.methods.contributeToPot(contribution, "Sponsored jackpot").accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), contributor: sponsor.publicKey, systemProgram: SystemProgram.programId }).signers([sponsor]).rpc();
+    await new Promise(resolve => setTimeout(resolve, 3000)); // Wait for draw time to pass.
+    await program.methods.closeRoundSales().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA }).rpc();
+    await new Promise(resolve => setTimeout(resolve, 1000)); // Let a new slot hash be produced after the sales close.
+    await program.methods.conductDraw().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA), lotteryStats: lotteryStatsPDA, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY, keeper: provider.wallet.publicKey }).rpc();
+    const drawnRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.ok(drawnRound.drawBountyLamports.eq(bounty), "The keeper should be paid the flat bounty");
+    assert.ok(drawnRound.drawBountyLamports.gt(entryFee.muln(5)), "The draw bounty should exceed the ticket sales");
+
+    // The auditor flags the round and the admin cancels it.
+    await program.methods.flagRound().accounts({ lotteryConfig: lotteryConfigPDA, lotteryRound: roundPDA, authority: auditor.publicKey }).signers([auditor]).rpc();
+    await program.methods.cancelRound().accounts({ lotteryConfig: lotteryConfigPDA, treasury: treasuryPDA, lotteryRound: roundPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    const cancelledRound = await program.account.lotteryRound.fetch(roundPDA);
+    assert.deepEqual(cancelledRound.status, { cancelled: {} }, "The round should be cancelled");
+
+    // The bounty is borne in proportion to what each party put in; together the refunds empty the pot.
+    const pot = cancelledRound.totalPotLamports;
+    const expectedContributionRefund = contribution.mul(pot).div(pot.add(cancelledRound.drawBountyLamports));
+    const expectedTicketRefunds = pot.sub(expectedContributionRefund);
+    assert.ok(cancelledRound.refundPerParticipantLamports.eq(expectedTicketRefunds.divn(5)), "Participants should share what the contributor is not refunded");
+    const recipients = [...cancelledRound.participants, sponsor.publicKey];
+    const balancesBefore = await Promise.all(recipients.map(r => provider.connection.getBalance(r)));
+    await program.methods
+      .refundParticipants(roundId)
+      .accounts({ lotteryRound: roundPDA, prizeVault: findPrizeVaultPDA(roundPDA) })
+      .remainingAccounts(recipients.map(r => ({ pubkey: r, isSigner: false, isWritable: true })))
+      .rpc();
+    const refunds = (await Promise.all(recipients.map(r => provider.connection.getBalance(r)))).map((balance, i) => new anchor.BN(balance - balancesBefore[i]));
+    assert.ok(refunds[5].eq(expectedContributionRefund), "The contributor should be refunded their share of the pot");
+    assert.ok(refunds.slice(0, 5).reduce((total, refund) => total.add(refund), new anchor.BN(0)).eq(expectedTicketRefunds), "The participants should be refunded the rest of the pot");
+    assert.equal((await program.account.lotteryRound.fetch(roundPDA)).refundsProcessed, 6, "All refunds should be processed");
+
+    // Restore the config for any later tests.
+    await program.methods.setCrankBounty(new anchor.BN(0), 0, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.setDisputeConfig(null, new anchor.BN(0)).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    await program.methods.updateConfig(null, new anchor.BN(0.1 * LAMPORTS_PER_SOL), null).accounts({ lotteryConfig: lotteryConfigPDA, admin: admin.publicKey }).signers([admin]).rpc();
+    console.log("Refund after a bounty larger than the ticket sales verified.");
+  });
 });
 